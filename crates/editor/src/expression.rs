@@ -0,0 +1,438 @@
+//! A small arithmetic language for computing a value per entity, such as `index * 2` or
+//! `hash(uid)`.
+//!
+//! Expressions can only do arithmetic on the variables and functions listed below, so they are
+//! safe to evaluate on input typed by the user. Their length and nesting are limited, and results
+//! which aren't finite are rejected.
+
+use ambient_ecs::EntityId;
+use anyhow::{bail, ensure, Context};
+use glam::Vec3;
+
+/// The longest accepted expression, in characters
+const MAX_LENGTH: usize = 256;
+/// The deepest accepted nesting of parentheses, calls and operators
+const MAX_DEPTH: usize = 32;
+
+/// The per-entity data an expression can refer to
+#[derive(Debug, Clone, Copy)]
+pub struct ExprContext {
+    /// The position of the entity in the selection
+    pub index: usize,
+    /// The number of selected entities
+    pub count: usize,
+    /// The current value of the field being set
+    pub value: f32,
+    pub position: Vec3,
+    pub uid: EntityId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Var {
+    Index,
+    Count,
+    Value,
+    X,
+    Y,
+    Z,
+    Uid,
+    Pi,
+}
+
+impl Var {
+    fn from_name(name: &str) -> Option<Var> {
+        Some(match name {
+            "index" | "i" => Var::Index,
+            "count" | "n" => Var::Count,
+            "value" | "v" => Var::Value,
+            "x" => Var::X,
+            "y" => Var::Y,
+            "z" => Var::Z,
+            "uid" => Var::Uid,
+            "pi" => Var::Pi,
+            _ => return None,
+        })
+    }
+
+    fn get(self, ctx: &ExprContext) -> f32 {
+        match self {
+            Var::Index => ctx.index as f32,
+            Var::Count => ctx.count as f32,
+            Var::Value => ctx.value,
+            Var::X => ctx.position.x,
+            Var::Y => ctx.position.y,
+            Var::Z => ctx.position.z,
+            // Only the low bits, which a float represents exactly
+            Var::Uid => (ctx.uid.0 & 0xff_ffff) as f32,
+            Var::Pi => std::f32::consts::PI,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Func {
+    Sin,
+    Cos,
+    Abs,
+    Floor,
+    Round,
+    Sqrt,
+    Min,
+    Max,
+    Hash,
+}
+
+impl Func {
+    fn from_name(name: &str) -> Option<Func> {
+        Some(match name {
+            "sin" => Func::Sin,
+            "cos" => Func::Cos,
+            "abs" => Func::Abs,
+            "floor" => Func::Floor,
+            "round" => Func::Round,
+            "sqrt" => Func::Sqrt,
+            "min" => Func::Min,
+            "max" => Func::Max,
+            "hash" => Func::Hash,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Func::Min | Func::Max => 2,
+            _ => 1,
+        }
+    }
+
+    fn call(self, args: &[f32]) -> f32 {
+        match self {
+            Func::Sin => args[0].sin(),
+            Func::Cos => args[0].cos(),
+            Func::Abs => args[0].abs(),
+            Func::Floor => args[0].floor(),
+            Func::Round => args[0].round(),
+            Func::Sqrt => args[0].sqrt(),
+            Func::Min => args[0].min(args[1]),
+            Func::Max => args[0].max(args[1]),
+            Func::Hash => hash(args[0]),
+        }
+    }
+}
+
+/// Maps a number to a well distributed value in `[0, 1)`
+fn hash(v: f32) -> f32 {
+    // splitmix64
+    let mut x = (v.to_bits() as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    (x >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(f32),
+    Var(Var),
+    Neg(Box<Node>),
+    Bin(Op, Box<Node>, Box<Node>),
+    Call(Func, Vec<Node>),
+}
+
+impl Node {
+    fn eval(&self, ctx: &ExprContext) -> f32 {
+        match self {
+            Node::Num(v) => *v,
+            Node::Var(var) => var.get(ctx),
+            Node::Neg(v) => -v.eval(ctx),
+            Node::Bin(op, a, b) => {
+                let (a, b) = (a.eval(ctx), b.eval(ctx));
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                    Op::Rem => a.rem_euclid(b),
+                    Op::Pow => a.powf(b),
+                }
+            }
+            Node::Call(func, args) => func.call(&args.iter().map(|v| v.eval(ctx)).collect::<Vec<_>>()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f32),
+    Ident(String),
+    Sym(char),
+}
+
+fn tokenize(src: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut num = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                num.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Num(num.parse().with_context(|| format!("Invalid number {num}"))?));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_') {
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+        } else if "+-*/%^(),".contains(c) {
+            tokens.push(Token::Sym(c));
+            chars.next();
+        } else {
+            bail!("Unexpected character {c:?}");
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, sym: char) -> bool {
+        if self.peek() == Some(&Token::Sym(sym)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, sym: char) -> anyhow::Result<()> {
+        ensure!(self.eat(sym), "Expected {sym:?}");
+        Ok(())
+    }
+
+    fn nested(&mut self, parse: impl FnOnce(&mut Self) -> anyhow::Result<Node>) -> anyhow::Result<Node> {
+        self.depth += 1;
+        ensure!(self.depth <= MAX_DEPTH, "The expression is nested too deeply");
+        let node = parse(self);
+        self.depth -= 1;
+        node
+    }
+
+    /// sum = product (('+' | '-') product)*
+    fn sum(&mut self) -> anyhow::Result<Node> {
+        self.nested(|p| {
+            let mut node = p.product()?;
+            loop {
+                let op = if p.eat('+') {
+                    Op::Add
+                } else if p.eat('-') {
+                    Op::Sub
+                } else {
+                    return Ok(node);
+                };
+                node = Node::Bin(op, Box::new(node), Box::new(p.product()?));
+            }
+        })
+    }
+
+    /// product = unary (('*' | '/' | '%') unary)*
+    fn product(&mut self) -> anyhow::Result<Node> {
+        let mut node = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else if self.eat('%') {
+                Op::Rem
+            } else {
+                return Ok(node);
+            };
+            node = Node::Bin(op, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    /// unary = '-' unary | atom ('^' unary)?
+    fn unary(&mut self) -> anyhow::Result<Node> {
+        self.nested(|p| {
+            if p.eat('-') {
+                return Ok(Node::Neg(Box::new(p.unary()?)));
+            }
+            let atom = p.atom()?;
+            if p.eat('^') {
+                Ok(Node::Bin(Op::Pow, Box::new(atom), Box::new(p.unary()?)))
+            } else {
+                Ok(atom)
+            }
+        })
+    }
+
+    /// atom = number | variable | function '(' sum (',' sum)* ')' | '(' sum ')'
+    fn atom(&mut self) -> anyhow::Result<Node> {
+        match self.peek().cloned() {
+            Some(Token::Num(v)) => {
+                self.pos += 1;
+                Ok(Node::Num(v))
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                if let Some(func) = Func::from_name(&name) {
+                    self.expect('(')?;
+                    let mut args = vec![self.sum()?];
+                    while self.eat(',') {
+                        args.push(self.sum()?);
+                    }
+                    self.expect(')')?;
+                    ensure!(args.len() == func.arity(), "{name} takes {} arguments, got {}", func.arity(), args.len());
+                    Ok(Node::Call(func, args))
+                } else {
+                    Var::from_name(&name).map(Node::Var).with_context(|| format!("Unknown name {name}"))
+                }
+            }
+            Some(Token::Sym('(')) => {
+                self.pos += 1;
+                let node = self.sum()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            Some(Token::Sym(c)) => bail!("Unexpected {c:?}"),
+            None => bail!("Unexpected end of expression"),
+        }
+    }
+}
+
+/// A parsed expression, which can be evaluated for each entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr(Node);
+
+impl Expr {
+    pub fn parse(src: &str) -> anyhow::Result<Expr> {
+        ensure!(src.chars().count() <= MAX_LENGTH, "The expression is longer than {MAX_LENGTH} characters");
+
+        let mut parser = Parser { tokens: tokenize(src)?, pos: 0, depth: 0 };
+        let node = parser.sum()?;
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected {token:?}");
+        }
+        Ok(Expr(node))
+    }
+
+    pub fn eval(&self, ctx: &ExprContext) -> anyhow::Result<f32> {
+        let value = self.0.eval(ctx);
+        ensure!(value.is_finite(), "The result for entity {} is not a finite number", ctx.index);
+        Ok(value)
+    }
+}
+
+/// Lists the names an expression can use, for showing next to the input
+pub const EXPR_HELP: &str =
+    "Variables: index, count, value, x, y, z, uid, pi. Functions: sin, cos, abs, floor, round, sqrt, min, max, hash";
+
+#[cfg(test)]
+mod test {
+    use ambient_ecs::EntityId;
+    use glam::Vec3;
+
+    use super::{Expr, ExprContext, MAX_DEPTH, MAX_LENGTH};
+
+    fn ctx() -> ExprContext {
+        ExprContext { index: 3, count: 10, value: 2., position: Vec3::new(1., 2., 3.), uid: EntityId::null() }
+    }
+
+    fn eval(src: &str) -> f32 {
+        Expr::parse(src).unwrap().eval(&ctx()).unwrap()
+    }
+
+    #[test]
+    fn operators_follow_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.);
+        assert_eq!(eval("(1 + 2) * 3"), 9.);
+        assert_eq!(eval("10 - 4 - 3"), 3.);
+        assert_eq!(eval("7 % 4 * 2"), 6.);
+        assert_eq!(eval("2 * 3 ^ 2"), 18.);
+        // Powers bind to the right
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.);
+        assert_eq!(eval("index * 2 + value"), 8.);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-2 ^ 2"), -4.);
+        assert_eq!(eval("(-2) ^ 2"), 4.);
+        assert_eq!(eval("2 * -3"), -6.);
+        assert_eq!(eval("--1"), 1.);
+        assert_eq!(eval("-x - y"), -3.);
+    }
+
+    #[test]
+    fn functions_check_their_arity() {
+        assert_eq!(eval("min(1, 2) + max(1, 2)"), 3.);
+        assert!(Expr::parse("min(1)").is_err());
+        assert!(Expr::parse("max(1, 2, 3)").is_err());
+        assert!(Expr::parse("sin(1, 2)").is_err());
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        assert!(Expr::parse("foo").is_err());
+        assert!(Expr::parse("foo(1)").is_err());
+        assert!(Expr::parse("index * bar").is_err());
+    }
+
+    #[test]
+    fn length_and_depth_are_limited() {
+        let longest = format!("1{}", "+1".repeat((MAX_LENGTH - 1) / 2));
+        assert!(longest.len() <= MAX_LENGTH);
+        assert!(Expr::parse(&longest).is_ok());
+        assert!(Expr::parse(&format!("{longest}+1")).is_err());
+
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        // Each parenthesis nests a sum and an operand
+        assert!(Expr::parse(&nested(MAX_DEPTH / 4)).is_ok());
+        assert!(Expr::parse(&nested(MAX_DEPTH)).is_err());
+
+        // The outer sum and its operand take two levels, and each minus one more
+        let negated = |count: usize| format!("{}1", "-".repeat(count));
+        assert_eq!(eval(&negated(MAX_DEPTH - 2)), 1.);
+        let err = Expr::parse(&negated(MAX_DEPTH - 1)).unwrap_err();
+        assert!(err.to_string().contains("nested too deeply"), "{err}");
+        assert!(Expr::parse(&negated(MAX_DEPTH + 1)).is_err());
+    }
+
+    #[test]
+    fn non_finite_results_are_rejected() {
+        for src in ["1 / 0", "sqrt(-1)", "0 / 0", "10 ^ 100"] {
+            assert!(Expr::parse(src).unwrap().eval(&ctx()).is_err(), "{src}");
+        }
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        for src in ["1.2.3", "1 +", "(1", "1)", "2 3", "", "1 $ 2", "min(1,)"] {
+            assert!(Expr::parse(src).is_err(), "{src}");
+        }
+    }
+}
@@ -1,23 +1,37 @@
 use ambient_core::{
-    self, selectable, snap_to_ground,
-    transform::{get_world_transform, rotation, scale, translation},
+    self, asset_cache,
+    bounding::calc_world_bounding_recursive,
+    hierarchy::{add_child, children, parent},
+    name, selectable, snap_to_ground,
+    transform::{get_world_transform, local_to_parent, mesh_to_local, rotation, scale, translation},
 };
-use ambient_ecs::{components, Entity, EntityId, World};
+use ambient_ecs::{components, query, ComponentDesc, Entity, EntityId, World};
 use ambient_intent::{use_old_state, IntentContext, IntentRegistry};
 use ambient_network::get_player_by_user_id;
-use ambient_physics::{collider::collider_shapes_convex, main_physics_scene, physx::rigid_actor, PxShapeUserData};
+use ambient_physics::{
+    collider::{collider, collider_shapes_convex, collider_type, ColliderDef, ColliderType},
+    main_physics_scene,
+    physx::rigid_actor,
+    PxShapeUserData,
+};
 use anyhow::Context;
-use glam::{Mat4, Vec3, Vec3Swizzles};
+use glam::{vec3, Mat4, Quat, Vec2, Vec3, Vec3Swizzles};
 use itertools::{izip, process_results, Itertools};
 
-use ambient_std::shapes::{Ray, Shape, AABB};
+use ambient_std::{
+    asset_cache::AsyncAssetKeyExt,
+    asset_url::AssetUrl,
+    shapes::{Ray, Shape, AABB},
+};
 use ambient_terrain::get_terrain_height;
 use ordered_float::OrderedFloat;
 use physxx::{PxActor, PxQueryFilterData, PxRaycastCallback, PxTransform, PxUserData};
+use rand::{distributions::WeightedIndex, prelude::Distribution, SeedableRng};
+use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
 
-use crate::{selection, ui::entity_editor::EntityComponentChange, Selection};
-use ambient_prefab::prefab_from_url;
+use crate::{anchor, selection, transform_pinned, ui::entity_editor::EntityComponentChange, Selection};
+use ambient_prefab::{prefab_from_url, prefab_main_url, spawned, PrefabFromUrl};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct IntentTransformRevert {
@@ -26,6 +40,140 @@ pub struct IntentTransformRevert {
     snap_to_ground: Option<f32>,
 }
 
+/// The world transform of the parent of `id`, which is the frame of its local grid
+pub fn parent_frame(world: &World, id: EntityId) -> Option<Mat4> {
+    get_world_transform(world, world.get(id, parent()).ok()?).ok()
+}
+
+/// Rounds `v` to multiples of `step`, to clean up floating-point drift such as 2.9999998
+pub fn round_to_step(v: Vec3, step: f32) -> Vec3 {
+    let inverse = (1. / step).round();
+    if step < 1. && (1. / step - inverse).abs() < 1e-4 {
+        // Dividing by a whole number lands on the float closest to the rounded value, where
+        // multiplying by a decimal step would not
+        (v * inverse).round() / inverse
+    } else {
+        (v / step).round() * step
+    }
+}
+
+/// Rounds the translation and scale of `transform` to multiples of `step`. The scale is never
+/// rounded to zero, and the rotation is kept as is
+pub fn round_transform(transform: Mat4, step: f32) -> Mat4 {
+    let (scl, rot, pos) = transform.to_scale_rotation_translation();
+    Mat4::from_scale_rotation_translation(round_to_step(scl, step).max(Vec3::splat(step)), rot, round_to_step(pos, step))
+}
+
+/// How far away an object's origin can be from the placement point to be snapped to
+const ORIGIN_SNAP_RADIUS: f32 = 5.0;
+
+/// Finds the world position of the object closest to `point`, not counting `exclude`
+fn nearest_origin(world: &World, point: Vec3, exclude: &[EntityId]) -> Option<Vec3> {
+    query(selectable())
+        .iter(world, None)
+        .map(|(id, _)| id)
+        .filter(|id| !exclude.contains(id))
+        .filter_map(|id| Some(get_world_transform(world, id).ok()?.transform_point3(Vec3::ZERO)))
+        .filter(|pos| pos.distance(point) <= ORIGIN_SNAP_RADIUS)
+        .min_by_key(|pos| OrderedFloat(pos.distance_squared(point)))
+}
+
+/// Moves the origin of `id` to the world position `origin`, and moves its children and mesh the
+/// other way so that they stay in place. Returns the previous world origin
+fn set_origin(world: &mut World, id: EntityId, origin: Vec3) -> anyhow::Result<Vec3> {
+    let transform = get_world_transform(world, id).context("No transform")?;
+    let old_origin = transform.transform_point3(Vec3::ZERO);
+    // The new origin in the local space of the entity
+    let offset = transform.inverse().transform_point3(origin);
+    let to_new_origin = Mat4::from_translation(-offset);
+
+    let pos = world.get(id, translation()).unwrap_or_default();
+    let rot = world.get(id, rotation()).unwrap_or_default();
+    let scl = world.get(id, scale()).unwrap_or(Vec3::ONE);
+    world.add_component(id, translation(), pos + rot * (scl * offset))?;
+
+    if let Ok(mesh_transform) = world.get(id, mesh_to_local()) {
+        world.set(id, mesh_to_local(), to_new_origin * mesh_transform)?;
+    }
+    for child in world.get_cloned(id, children()).unwrap_or_default() {
+        if let Ok(pos) = world.get(child, translation()) {
+            world.set(child, translation(), pos - offset)?;
+        } else if let Ok(transform) = world.get(child, local_to_parent()) {
+            world.set(child, local_to_parent(), to_new_origin * transform)?;
+        }
+    }
+
+    Ok(old_origin)
+}
+
+/// Sets the rotation and scale of `id`, and moves its mesh and children the other way so that they
+/// stay in place. Returns the previous rotation and scale.
+///
+/// A child with its own rotation is skewed by a non-uniform change of scale, which its
+/// translation, rotation and scale can't hold, so it is only kept in place approximately
+fn set_basis(world: &mut World, id: EntityId, new_rot: Quat, new_scl: Vec3) -> anyhow::Result<(Quat, Vec3)> {
+    let rot = world.get(id, rotation()).unwrap_or_default();
+    let scl = world.get(id, scale()).unwrap_or(Vec3::ONE);
+    anyhow::ensure!(scl.abs().min_element() > 0. && new_scl.abs().min_element() > 0., "Can't apply a zero scale");
+    let bake = Mat4::from_scale_rotation_translation(new_scl, new_rot, Vec3::ZERO).inverse()
+        * Mat4::from_scale_rotation_translation(scl, rot, Vec3::ZERO);
+
+    world.add_component(id, rotation(), new_rot)?;
+    world.add_component(id, scale(), new_scl)?;
+
+    if let Ok(mesh_transform) = world.get(id, mesh_to_local()) {
+        world.set(id, mesh_to_local(), bake * mesh_transform)?;
+    }
+    for child in world.get_cloned(id, children()).unwrap_or_default() {
+        if world.has_component(child, translation()) {
+            let (scl, rot, pos) = (bake * local_transform(world, child)).to_scale_rotation_translation();
+            world.set(child, translation(), pos)?;
+            world.add_component(child, rotation(), rot)?;
+            world.add_component(child, scale(), scl)?;
+        } else if let Ok(transform) = world.get(child, local_to_parent()) {
+            world.set(child, local_to_parent(), bake * transform)?;
+        }
+    }
+
+    Ok((rot, scl))
+}
+
+/// The transform of `id` relative to its parent, or to the world when it has none
+fn local_transform(world: &World, id: EntityId) -> Mat4 {
+    Mat4::from_scale_rotation_translation(
+        world.get(id, scale()).unwrap_or(Vec3::ONE),
+        world.get(id, rotation()).unwrap_or_default(),
+        world.get(id, translation()).unwrap_or_default(),
+    )
+}
+
+/// Moves `id` under `new_parent`, or to the top level if None, with `local` as its transform
+/// relative to the new parent
+fn set_parent(world: &mut World, id: EntityId, new_parent: Option<EntityId>, local: Mat4) -> anyhow::Result<()> {
+    if let Some(children) = world.get(id, parent()).ok().and_then(|old_parent| world.get_mut(old_parent, children()).ok()) {
+        children.retain(|&v| v != id);
+    }
+
+    let (scl, rot, pos) = local.to_scale_rotation_translation();
+    world.add_component(id, translation(), pos)?;
+    world.add_component(id, rotation(), rot)?;
+    world.add_component(id, scale(), scl)?;
+    match new_parent {
+        Some(new_parent) => {
+            world.add_component(id, parent(), new_parent)?;
+            world.add_component(id, local_to_parent(), local)?;
+            add_child(world, new_parent, id)?;
+        }
+        None => world.remove_components(id, vec![parent().into(), local_to_parent().into()])?,
+    }
+    Ok(())
+}
+
+/// Leaves out the entities whose transform is pinned
+fn unpinned(world: &World, targets: Vec<EntityId>) -> Vec<EntityId> {
+    targets.into_iter().filter(|&id| !world.has_component(id, transform_pinned())).collect_vec()
+}
+
 fn undo_transform(ctx: IntentContext, undo_state: Vec<IntentTransformRevert>) -> anyhow::Result<()> {
     let world = ctx.world;
     for state in undo_state {
@@ -46,6 +194,47 @@ fn undo_transform(ctx: IntentContext, undo_state: Vec<IntentTransformRevert>) ->
     Ok(())
 }
 
+/// The components which the object at `object_url` sets on its instances, if it is loaded
+fn object_components(world: &World, object_url: &str) -> Option<Vec<ComponentDesc>> {
+    let url = AssetUrl::parse(prefab_main_url(object_url.to_string())).ok()?;
+    let object = PrefabFromUrl(url).is_loaded(world.resource_opt(asset_cache())?)?.ok()?;
+    let base = *object.resource_opt(children())?.first()?;
+    Some(object.clone_entity(base).ok()?.components())
+}
+
+/// Respawns the entity as an instance of the object, at the same place under the same parent.
+///
+/// The uid is kept and everything which the old object didn't set carries over, such as the name,
+/// the tags and the list of children, so that the selection and the hierarchy stay valid
+fn replace_with_object(world: &mut World, id: EntityId, data: &Entity, object_url: String) {
+    let owned = data.get_ref(prefab_from_url()).and_then(|url| object_components(world, url)).unwrap_or_default();
+    let mut new = data.clone();
+    new.filter(&|desc| !owned.contains(&desc));
+    // The transform and the hierarchy belong to the instance, even where the object has them too
+    for desc in [translation().desc(), rotation().desc(), scale().desc(), parent().desc(), local_to_parent().desc(), children().desc()] {
+        if let Some(entry) = data.get_entry(desc) {
+            new.set_entry(entry.clone());
+        }
+    }
+    new.remove_self(spawned());
+    let new = new
+        .with_default_if_empty(translation())
+        .with_default_if_empty(rotation())
+        .with_if_empty(scale(), Vec3::ONE)
+        .with_default(selectable())
+        .with(prefab_from_url(), object_url);
+
+    world.despawn(id);
+    world.spawn_with_id(id, new);
+}
+
+fn restore_entities(world: &mut World, old: Vec<(EntityId, Entity)>) {
+    for (id, data) in old {
+        world.despawn(id);
+        world.spawn_with_id(id, data);
+    }
+}
+
 components!("editor", {
     /// Moves many entities collectively to another point, while keeping their relative positions
     /// to each other
@@ -61,12 +250,43 @@ components!("editor", {
     intent_select_undo: Selection,
     intent_spawn_object_undo: (EntityId, bool, Selection),
     intent_spawn_object: IntentSpawnObject,
+    intent_spawn_anchor: IntentSpawnAnchor,
+    intent_spawn_anchor_undo: (EntityId, bool, Selection),
     intent_duplicate: IntentDuplicate,
     intent_duplicate_undo: Vec<EntityId>,
+    intent_spawn_at: IntentSpawnAt,
+    /// Each copy, along with the anchor it was parented to
+    intent_spawn_at_undo: Vec<(EntityId, Option<EntityId>)>,
+    intent_stamp: IntentStamp,
+    /// The copies spawned by the stamp run
+    intent_stamp_undo: Vec<EntityId>,
+    /// Replaces every instance of the first object url with the second
+    intent_replace_object: (String, String),
+    /// The replaced instances, to be respawned with their original uids
+    intent_replace_object_undo: Vec<(EntityId, Entity)>,
+    intent_scatter_objects: IntentScatterObjects,
+    /// The replaced entities, to be respawned with their original uids
+    intent_scatter_objects_undo: Vec<(EntityId, Entity)>,
+    /// Moves the origin of each entity to the paired world position, without moving what the
+    /// entity looks like
+    intent_set_origin: Vec<(EntityId, Vec3)>,
+    /// The previous world origin of each entity
+    intent_set_origin_undo: Vec<(EntityId, Vec3)>,
+    intent_group: IntentGroup,
+    /// Each new parent, along with the previous parent and local transform of each of its members
+    intent_group_undo: Vec<(EntityId, Vec<(EntityId, Option<EntityId>, Mat4)>)>,
+    intent_apply_transform: IntentApplyTransform,
+    /// The previous rotation and scale of each entity
+    intent_apply_transform_undo: Vec<(EntityId, Quat, Vec3)>,
     intent_delete: Vec<EntityId>,
-    intent_delete_undo: (World, Selection),
+    /// The deleted entities along with their original uids, so that they can be restored and
+    /// reselected on undo
+    intent_delete_undo: (Vec<(EntityId, Entity)>, Selection),
     intent_component_change: (EntityId, EntityComponentChange),
     intent_component_change_undo: (EntityId, EntityComponentChange),
+    /// Changes to many entities as a single undo step
+    intent_component_changes: Vec<(EntityId, EntityComponentChange)>,
+    intent_component_changes_undo: Vec<(EntityId, EntityComponentChange)>,
 });
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -82,6 +302,79 @@ pub struct IntentDuplicate {
     pub entities: Vec<EntityId>,
     pub new_uids: Vec<EntityId>,
     pub select: bool,
+    /// The world transform of each copy. If None, the copies keep the transforms of their originals
+    pub transforms: Option<Vec<Mat4>>,
+}
+
+impl IntentDuplicate {
+    /// Copies each entity to the paired world transform in a single intent, so that a bulk
+    /// operation such as an array is undone at once rather than copy by copy
+    pub fn batch(copies: impl IntoIterator<Item = (EntityId, Mat4)>, select: bool) -> Self {
+        let (entities, transforms): (Vec<_>, Vec<_>) = copies.into_iter().unzip();
+        let new_uids = entities.iter().map(|_| EntityId::new()).collect_vec();
+        Self { entities, new_uids, select, transforms: Some(transforms) }
+    }
+}
+
+/// Spawns a copy of `template` at each of `anchors`, such as to populate spawn points
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntentSpawnAt {
+    pub template: EntityId,
+    pub anchors: Vec<EntityId>,
+    pub new_uids: Vec<EntityId>,
+    /// Take the rotation and scale of each anchor. Otherwise the copies are unrotated and unscaled
+    pub inherit_transform: bool,
+    /// Make each copy a child of its anchor, so that it follows the anchor when moved
+    pub parent_to_anchor: bool,
+    pub select: bool,
+}
+
+/// Stamps copies of `template` at each of `transforms`, ordered copy by copy.
+///
+/// The same `new_uids` are reused as a stamp run grows or shrinks, which lets each push collapse
+/// into the previous one. Copies past the end of `transforms` are removed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntentStamp {
+    pub template: Vec<EntityId>,
+    pub new_uids: Vec<EntityId>,
+    pub transforms: Vec<Mat4>,
+}
+
+/// Resets the rotation and or scale of each target, moving them into its mesh and children so that
+/// nothing moves, like applying a transform in a modelling tool
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntentApplyTransform {
+    pub targets: Vec<EntityId>,
+    pub rotation: bool,
+    pub scale: bool,
+}
+
+/// Parents each group of entities to a new empty entity at the middle of the group, keeping
+/// where the members are in the world
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntentGroup {
+    /// The uid of each new parent, along with its members
+    pub groups: Vec<(EntityId, Vec<EntityId>)>,
+    /// Select the new parents
+    pub select: bool,
+}
+
+/// Replaces each target with an object picked at random from the palette, keeping their
+/// transforms
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntentScatterObjects {
+    pub targets: Vec<EntityId>,
+    /// Each object url along with its weight
+    pub palette: Vec<(String, f32)>,
+    pub seed: u64,
+}
+
+/// Picks an index into `weights` for each of `count` entities. The same seed always gives the same
+/// picks, so that the panel can preview what the intent will do
+pub fn palette_picks(count: usize, weights: &[f32], seed: u64) -> anyhow::Result<Vec<usize>> {
+    let dist = WeightedIndex::new(weights).context("The palette has no objects with a weight above zero")?;
+    let mut rng = Pcg64::seed_from_u64(seed);
+    Ok((0..count).map(|_| dist.sample(&mut rng)).collect())
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -92,6 +385,16 @@ pub struct IntentSpawnObject {
     pub select: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IntentSpawnAnchor {
+    pub entity_id: EntityId,
+    pub position: Vec3,
+    pub select: bool,
+}
+
+/// The radius of the picking collider of an anchor, which makes it selectable without a mesh
+const ANCHOR_PICKING_RADIUS: f32 = 0.25;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum MovePosition {
     Raycast { ray: Ray },
@@ -142,6 +445,54 @@ impl Snapping {
 pub struct IntentTranslate {
     pub targets: Vec<EntityId>,
     pub position: Vec3,
+    pub surface: SurfaceConstraint,
+    /// Round the resulting positions to multiples of this
+    pub round_to: Option<f32>,
+}
+
+/// How translated entities follow the scene geometry below them
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurfaceConstraint {
+    /// Move freely on the constraint plane
+    #[default]
+    Free,
+    /// Rest the bottom of each entity on the surface below it
+    KeepOnSurface,
+    /// Rest on the surface, and tilt the up axis of each entity to the surface normal
+    AlignToNormal,
+}
+
+impl SurfaceConstraint {
+    pub const ALL: [SurfaceConstraint; 3] = [SurfaceConstraint::Free, SurfaceConstraint::KeepOnSurface, SurfaceConstraint::AlignToNormal];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SurfaceConstraint::Free => "Move freely",
+            SurfaceConstraint::KeepOnSurface => "Keep on surface",
+            SurfaceConstraint::AlignToNormal => "Keep on surface and align to normal",
+        }
+    }
+}
+
+/// How far above an entity to start looking for the surface below it
+const SURFACE_PROBE_HEIGHT: f32 = 1000.;
+
+/// Moves `pos` onto the scene geometry below it, not counting `exclude`, keeping `base_offset`
+/// between the surface and `pos`. The rotation is tilted to the surface normal when `align` is set
+fn onto_surface(world: &mut World, pos: Vec3, rot: Quat, base_offset: f32, exclude: &[EntityId], align: bool) -> (Vec3, Quat) {
+    let ray = Ray::new(pos + Vec3::Z * SURFACE_PROBE_HEIGHT, -Vec3::Z);
+    let intersect = match find_world_intersection_without_entities(world, ray, exclude, SURFACE_PROBE_HEIGHT * 2.) {
+        Some(v) => v,
+        None => return (pos, rot),
+    };
+
+    if align {
+        let up = rot * Vec3::Z;
+        let rot = Quat::from_rotation_arc(up, intersect.normal.normalize_or_zero()) * rot;
+        (intersect.point + intersect.normal * base_offset, rot)
+    } else {
+        (vec3(pos.x, pos.y, intersect.point.z + base_offset), rot)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,6 +501,31 @@ pub struct IntentPlaceRay {
     pub ray: Ray,
     /// Apply snapping relative to the object the ray intersected
     pub snap: Option<f32>,
+    /// Move the midpoint of the targets onto the origin of the closest other object instead
+    pub snap_to_origins: bool,
+    /// Round the resulting positions to multiples of this
+    pub round_to: Option<f32>,
+    /// Snap on the local grid of the parent of the targets rather than relative to the surface
+    pub snap_in_parent: bool,
+    /// Turn the up axis of the targets to the world axis closest to the normal of the surface, so
+    /// that moving from a floor onto a wall stands them off the wall
+    pub auto_orient: bool,
+    /// Horizontal positions the point hit snaps onto when close, such as where construction
+    /// lines cross. These take precedence over `snap`
+    pub snap_points: Vec<Vec2>,
+}
+
+/// How close horizontally a point must be to one of the snap points to snap onto it
+pub const SNAP_POINT_RADIUS: f32 = 0.5;
+
+/// Moves `p` horizontally onto the closest of `points` within [`SNAP_POINT_RADIUS`]
+pub fn snap_to_points(points: &[Vec2], p: Vec3) -> Option<Vec3> {
+    points
+        .iter()
+        .map(|point| (point, point.distance(p.xy())))
+        .filter(|(_, distance)| *distance < SNAP_POINT_RADIUS)
+        .min_by_key(|(_, distance)| OrderedFloat(*distance))
+        .map(|(point, _)| point.extend(p.z))
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
@@ -165,6 +541,18 @@ impl Default for SelectMode {
     }
 }
 
+/// The world axis closest to `normal`, keeping its sign
+pub fn dominant_axis(normal: Vec3) -> Vec3 {
+    let abs = normal.abs();
+    if abs.z >= abs.x && abs.z >= abs.y {
+        Vec3::Z * normal.z.signum()
+    } else if abs.x >= abs.y {
+        Vec3::X * normal.x.signum()
+    } else {
+        Vec3::Y * normal.y.signum()
+    }
+}
+
 fn axis_aligned_plane(normal: Vec3) -> (Vec3, Vec3) {
     assert!(normal.is_normalized(), "Normal is not normalized");
     if normal.dot(Vec3::Z).abs() < 0.99 {
@@ -242,9 +630,10 @@ pub fn register_intents(reg: &mut IntentRegistry) {
     reg.register(
         intent_place_ray(),
         intent_place_ray_undo(),
-        |ctx, IntentPlaceRay { targets, ray, snap }| {
+        |ctx, IntentPlaceRay { targets, ray, snap, snap_to_origins, round_to, snap_in_parent, auto_orient, snap_points }| {
             profiling::scope!("handle_intent_move");
             let world = ctx.world;
+            let targets = unpinned(world, targets);
 
             enum SurfaceOffset {
                 Keep { _normal: Vec3 },
@@ -274,22 +663,40 @@ pub fn register_intents(reg: &mut IntentRegistry) {
 
             let intersect = find_world_intersection_without_entities(world, ray, &ids, 500.);
 
-            let target = if let Some(mut intersect) = intersect {
+            let origin = match &intersect {
+                Some(intersect) if snap_to_origins => nearest_origin(world, intersect.point, &targets),
+                _ => None,
+            };
+
+            // Rotates the targets around their midpoint when auto-orienting
+            let mut orient = Quat::IDENTITY;
+            let target = if let Some(origin) = origin {
+                // Aligned exactly, so don't clip out of the surface
+                origin
+            } else if let Some(mut intersect) = intersect {
                 use ambient_terrain::terrain_world_cell;
 
                 // The terrain should always offset upwards
                 if world.get(intersect.id, terrain_world_cell()).is_ok() {
                     intersect.normal = Vec3::Z
                 }
+                if auto_orient {
+                    orient = Quat::from_rotation_arc(Vec3::Z, dominant_axis(intersect.normal.normalize_or_zero()));
+                }
 
                 let subject_transform = get_world_transform(world, intersect.id).expect("Missing position for entity");
                 let (_, _, subject_pos) = subject_transform.to_scale_rotation_translation();
 
                 // log::info!("Snap: {snap:?}");
-                let target = match snap {
-                    None => intersect.point,
+                let frame = ids.first().filter(|_| snap_in_parent).and_then(|&&id| parent_frame(world, id));
+                let target = match (snap, frame) {
+                    (None, _) => intersect.point,
+                    // Snap on the grid of the rotated parent, so that siblings line up within it
+                    (Some(snap), Some(frame)) => {
+                        frame.transform_point3((frame.inverse().transform_point3(intersect.point) / snap).round() * snap)
+                    }
                     // (Some(size), true) => Snapping { size, origin: Vec3::ZERO, mode: SnappingShape::Volume }.snap(intersect.point),
-                    Some(snap) => {
+                    (Some(snap), None) => {
                         let (tangent, bitangent) = axis_aligned_plane(intersect.normal);
                         Snapping {
                             size: snap,
@@ -300,6 +707,8 @@ pub fn register_intents(reg: &mut IntentRegistry) {
                     }
                 };
 
+                let target = snap_to_points(&snap_points, intersect.point).unwrap_or(target);
+
                 // Once the snapped intersection point has been
                 // established, move out to clip to the side of
                 // the manipulated objects
@@ -318,13 +727,14 @@ pub fn register_intents(reg: &mut IntentRegistry) {
                         let (scl, rot, pos) = transform.to_scale_rotation_translation();
 
                         // World space position
-                        let new_pos = pos - midpoint + target;
+                        let new_pos = orient * (pos - midpoint) + target;
+                        let new_pos = round_to.map_or(new_pos, |step| round_to_step(new_pos, step));
                         tracing::debug!(?midpoint, "Moving {uid} {pos} => {new_pos}");
 
                         update_snap_to_ground(world, id, pos);
 
                         world.set_if_changed(id, translation(), new_pos).unwrap();
-                        world.set_if_changed(id, rotation(), rot).unwrap();
+                        world.set_if_changed(id, rotation(), orient * rot).unwrap();
                         world.set_if_changed(id, scale(), scl).unwrap();
 
                         Ok(IntentTransformRevert { snap_to_ground: old_snap_to_ground, transform, uid })
@@ -339,9 +749,10 @@ pub fn register_intents(reg: &mut IntentRegistry) {
     reg.register(
         intent_translate(),
         intent_translate_undo(),
-        |ctx, IntentTranslate { targets, position }| {
+        |ctx, IntentTranslate { targets, position, surface, round_to }| {
             profiling::scope!("handle_intent_move");
             let world = ctx.world;
+            let targets = unpinned(world, targets);
 
             enum SurfaceOffset {
                 Keep { _normal: Vec3 },
@@ -366,6 +777,7 @@ pub fn register_intents(reg: &mut IntentRegistry) {
                 .fold(Vec3::ZERO, |acc, x| acc + x)
                 / transforms.len().max(1) as f32;
 
+            let exclude = targets.clone();
             izip!(targets, ids, transforms)
                 .map(|(uid, id, transform): (_, _, Mat4)| {
                     let old_snap_to_ground = world.get(id, snap_to_ground()).ok();
@@ -374,6 +786,14 @@ pub fn register_intents(reg: &mut IntentRegistry) {
 
                     // World space position
                     let new_pos = pos - midpoint + position;
+                    let (new_pos, rot) = match surface {
+                        SurfaceConstraint::Free => (new_pos, rot),
+                        SurfaceConstraint::KeepOnSurface | SurfaceConstraint::AlignToNormal => {
+                            let base_offset = calc_world_bounding_recursive(world, id).map(|v| pos.z - v.min.z).unwrap_or_default();
+                            onto_surface(world, new_pos, rot, base_offset, &exclude, surface == SurfaceConstraint::AlignToNormal)
+                        }
+                    };
+                    let new_pos = round_to.map_or(new_pos, |step| round_to_step(new_pos, step));
                     tracing::debug!(?midpoint, "Moving {uid} {pos} => {new_pos}");
 
                     update_snap_to_ground(world, id, pos);
@@ -395,10 +815,14 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         intent_set_transform_undo(),
         |ctx, intent| {
             let world = ctx.world;
-            intent
+            let entities = intent
                 .entities
                 .iter()
                 .zip_eq(intent.transforms)
+                .filter(|(&id, _)| !world.has_component(id, transform_pinned()))
+                .collect_vec();
+            entities
+                .into_iter()
                 .map(|(&id, transform)| {
                     let old_transform = get_world_transform(world, id).context("No transform")?;
 
@@ -545,16 +969,70 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         },
         use_old_state,
     );
+    reg.register(
+        intent_spawn_anchor(),
+        intent_spawn_anchor_undo(),
+        |ctx, IntentSpawnAnchor { entity_id, position, select }| {
+            let user_id = ctx.user_id;
+            let world = ctx.world;
+
+            let data = Entity::new()
+                .with(name(), "Anchor".to_string())
+                .with(translation(), position)
+                .with_default(rotation())
+                .with(scale(), Vec3::ONE)
+                .with_default(selectable())
+                .with_default(anchor())
+                .with(collider(), ColliderDef::Sphere { radius: ANCHOR_PICKING_RADIUS, center: Vec3::ZERO })
+                .with(collider_type(), ColliderType::Picking);
+            world.spawn_with_id(entity_id, data);
+
+            let player_entity = get_player_by_user_id(world, user_id).context("Player not found")?;
+            let old_selection = world.get_ref(player_entity, selection()).cloned().context("Failed to get selection")?;
+
+            if select {
+                world.set(player_entity, selection(), Selection::new(vec![entity_id])).context("Failed to set selection")?;
+            }
+            Ok((entity_id, select, old_selection))
+        },
+        move |ctx, (id, select, old_selection)| {
+            let user_id = ctx.user_id.to_string();
+            let world = ctx.world;
+            world.despawn(id);
+            if select {
+                if let Some(player_entity) = get_player_by_user_id(world, &user_id) {
+                    world.set(player_entity, selection(), old_selection).ok();
+                }
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
     reg.register(
         intent_duplicate(),
         intent_duplicate_undo(),
-        |ctx, IntentDuplicate { entities, new_uids, select }| {
+        |ctx, IntentDuplicate { entities, new_uids, select, transforms }| {
             let world = ctx.world;
             let player_entity = get_player_by_user_id(world, ctx.user_id).context("Player not found")?;
+            anyhow::ensure!(entities.len() == new_uids.len(), "Expected a uid for each copy");
+
+            // Clone everything up front, so that a missing entity fails the whole batch rather
+            // than leaving the copies made before it
+            let originals = entities.iter().map(|&id| Ok(world.clone_entity(id)?.serializable())).collect::<anyhow::Result<Vec<_>>>()?;
 
-            for (id, new_id) in entities.iter().zip(new_uids.iter()) {
-                let data = world.clone_entity(*id)?.serializable();
+            for (i, (mut data, new_id)) in originals.into_iter().zip(new_uids.iter()).enumerate() {
+                let transform = transforms.as_ref().and_then(|v| v.get(i));
+                if let Some(transform) = transform {
+                    let (scl, rot, pos) = transform.to_scale_rotation_translation();
+                    data.set(translation(), pos);
+                    data.set(rotation(), rot);
+                    data.set(scale(), scl);
+                }
                 world.spawn_with_id(*new_id, data);
+
+                if let Some(transform) = transform {
+                    update_snap_to_ground(world, *new_id, transform.w_axis.truncate());
+                }
             }
 
             // Set the selection to the new objects
@@ -573,15 +1051,266 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         },
         use_old_state,
     );
+    reg.register(
+        intent_spawn_at(),
+        intent_spawn_at_undo(),
+        |ctx, IntentSpawnAt { template, anchors, new_uids, inherit_transform, parent_to_anchor, select }| {
+            let world = ctx.world;
+            let player_entity = get_player_by_user_id(world, ctx.user_id).context("Player not found")?;
+            anyhow::ensure!(anchors.len() == new_uids.len(), "Expected a uid for each anchor");
+
+            let mut spawned = Vec::new();
+            for (&anchor_id, &new_id) in anchors.iter().zip(&new_uids) {
+                let anchor_transform = get_world_transform(world, anchor_id).context("Anchor has no transform")?;
+                let transform = if inherit_transform {
+                    anchor_transform
+                } else {
+                    Mat4::from_translation(anchor_transform.transform_point3(Vec3::ZERO))
+                };
+
+                // Children are placed relative to their parent
+                let local = if parent_to_anchor { anchor_transform.inverse() * transform } else { transform };
+                let (scl, rot, pos) = local.to_scale_rotation_translation();
+                let mut data = world.clone_entity(template)?.serializable();
+                data.set(translation(), pos);
+                data.set(rotation(), rot);
+                data.set(scale(), scl);
+                if parent_to_anchor {
+                    data.set(parent(), anchor_id);
+                    data.set(local_to_parent(), local);
+                }
+                world.spawn_with_id(new_id, data);
+
+                if parent_to_anchor {
+                    add_child(world, anchor_id, new_id)?;
+                    spawned.push((new_id, Some(anchor_id)));
+                } else {
+                    update_snap_to_ground(world, new_id, pos);
+                    spawned.push((new_id, None));
+                }
+            }
+
+            if select {
+                world.set(player_entity, selection(), Selection::new(new_uids.clone())).ok();
+            }
+
+            Ok(spawned)
+        },
+        |ctx, spawned| {
+            let world = ctx.world;
+            for (id, anchor_id) in spawned {
+                if let Some(children) = anchor_id.and_then(|anchor_id| world.get_mut(anchor_id, children()).ok()) {
+                    children.retain(|&v| v != id);
+                }
+                world.despawn(id);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_stamp(),
+        intent_stamp_undo(),
+        |ctx, IntentStamp { template, new_uids, transforms }| {
+            let world = ctx.world;
+            anyhow::ensure!(!template.is_empty(), "Nothing to stamp");
+            // Checked up front, so that a run is never left half stamped
+            anyhow::ensure!(template.iter().all(|&id| world.exists(id)), "A stamped entity no longer exists");
+
+            let mut spawned = Vec::new();
+            for (i, &new_id) in new_uids.iter().enumerate() {
+                let transform = match transforms.get(i) {
+                    Some(v) => v,
+                    None => {
+                        world.despawn(new_id);
+                        continue;
+                    }
+                };
+
+                if !world.exists(new_id) {
+                    let data = world.clone_entity(template[i % template.len()])?.serializable();
+                    world.spawn_with_id(new_id, data);
+                    spawned.push(new_id);
+                }
+
+                let (scl, rot, pos) = transform.to_scale_rotation_translation();
+                world.set(new_id, translation(), pos)?;
+                world.set(new_id, rotation(), rot)?;
+                world.set(new_id, scale(), scl)?;
+                update_snap_to_ground(world, new_id, pos);
+            }
+
+            Ok(spawned)
+        },
+        |ctx, ids| {
+            let world = ctx.world;
+            for id in ids {
+                world.despawn(id);
+            }
+            Ok(())
+        },
+        // Each push of a run may spawn more copies, all of which are removed by the undo
+        |_, old_state, new_arg, new_state| (new_arg.clone(), old_state.iter().chain(new_state).copied().unique().collect_vec()),
+    );
+    reg.register(
+        intent_replace_object(),
+        intent_replace_object_undo(),
+        |ctx, (from, to)| {
+            let world = ctx.world;
+            anyhow::ensure!(from != to, "The object would be replaced with itself");
+
+            let instances = query((selectable(), prefab_from_url()))
+                .iter(world, None)
+                .filter(|(_, (_, url))| **url == from)
+                .map(|(id, _)| id)
+                .collect_vec();
+            let old = instances.iter().map(|&id| Ok((id, world.clone_entity(id)?.serializable()))).collect::<anyhow::Result<Vec<_>>>()?;
+
+            tokio::task::block_in_place(|| {
+                for (id, data) in &old {
+                    replace_with_object(world, *id, data, to.clone());
+                }
+            });
+
+            Ok(old)
+        },
+        |ctx, old| {
+            restore_entities(ctx.world, old);
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_scatter_objects(),
+        intent_scatter_objects_undo(),
+        |ctx, IntentScatterObjects { targets, palette, seed }| {
+            let world = ctx.world;
+            let weights = palette.iter().map(|(_, weight)| *weight).collect_vec();
+            let picks = palette_picks(targets.len(), &weights, seed)?;
+            let old = targets.iter().map(|&id| Ok((id, world.clone_entity(id)?.serializable()))).collect::<anyhow::Result<Vec<_>>>()?;
+
+            tokio::task::block_in_place(|| {
+                for ((id, data), pick) in old.iter().zip(picks) {
+                    replace_with_object(world, *id, data, palette[pick].0.clone());
+                }
+            });
+
+            Ok(old)
+        },
+        |ctx, old| {
+            restore_entities(ctx.world, old);
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_set_origin(),
+        intent_set_origin_undo(),
+        |ctx, origins| {
+            let world = ctx.world;
+            let origins = origins.into_iter().filter(|&(id, _)| !world.has_component(id, transform_pinned())).collect_vec();
+            origins.into_iter().map(|(id, origin)| Ok((id, set_origin(world, id, origin)?))).collect()
+        },
+        |ctx, old_origins| {
+            let world = ctx.world;
+            for (id, origin) in old_origins {
+                set_origin(world, id, origin)?;
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_group(),
+        intent_group_undo(),
+        |ctx, IntentGroup { groups, select }| {
+            let world = ctx.world;
+            let player_entity = get_player_by_user_id(world, ctx.user_id).context("Player not found")?;
+
+            // Looked up front, so that a missing entity fails the whole batch
+            let groups = groups
+                .into_iter()
+                .map(|(group_id, members)| {
+                    let transforms = members
+                        .iter()
+                        .map(|&id| get_world_transform(world, id).context("Entity has no transform"))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    Ok((group_id, members, transforms))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let mut grouped = Vec::new();
+            for (group_id, members, transforms) in groups {
+                let midpoint = transforms.iter().map(|t| t.transform_point3(Vec3::ZERO)).sum::<Vec3>() / transforms.len().max(1) as f32;
+                let data = Entity::new()
+                    .with(name(), "Group".to_string())
+                    .with(translation(), midpoint)
+                    .with_default(rotation())
+                    .with(scale(), Vec3::ONE)
+                    .with_default(selectable());
+                world.spawn_with_id(group_id, data);
+
+                let to_group = Mat4::from_translation(-midpoint);
+                let mut old = Vec::new();
+                for (id, transform) in members.into_iter().zip(transforms) {
+                    old.push((id, world.get(id, parent()).ok(), local_transform(world, id)));
+                    set_parent(world, id, Some(group_id), to_group * transform)?;
+                }
+                grouped.push((group_id, old));
+            }
+
+            if select {
+                world.set(player_entity, selection(), Selection::new(grouped.iter().map(|(id, _)| *id).collect_vec())).ok();
+            }
+
+            Ok(grouped)
+        },
+        |ctx, grouped| {
+            let world = ctx.world;
+            for (group_id, members) in grouped {
+                for (id, old_parent, local) in members {
+                    set_parent(world, id, old_parent, local)?;
+                }
+                world.despawn(group_id);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_apply_transform(),
+        intent_apply_transform_undo(),
+        |ctx, IntentApplyTransform { targets, rotation: apply_rotation, scale: apply_scale }| {
+            let world = ctx.world;
+            unpinned(world, targets)
+                .into_iter()
+                .map(|id| {
+                    let new_rot = if apply_rotation { Quat::IDENTITY } else { world.get(id, rotation()).unwrap_or_default() };
+                    let new_scl = if apply_scale { Vec3::ONE } else { world.get(id, scale()).unwrap_or(Vec3::ONE) };
+                    let (rot, scl) = set_basis(world, id, new_rot, new_scl)?;
+                    Ok((id, rot, scl))
+                })
+                .collect()
+        },
+        |ctx, old| {
+            let world = ctx.world;
+            for (id, rot, scl) in old {
+                set_basis(world, id, rot, scl)?;
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
     reg.register(
         intent_delete(),
         intent_delete_undo(),
         |ctx, entities| {
             let world = ctx.world;
             let player_entity = get_player_by_user_id(world, ctx.user_id).context("Player not found")?;
-            let old = World::from_entities(world, entities.clone(), true);
+            // Entities which are already gone, such as ones deleted by another client, are skipped
+            let old = entities.iter().filter_map(|&id| Some((id, world.clone_entity(id).ok()?.serializable()))).collect_vec();
 
-            for &id in entities.iter() {
+            for &(id, _) in &old {
                 world.despawn(id);
             }
 
@@ -595,9 +1324,17 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         },
         |ctx, (entities, old_selection)| {
             let world = ctx.world;
-            let _ids = entities.spawn_into_world(world, None);
+            let mut restored = Selection::default();
+            // Respawn with the original uids so that the selection and any references stay valid
+            for (id, data) in entities {
+                world.spawn_with_id(id, data);
+                restored.add(id);
+            }
+
             if let Some(player_entity) = get_player_by_user_id(world, ctx.user_id) {
-                world.set(player_entity, selection(), old_selection).ok();
+                let mut selection_after = old_selection;
+                selection_after.union(&restored);
+                world.set(player_entity, selection(), selection_after).ok();
             }
             Ok(())
         },
@@ -617,6 +1354,22 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         },
         use_old_state,
     );
+    reg.register(
+        intent_component_changes(),
+        intent_component_changes_undo(),
+        |ctx, changes| {
+            let world = ctx.world;
+            Ok(changes.into_iter().map(|(id, change)| (id, change.apply_to_entity(world, id))).collect())
+        },
+        |ctx, reverts| {
+            let world = ctx.world;
+            for (id, revert) in reverts.into_iter().rev() {
+                revert.apply_to_entity(world, id);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
 
     ambient_terrain::intents::register_intents(reg);
     // Box::new(common_intent_systems()),
@@ -683,3 +1436,41 @@ fn set_snap_to_ground(world: &mut World, id: EntityId, height: f32) {
     // Modify the transformed z value
     world.add_component(id, snap_to_ground(), height).expect("Invalid entity");
 }
+
+#[cfg(test)]
+mod test {
+    use ambient_core::{
+        hierarchy::{children, parent},
+        name,
+        transform::{local_to_parent, translation},
+    };
+    use ambient_ecs::{Entity, World};
+    use ambient_prefab::prefab_from_url;
+    use glam::vec3;
+
+    use super::replace_with_object;
+
+    #[test]
+    fn replacing_a_parent_keeps_the_hierarchy() {
+        ambient_core::init_all_components();
+        ambient_prefab::init_components();
+
+        let mut world = World::new("replace_with_object");
+        let tower = Entity::new()
+            .with(translation(), vec3(1., 2., 3.))
+            .with(name(), "Tower".to_string())
+            .with(prefab_from_url(), "assets/old".to_string())
+            .spawn(&mut world);
+        let child = Entity::new().with(parent(), tower).with_default(local_to_parent()).spawn(&mut world);
+        world.add_component(tower, children(), vec![child]).unwrap();
+
+        let data = world.clone_entity(tower).unwrap().serializable();
+        replace_with_object(&mut world, tower, &data, "assets/new".to_string());
+
+        assert_eq!(world.get_ref(tower, children()).unwrap(), &vec![child]);
+        assert_eq!(world.get(child, parent()).unwrap(), tower);
+        assert_eq!(world.get_ref(tower, name()).unwrap(), "Tower");
+        assert_eq!(world.get(tower, translation()).unwrap(), vec3(1., 2., 3.));
+        assert_eq!(world.get_ref(tower, prefab_from_url()).unwrap(), "assets/new");
+    }
+}
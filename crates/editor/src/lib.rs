@@ -1,15 +1,60 @@
-use ambient_ecs::{components, EntityId};
+use ambient_ecs::{components, Debuggable, Description, EntityId, Name, Networked, Store};
+use glam::{Quat, Vec3};
 use std::iter::Cloned;
 
 #[macro_use]
 extern crate closure;
+pub mod expression;
 pub mod intents;
+pub mod recording;
 pub mod rpc;
 pub mod ui;
 
 components!("editor", {
     selection: Selection,
     prev_selection: Selection,
+    @[
+        Debuggable, Networked, Store,
+        Name["Transform pinned"],
+        Description["If attached, the editor will not move, rotate or scale this entity. It can still be selected and its other components edited."]
+    ]
+    transform_pinned: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Anchor"],
+        Description["An entity without a mesh, used as a group parent or reference point. The editor draws a glyph for it."]
+    ]
+    anchor: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Organization color"],
+        Description["A color for grouping content in the editor. Selected entities are outlined in this color."]
+    ]
+    organization_color: Vec3,
+    @[
+        Debuggable, Networked, Store,
+        Name["Camera bookmarks"],
+        Description["Named editor viewpoints, saved with the scene."]
+    ]
+    camera_bookmarks: Vec<CameraBookmark>,
+    @[
+        Debuggable, Networked, Store,
+        Name["Review marker"],
+        Description["Flags an entity for review, with an optional note. The editor draws a box around it whether it is selected or not."]
+    ]
+    review_marker: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Reference image"],
+        Description["An image the editor lays in the viewport to recreate a layout from, saved with the scene."]
+    ]
+    reference_image: ReferenceImage,
+    @[
+        Debuggable, Networked, Store,
+        Name["Editor macros"],
+        Description["Named sequences of recorded editor operations, saved with the scene."]
+    ]
+    editor_macros: Vec<recording::EditorMacro>,
 });
 
 pub fn init_all_components() {
@@ -19,6 +64,34 @@ pub fn init_all_components() {
 
 pub const GRID_SIZE: f32 = 1.0;
 
+/// A named viewpoint of the editor camera
+#[derive(Debug, PartialEq, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+/// An image laid on a horizontal plane in the viewport for recreating a layout, such as a blueprint
+/// or concept art. It is only shown in the editor
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReferenceImage {
+    pub url: Option<String>,
+    pub opacity: f32,
+    /// The center of the image
+    pub position: Vec3,
+    /// The width of the image in meters, the height follows its aspect ratio
+    pub width: f32,
+    /// The rotation around the up axis, in degrees
+    pub yaw: f32,
+}
+
+impl Default for ReferenceImage {
+    fn default() -> Self {
+        Self { url: None, opacity: 0.5, position: Vec3::ZERO, width: 20., yaw: 0. }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct Selection {
     pub entities: Vec<EntityId>,
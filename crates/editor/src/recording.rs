@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use ambient_ecs::{query, EntityId, World};
+use ambient_intent::{intent_reverted, intent_success, intent_timestamp, intent_user_id, server_push_intent};
+use ambient_network::{client::GameRpcArgs, server::SharedServerState};
+use chrono::{DateTime, Utc};
+use glam::{Mat4, Vec3};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::intents::{
+    intent_delete, intent_duplicate, intent_set_transform, intent_spawn_anchor, intent_spawn_object, intent_stamp, intent_translate,
+    IntentDuplicate, IntentSpawnAnchor, IntentSpawnObject, IntentStamp, IntentTransform, IntentTranslate,
+};
+
+/// An editor operation captured by the macro recorder
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum MacroStep {
+    SpawnObject(IntentSpawnObject),
+    SpawnAnchor(IntentSpawnAnchor),
+    Duplicate(IntentDuplicate),
+    Stamp(IntentStamp),
+    Translate(IntentTranslate),
+    SetTransform(IntentTransform),
+    Delete(Vec<EntityId>),
+}
+
+/// A named sequence of recorded operations, which can be replayed at an offset
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EditorMacro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+/// Maps the entities spawned while recording to the entities spawned by the playback. Entities
+/// which existed before the recording are acted on directly
+#[derive(Default)]
+struct Remap(HashMap<EntityId, EntityId>);
+
+impl Remap {
+    fn spawned(&mut self, id: EntityId) -> EntityId {
+        *self.0.entry(id).or_insert_with(EntityId::new)
+    }
+
+    fn get(&self, id: EntityId) -> EntityId {
+        self.0.get(&id).copied().unwrap_or(id)
+    }
+
+    fn all(&self, ids: &[EntityId]) -> Vec<EntityId> {
+        ids.iter().map(|&id| self.get(id)).collect_vec()
+    }
+}
+
+fn offset_transforms(transforms: &[Mat4], offset: Vec3) -> Vec<Mat4> {
+    transforms.iter().map(|&transform| Mat4::from_translation(offset) * transform).collect_vec()
+}
+
+impl MacroStep {
+    /// Reads the operation of an applied intent, if it is one that can be replayed
+    fn from_intent(world: &World, id: EntityId) -> Option<MacroStep> {
+        if let Ok(v) = world.get_cloned(id, intent_spawn_object()) {
+            Some(MacroStep::SpawnObject(v))
+        } else if let Ok(v) = world.get_cloned(id, intent_spawn_anchor()) {
+            Some(MacroStep::SpawnAnchor(v))
+        } else if let Ok(v) = world.get_cloned(id, intent_duplicate()) {
+            Some(MacroStep::Duplicate(v))
+        } else if let Ok(v) = world.get_cloned(id, intent_stamp()) {
+            Some(MacroStep::Stamp(v))
+        } else if let Ok(v) = world.get_cloned(id, intent_translate()) {
+            Some(MacroStep::Translate(v))
+        } else if let Ok(v) = world.get_cloned(id, intent_set_transform()) {
+            Some(MacroStep::SetTransform(v))
+        } else {
+            world.get_cloned(id, intent_delete()).ok().map(MacroStep::Delete)
+        }
+    }
+
+    /// Moves the step by `offset`, and gives the entities it spawns new uids
+    fn relocate(self, remap: &mut Remap, offset: Vec3) -> MacroStep {
+        match self {
+            MacroStep::SpawnObject(v) => {
+                MacroStep::SpawnObject(IntentSpawnObject { entity_id: remap.spawned(v.entity_id), position: v.position + offset, ..v })
+            }
+            MacroStep::SpawnAnchor(v) => {
+                MacroStep::SpawnAnchor(IntentSpawnAnchor { entity_id: remap.spawned(v.entity_id), position: v.position + offset, ..v })
+            }
+            MacroStep::Duplicate(v) => MacroStep::Duplicate(IntentDuplicate {
+                entities: remap.all(&v.entities),
+                new_uids: v.new_uids.iter().map(|&id| remap.spawned(id)).collect_vec(),
+                select: v.select,
+                transforms: v.transforms.map(|transforms| offset_transforms(&transforms, offset)),
+            }),
+            MacroStep::Stamp(v) => MacroStep::Stamp(IntentStamp {
+                template: remap.all(&v.template),
+                new_uids: v.new_uids.iter().map(|&id| remap.spawned(id)).collect_vec(),
+                transforms: offset_transforms(&v.transforms, offset),
+            }),
+            MacroStep::Translate(v) => {
+                MacroStep::Translate(IntentTranslate { targets: remap.all(&v.targets), position: v.position + offset, ..v })
+            }
+            MacroStep::SetTransform(v) => MacroStep::SetTransform(IntentTransform {
+                entities: remap.all(&v.entities),
+                transforms: offset_transforms(&v.transforms, offset),
+                ..v
+            }),
+            MacroStep::Delete(entities) => MacroStep::Delete(remap.all(&entities)),
+        }
+    }
+
+    async fn push(self, state: SharedServerState, user_id: String) {
+        match self {
+            MacroStep::SpawnObject(v) => server_push_intent(state, intent_spawn_object(), v, user_id, None).await,
+            MacroStep::SpawnAnchor(v) => server_push_intent(state, intent_spawn_anchor(), v, user_id, None).await,
+            MacroStep::Duplicate(v) => server_push_intent(state, intent_duplicate(), v, user_id, None).await,
+            MacroStep::Stamp(v) => server_push_intent(state, intent_stamp(), v, user_id, None).await,
+            MacroStep::Translate(v) => server_push_intent(state, intent_translate(), v, user_id, None).await,
+            MacroStep::SetTransform(v) => server_push_intent(state, intent_set_transform(), v, user_id, None).await,
+            MacroStep::Delete(v) => server_push_intent(state, intent_delete(), v, user_id, None).await,
+        }
+    }
+}
+
+/// Marks the start of a recording, as the time of the latest operation the player has applied.
+///
+/// The operations are stamped by the client that applied them, so the mark is taken from them
+/// rather than from a clock which may disagree
+pub async fn rpc_start_recording(args: GameRpcArgs, _: ()) -> Option<DateTime<Utc>> {
+    let state = args.state.lock();
+    let world = state.get_player_world(&args.user_id)?;
+
+    let latest = query((intent_user_id(), intent_timestamp()))
+        .iter(world, None)
+        .filter(|(_, (user_id, _))| **user_id == args.user_id)
+        .map(|(_, (_, &timestamp))| timestamp)
+        .max();
+
+    Some(latest.unwrap_or(DateTime::<Utc>::MIN_UTC))
+}
+
+/// Returns the operations the player has applied after the mark returned by
+/// [`rpc_start_recording`], in order. Undone operations and operations which can't be replayed
+/// are left out
+pub async fn rpc_recorded_steps(args: GameRpcArgs, since: DateTime<Utc>) -> Option<Vec<MacroStep>> {
+    let state = args.state.lock();
+    let world = state.get_player_world(&args.user_id)?;
+
+    let steps = query((intent_user_id(), intent_timestamp()))
+        .incl(intent_success())
+        .excl(intent_reverted())
+        .iter(world, None)
+        .filter(|(_, (user_id, timestamp))| **user_id == args.user_id && **timestamp > since)
+        .sorted_by_key(|(_, (_, timestamp))| **timestamp)
+        .filter_map(|(id, _)| MacroStep::from_intent(world, id))
+        .collect_vec();
+
+    Some(steps)
+}
+
+/// Replays recorded operations moved by `offset`, as one undo step each.
+///
+/// Returns the number of replayed operations
+pub async fn rpc_play_macro(args: GameRpcArgs, (steps, offset): (Vec<MacroStep>, Vec3)) -> Option<usize> {
+    let mut remap = Remap::default();
+    let count = steps.len();
+    for step in steps {
+        step.relocate(&mut remap, offset).push(args.state.clone(), args.user_id.clone()).await;
+    }
+
+    Some(count)
+}
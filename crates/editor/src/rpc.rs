@@ -1,5 +1,7 @@
-use ambient_ecs::{ArchetypeFilter, Entity, EntityId};
-use ambient_intent::server_push_intent;
+use std::collections::HashSet;
+
+use ambient_ecs::{query, ArchetypeFilter, Entity, EntityId, World};
+use ambient_intent::{intent, server_push_intent};
 use ambient_network::client::GameRpcArgs;
 use ambient_physics::visualization::{visualize_collider, visualizing};
 use ambient_physics::{
@@ -7,15 +9,23 @@ use ambient_physics::{
     intersection::{intersect_frustum, raycast_filtered, rpc_pick, RaycastFilter},
 };
 use ambient_rpc::RpcRegistry;
-use ambient_std::{shapes::Ray, unwrap_log_err};
+use ambient_std::{
+    shapes::{Ray, AABB},
+    unwrap_log_err,
+};
 use anyhow::Context;
 use bitflags::bitflags;
-use glam::{vec3, Vec3};
+use glam::{vec3, Mat4, Vec3, Vec4, Vec4Swizzles};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::intents::{intent_select, SelectMode};
 use crate::Selection;
-use ambient_core::selectable;
+use ambient_core::{
+    bounding::calc_world_bounding_recursive,
+    hierarchy::{children, despawn_recursive},
+    selectable, tags,
+};
 
 bitflags! {
     #[derive(Serialize, Deserialize)]
@@ -47,16 +57,53 @@ pub fn register_rpcs(reg: &mut RpcRegistry<GameRpcArgs>) {
     reg.register(rpc_toggle_visualize_colliders);
     // reg.register(rpc_save);
     reg.register(rpc_spawn);
+    reg.register(rpc_snapshot);
+    reg.register(rpc_restore_snapshot);
+    reg.register(crate::recording::rpc_start_recording);
+    reg.register(crate::recording::rpc_recorded_steps);
+    reg.register(crate::recording::rpc_play_macro);
     // reg.register(rpc_teleport_player);
 }
 
+fn has_tag(world: &World, id: EntityId, tag: &str) -> bool {
+    world.get_ref(id, tags()).map_or(false, |tags| tags.iter().any(|v| v == tag))
+}
+
+/// Whether any part of `bounds` can be inside the view frustum of `proj_view`.
+///
+/// Boxes near the corners of the frustum may be counted as inside, which errs on the side of
+/// selecting
+fn in_frustum(proj_view: Mat4, bounds: &AABB) -> bool {
+    let rows = [proj_view.row(0), proj_view.row(1), proj_view.row(2), proj_view.row(3)];
+    // Both standard and reversed depth map the frustum to 0 <= z <= w
+    let planes: [Vec4; 6] = [rows[3] + rows[0], rows[3] - rows[0], rows[3] + rows[1], rows[3] - rows[1], rows[2], rows[3] - rows[2]];
+
+    planes.iter().all(|plane| {
+        // The corner of the box furthest along the plane normal
+        let normal = plane.xyz();
+        let corner = Vec3::select(normal.cmpge(Vec3::ZERO), bounds.max, bounds.min);
+        normal.dot(corner) + plane.w >= 0.
+    })
+}
+
 pub async fn rpc_select(args: GameRpcArgs, (method, mode): (SelectMethod, SelectMode)) {
     let entities = {
         let mut state = args.state.lock();
         let world = unwrap_log_err!(state.get_player_world_mut(&args.user_id).context("No player world"));
         match method {
-            SelectMethod::Frustum(frustum) => {
-                intersect_frustum(world, &frustum).into_iter().filter(|id| world.has_component(*id, selectable())).collect()
+            SelectMethod::Frustum(frustum, tag) => intersect_frustum(world, &frustum)
+                .into_iter()
+                .filter(|id| world.has_component(*id, selectable()))
+                .filter(|id| tag.as_ref().map_or(true, |tag| has_tag(world, *id, tag)))
+                .collect(),
+            SelectMethod::Visible(proj_view, tag) => query(selectable())
+                .iter(world, None)
+                .map(|(id, _)| id)
+                .filter(|id| calc_world_bounding_recursive(world, *id).map_or(false, |bounds| in_frustum(proj_view, &bounds)))
+                .filter(|id| tag.as_ref().map_or(true, |tag| has_tag(world, *id, tag)))
+                .collect(),
+            SelectMethod::Tag(tag) => {
+                query(selectable()).iter(world, None).map(|(id, _)| id).filter(|id| has_tag(world, *id, &tag)).collect()
             }
             SelectMethod::Ray(ray) => {
                 if let Some((entity, _)) = raycast_filtered(
@@ -152,6 +199,52 @@ pub async fn rpc_toggle_visualize_colliders(args: GameRpcArgs, entities: Vec<Ent
 //     };
 //     log_warning!(save_world(stored_map, map_path).await);
 // }
+/// The selectable entities of `world` along with all their descendants, each once
+fn scene_entities(world: &World) -> Vec<EntityId> {
+    let mut ids = query(selectable()).iter(world, None).map(|(id, _)| id).collect_vec();
+    let mut seen = ids.iter().copied().collect::<HashSet<_>>();
+    let mut i = 0;
+    while let Some(&id) = ids.get(i) {
+        if let Ok(children) = world.get_ref(id, children()) {
+            ids.extend(children.iter().copied().filter(|&child| seen.insert(child)));
+        }
+        i += 1;
+    }
+    ids
+}
+
+/// Serializes the selectable entities of the player's world and their descendants, keeping their
+/// ids, so that the scene can be recovered after a crash
+pub async fn rpc_snapshot(args: GameRpcArgs, _: ()) -> Option<String> {
+    let state = args.state.lock();
+    let world = state.get_player_world(&args.user_id)?;
+    let entities =
+        scene_entities(world).into_iter().filter_map(|id| Some((id, world.clone_entity(id).ok()?))).collect::<Vec<(EntityId, Entity)>>();
+    Some(unwrap_log_err!(serde_json::to_string(&entities)))
+}
+
+/// Replaces the selectable entities of the player's world with a snapshot from [rpc_snapshot].
+///
+/// The entities get their ids back, so parents, children and the selection keep pointing at them.
+/// The undo history is cleared, as it describes the scene from before the restore.
+///
+/// Returns the number of restored entities
+pub async fn rpc_restore_snapshot(args: GameRpcArgs, snapshot: String) -> Option<usize> {
+    let entities: Vec<(EntityId, Entity)> = unwrap_log_err!(serde_json::from_str(&snapshot));
+
+    let mut state = args.state.lock();
+    let world = state.get_player_world_mut(&args.user_id)?;
+    for id in query(selectable()).iter(world, None).map(|(id, _)| id).collect_vec() {
+        despawn_recursive(world, id);
+    }
+    for id in query(intent()).iter(world, None).map(|(id, _)| id).collect_vec() {
+        world.despawn(id);
+    }
+
+    let count = entities.into_iter().map(|(id, entity)| world.spawn_with_id(id, entity)).filter(|&spawned| spawned).count();
+    Some(count)
+}
+
 pub async fn rpc_spawn(args: GameRpcArgs, entity_data: Entity) -> Option<EntityId> {
     let mut state = args.state.lock();
     let world = state.get_player_world_mut(&args.user_id)?;
@@ -177,7 +270,13 @@ pub async fn rpc_spawn(args: GameRpcArgs, entity_data: Entity) -> Option<EntityI
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum SelectMethod {
-    Frustum([Vec3; 8]),
+    /// The entities inside the frustum, optionally only those with a tag
+    Frustum([Vec3; 8], Option<String>),
+    /// The entities whose bounds are in view of the camera with this projection view matrix,
+    /// optionally only those with a tag
+    Visible(Mat4, Option<String>),
+    /// All entities with a tag
+    Tag(String),
     Ray(Ray),
     Manual(Selection),
 }
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use ambient_core::{asset_cache, runtime};
+use ambient_ecs::World;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::{client::GameClient, log_network_result};
+use ambient_std::{asset_cache::SyncAssetKeyExt, cb, download_asset::AssetsCacheDir, Cb};
+use ambient_ui::{use_interval_deps, Alert};
+
+use super::EditorPrefs;
+use crate::rpc::{rpc_restore_snapshot, rpc_snapshot};
+
+/// Where the latest snapshot is kept. The file is removed when the editor closes cleanly, so
+/// finding it on startup means that the previous session ended unexpectedly
+fn recovery_path(world: &World) -> PathBuf {
+    AssetsCacheDir.get(world.resource(asset_cache())).join("editor_recovery.json")
+}
+
+/// Periodically snapshots the scene to a recovery file, and offers to restore the snapshot of a
+/// session which crashed or disconnected
+#[element_component]
+pub fn Autosave(hooks: &mut Hooks, set_screen: Cb<dyn Fn(Option<Element>) + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let runtime = hooks.world.resource(runtime()).clone();
+    let path = recovery_path(hooks.world);
+
+    // Read before the first autosave overwrites it
+    let (recovered, _) = hooks.use_state_with({
+        let path = path.clone();
+        move |_| std::fs::read_to_string(path).ok()
+    });
+
+    hooks.use_spawn({
+        let game_client = game_client.clone();
+        let runtime = runtime.clone();
+        let path = path.clone();
+        move |_| {
+            if let Some(snapshot) = recovered {
+                let on_restore = cb(move |_: &mut World| {
+                    let game_client = game_client.clone();
+                    let snapshot = snapshot.clone();
+                    runtime.spawn(async move {
+                        log_network_result!(game_client.rpc(rpc_restore_snapshot, snapshot).await);
+                    });
+                });
+
+                set_screen(Some(
+                    Alert::new(
+                        "The editor did not shut down cleanly. Replace the scene with the autosaved snapshot?",
+                        set_screen.clone(),
+                        Some(on_restore),
+                        Some(cb(|_: &mut World| {})),
+                    )
+                    .el(),
+                ));
+            }
+
+            Box::new(move |_| {
+                std::fs::remove_file(path).ok();
+            })
+        }
+    });
+
+    use_interval_deps(hooks, prefs.autosave_interval, false, prefs.autosave_interval, move |_| {
+        let game_client = game_client.clone();
+        let path = path.clone();
+        runtime.spawn(async move {
+            let snapshot = match game_client.rpc(rpc_snapshot, ()).await {
+                Ok(Some(v)) => v,
+                Ok(None) => return,
+                Err(err) => {
+                    tracing::warn!("Failed to snapshot the scene: {err:?}");
+                    return;
+                }
+            };
+
+            if let Err(err) = tokio::fs::write(&path, snapshot).await {
+                tracing::warn!("Failed to write the recovery file {path:?}: {err:?}");
+            }
+        });
+    });
+
+    Element::new()
+}
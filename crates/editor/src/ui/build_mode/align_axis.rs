@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::client::GameClient;
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, DropdownSelect, EditorRow, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use glam::{Mat4, Quat, Vec3};
+use itertools::Itertools;
+
+use super::{
+    batch::{use_batch_guard, PendingBatch},
+    transform::current_transforms,
+};
+use crate::intents::{intent_set_transform, IntentTransform, TerrainOffset};
+
+/// The axes which can be aligned, world +Z being up and +Y north
+const SIGNED_AXES: [(Vec3, &str); 6] =
+    [(Vec3::X, "+X"), (Vec3::NEG_X, "-X"), (Vec3::Y, "+Y (north)"), (Vec3::NEG_Y, "-Y"), (Vec3::Z, "+Z (up)"), (Vec3::NEG_Z, "-Z")];
+const UP: usize = 4;
+const NORTH: usize = 2;
+
+/// Rotates each of `transforms` around its own origin by the smallest rotation which turns its
+/// `local` axis onto the `world` axis
+fn align_transforms(transforms: &[Mat4], local: Vec3, world: Vec3) -> Vec<Mat4> {
+    transforms
+        .iter()
+        .map(|transform| {
+            let (scl, rot, pos) = transform.to_scale_rotation_translation();
+            let current = rot * local;
+            Mat4::from_scale_rotation_translation(scl, (Quat::from_rotation_arc(current, world) * rot).normalize(), pos)
+        })
+        .collect_vec()
+}
+
+/// The largest angle in degrees that any of `transforms` is turned by to align them
+fn max_angle(transforms: &[Mat4], local: Vec3, world: Vec3) -> f32 {
+    transforms
+        .iter()
+        .map(|transform| {
+            let (_, rot, _) = transform.to_scale_rotation_translation();
+            (rot * local).angle_between(world).to_degrees()
+        })
+        .fold(0., f32::max)
+}
+
+/// Turns each selected entity so that one of its local axes points along a world axis, such as to
+/// stand tilted imports upright or face props north, as one undo step
+#[element_component]
+pub fn AlignAxisPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let guard_batch = use_batch_guard(hooks);
+    let initial = hooks.use_memo_with(targets.clone(), |_, targets| current_transforms(&game_client.game_state.lock().world, targets));
+    let (local, set_local) = hooks.use_state(UP);
+    let (world, set_world) = hooks.use_state(UP);
+
+    let (local_axis, local_label) = SIGNED_AXES[local];
+    let (world_axis, world_label) = SIGNED_AXES[world];
+    let transforms = align_transforms(&initial.transforms, local_axis, world_axis);
+    let angle = max_angle(&initial.transforms, local_axis, world_axis);
+
+    let axis_select = |index: usize, set: Cb<dyn Fn(usize) + Sync + Send>| {
+        DropdownSelect {
+            content: Text::el(SIGNED_AXES[index].1),
+            on_select: cb(move |index| set(index)),
+            items: SIGNED_AXES.iter().map(|(_, label)| Text::el(*label)).collect(),
+            inline: false,
+        }
+        .el()
+    };
+    let preset = |label: &str, local: usize, world: usize| {
+        let set_local = set_local.clone();
+        let set_world = set_world.clone();
+        Button::new(label, move |_| {
+            set_local(local);
+            set_world(world);
+        })
+        .el()
+    };
+
+    FlowColumn::el([
+        Text::el("Align axis").section_style(),
+        FlowRow::el([preset("Upright", UP, UP), preset("Face north", NORTH, NORTH)]).set(space_between_items(), STREET),
+        EditorRow::el("Local axis", axis_select(local, set_local.clone())),
+        EditorRow::el("World axis", axis_select(world, set_world.clone())),
+        Text::el(format!("Turns local {local_label} onto world {world_label}, by up to {angle:.1}\u{00b0}")).small_style(),
+        FlowRow::el([
+            Button::new("Align", {
+                let on_close = on_close.clone();
+                move |world| {
+                    let intent = IntentTransform {
+                        entities: targets.to_vec(),
+                        transforms: transforms.clone(),
+                        terrain_offset: TerrainOffset::Update,
+                    };
+                    guard_batch(world, PendingBatch::intent("Align", targets.clone(), game_client.clone(), intent_set_transform(), intent));
+                    on_close();
+                }
+            })
+            .disabled(initial.transforms.len() != targets.len() || angle < 1e-3)
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use ambient_core::transform::get_world_transform;
+use ambient_ecs::query;
+use ambient_element::{element_component, Element, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_network::client::GameClient;
+use ambient_std::line_hash;
+use ambient_ui::use_interval_deps;
+use glam::{vec3, Vec3};
+
+use super::{transform::local_frame, use_gizmo_scope};
+use crate::anchor;
+
+const GLYPH_SIZE: f32 = 0.5;
+
+fn glyph_scope() -> u64 {
+    line_hash!()
+}
+
+/// Draws a small axis cross for each anchor, since they have no mesh of their own
+#[element_component]
+pub fn AnchorGlyphs(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    use_interval_deps(hooks, Duration::from_millis(100), true, (), {
+        let game_state = game_client.game_state.clone();
+        move |_| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(glyph_scope());
+            for (id, _) in query(anchor()).iter(&state.world, None) {
+                let transform = match get_world_transform(&state.world, id) {
+                    Ok(v) => v,
+                    Err(_) => continue,
+                };
+
+                let frame = local_frame(transform);
+                let pos = frame.transform_point3(Vec3::ZERO);
+                for (axis, color) in [(Vec3::X, vec3(1., 0.2, 0.2)), (Vec3::Y, vec3(0.2, 1., 0.2)), (Vec3::Z, vec3(0.2, 0.2, 1.))] {
+                    scope.draw(GizmoPrimitive::line(pos, pos + frame.transform_vector3(axis) * GLYPH_SIZE, 0.03).with_color(color));
+                }
+                scope.draw(GizmoPrimitive::sphere(pos, 0.08).with_color(Vec3::ONE));
+            }
+        }
+    });
+
+    use_gizmo_scope(hooks, glyph_scope());
+
+    Element::new()
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use ambient_core::runtime;
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_std::Cb;
+use ambient_ui::{space_between_items, Button, FlowColumn, FlowRow, StylesExt, Text, STREET};
+
+use crate::intents::{intent_apply_transform, IntentApplyTransform};
+
+/// Resets the rotation and or scale of the selection without moving what it looks like, by baking
+/// them into the mesh and children.
+///
+/// Cleans up entities with messy transforms before export, such as a non-uniform scale which
+/// skews rotated children
+#[element_component]
+pub fn ApplyTransformPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    let apply_button = |label: &'static str, rotation: bool, scale: bool| {
+        let game_client = game_client.clone();
+        let targets = targets.clone();
+        Button::new(label, move |world| {
+            let intent = IntentApplyTransform { targets: targets.to_vec(), rotation, scale };
+            world.resource(runtime()).spawn(client_push_intent(game_client.clone(), intent_apply_transform(), intent, None, None));
+        })
+        .el()
+    };
+
+    FlowColumn::el([
+        Text::el("Apply transform").section_style(),
+        Text::el("Resets the rotation or scale, and bakes it into the mesh and the children").small_style(),
+        FlowRow::el([apply_button("Rotation", true, false), apply_button("Scale", false, true), apply_button("Both", true, true)])
+            .set(space_between_items(), STREET),
+        Button::new("Close", move |_| on_close()).el(),
+    ])
+    .set(space_between_items(), STREET)
+}
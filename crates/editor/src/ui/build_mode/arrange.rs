@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use ambient_core::bounding::calc_world_bounding_recursive;
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_network::client::GameClient;
+use ambient_std::{line_hash, Cb};
+use ambient_ui::{
+    space_between_items, Button, ButtonStyle, Checkbox, CountInput, Editor, EditorRow, FlowColumn, FlowRow, Slider, StylesExt, Text, STREET,
+};
+use glam::{vec3, Mat4, Vec3};
+use itertools::Itertools;
+
+use super::{
+    batch::{use_batch_guard, PendingBatch},
+    transform::current_transforms,
+    use_gizmo_scope,
+};
+use crate::{
+    intents::{intent_set_transform, IntentTransform, TerrainOffset},
+    ui::EditorPrefs,
+};
+
+/// The gap left between the largest entities when picking the default spacing
+const DEFAULT_GAP: f32 = 1.;
+
+fn preview_scope() -> u64 {
+    line_hash!()
+}
+
+/// The number of columns of a grid of `count` entities which is as close to square as possible
+fn square_columns(count: usize) -> usize {
+    (count as f32).sqrt().ceil().max(1.) as usize
+}
+
+/// Moves each of `transforms` onto a cell of a grid with `columns` columns centered on `center`,
+/// filling it row by row from the north-west corner. Rotations and scales are kept
+fn grid_transforms(transforms: &[Mat4], columns: usize, spacing: f32, center: Vec3) -> Vec<Mat4> {
+    let columns = columns.clamp(1, transforms.len().max(1));
+    let rows = (transforms.len() + columns - 1) / columns;
+    let offset = vec3((columns - 1) as f32, -((rows.max(1) - 1) as f32), 0.) * spacing / 2.;
+
+    transforms
+        .iter()
+        .enumerate()
+        .map(|(i, transform)| {
+            let (scl, rot, _) = transform.to_scale_rotation_translation();
+            let cell = vec3((i % columns) as f32, -((i / columns) as f32), 0.) * spacing;
+            Mat4::from_scale_rotation_translation(scl, rot, center - offset + cell)
+        })
+        .collect_vec()
+}
+
+/// Lays the selection out on an evenly spaced grid, keeping the rotation and scale of each entity
+#[element_component]
+pub fn ArrangePanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let guard_batch = use_batch_guard(hooks);
+    let initial = hooks.use_memo_with(targets.clone(), |_, targets| current_transforms(&game_client.game_state.lock().world, targets));
+    // Far enough apart that the largest entities don't touch
+    let (spacing, set_spacing) = hooks.use_state_with(|_| {
+        let state = game_client.game_state.lock();
+        let largest = targets
+            .iter()
+            .filter_map(|&id| calc_world_bounding_recursive(&state.world, id))
+            .map(|bounds| bounds.size().truncate().max_element())
+            .fold(0., f32::max);
+        largest + DEFAULT_GAP
+    });
+    let (square, set_square) = hooks.use_state(true);
+    let (columns, set_columns) = hooks.use_state_with(|_| square_columns(targets.len()) as u32);
+    let (center, set_center) = hooks.use_state(initial.bounds.map(|v| v.center().truncate().extend(v.min.z)).unwrap_or(initial.midpoint));
+
+    let columns = if square { square_columns(targets.len()) } else { (columns as usize).min(targets.len()) };
+    let transforms = grid_transforms(&initial.transforms, columns, spacing, center);
+
+    use_gizmo_scope(hooks, preview_scope());
+    hooks.use_effect((transforms.clone(), initial.transforms.clone(), prefs.preview_color), {
+        let game_state = game_client.game_state.clone();
+        move |_, (transforms, originals, preview_color)| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(preview_scope());
+            for (original, transform) in originals.iter().zip(transforms) {
+                let position = transform.transform_point3(Vec3::ZERO);
+                scope.draw(GizmoPrimitive::line(original.transform_point3(Vec3::ZERO), position, 0.01).with_color(*preview_color));
+                scope.draw(GizmoPrimitive::sphere(position, 0.25).with_color(*preview_color));
+            }
+
+            Box::new(|_| {})
+        }
+    });
+
+    let can_arrange = !transforms.is_empty() && transforms.len() == targets.len();
+    let rows = (targets.len() + columns.max(1) - 1) / columns.max(1);
+    FlowColumn::el([
+        Text::el("Arrange on grid").section_style(),
+        Text::el(format!("{} entities in {rows} rows of {columns}", targets.len())).small_style(),
+        EditorRow::el("Square", Checkbox::new(square, move |v| set_square(v)).el()),
+        if square { Element::new() } else { EditorRow::el("Columns", CountInput::new(columns as u32, move |v| set_columns(v)).el()) },
+        EditorRow::el(
+            "Spacing",
+            Slider {
+                value: spacing,
+                on_change: Some(set_spacing),
+                min: 0.1,
+                max: 100.,
+                width: 100.,
+                logarithmic: true,
+                round: Some(2),
+                suffix: Some("m"),
+            }
+            .el(),
+        ),
+        EditorRow::el("Center", center.editor(set_center, Default::default())),
+        FlowRow::el([
+            Button::new("Arrange", {
+                let on_close = on_close.clone();
+                move |world| {
+                    let intent = IntentTransform {
+                        entities: targets.to_vec(),
+                        transforms: transforms.clone(),
+                        terrain_offset: TerrainOffset::Update,
+                    };
+                    guard_batch(
+                        world,
+                        PendingBatch::intent("Arrange", targets.clone(), game_client.clone(), intent_set_transform(), intent),
+                    );
+                    on_close();
+                }
+            })
+            .disabled(!can_arrange)
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
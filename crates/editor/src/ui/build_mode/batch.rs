@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use ambient_core::runtime;
+use ambient_ecs::{Component, ComponentValue, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_prefab::prefab_from_url;
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use itertools::Itertools;
+
+use crate::{anchor, ui::EditorPrefs};
+
+/// How many kinds of entities the summary lists before folding the rest into one line
+const MAX_KINDS: usize = 6;
+
+/// A batched operation on many entities, such as a bulk delete, which waits to be confirmed
+#[derive(Debug, Clone)]
+pub struct PendingBatch {
+    /// What the operation does, such as "Delete" or "Align"
+    pub action: String,
+    pub targets: Arc<[EntityId]>,
+    pub on_confirm: Cb<dyn Fn(&mut World) + Sync + Send>,
+}
+
+impl PendingBatch {
+    /// A batch which pushes `intent` with `arg` once confirmed
+    pub fn intent<T: ComponentValue>(
+        action: &str,
+        targets: Arc<[EntityId]>,
+        game_client: GameClient,
+        intent: Component<T>,
+        arg: T,
+    ) -> Self {
+        Self {
+            action: action.to_string(),
+            targets,
+            on_confirm: cb(move |world| {
+                world.resource(runtime()).spawn(client_push_intent(game_client.clone(), intent, arg.clone(), None, None));
+            }),
+        }
+    }
+}
+
+/// The batched operation waiting to be confirmed, if any
+#[derive(Debug, Clone, Default)]
+pub struct PendingBatchState(pub Option<PendingBatch>);
+
+/// Returns a function which runs a batched operation right away when it affects at most
+/// [`EditorPrefs::batch_warning`] entities, and otherwise holds it until it's confirmed in the
+/// [`BatchConfirmPanel`]. All bulk tools should go through this so that they ask the same way
+pub(super) fn use_batch_guard(hooks: &mut Hooks) -> Cb<dyn Fn(&mut World, PendingBatch) + Sync + Send> {
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let (_, set_pending) = hooks.consume_context::<PendingBatchState>().unwrap();
+    cb(move |world, batch| {
+        if batch.targets.len() > prefs.batch_warning as usize {
+            set_pending(PendingBatchState(Some(batch)));
+        } else {
+            (batch.on_confirm)(world);
+        }
+    })
+}
+
+/// The kind of each entity, as the name of its object, and how many there are of each, most first
+fn kinds(world: &World, targets: &[EntityId]) -> Vec<(String, usize)> {
+    targets
+        .iter()
+        .map(|&id| match world.get_ref(id, prefab_from_url()) {
+            Ok(url) => url.rsplit('/').next().unwrap_or(url).to_string(),
+            Err(_) if world.has_component(id, anchor()) => "Anchor".to_string(),
+            Err(_) => "Other".to_string(),
+        })
+        .counts()
+        .into_iter()
+        .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)))
+        .collect_vec()
+}
+
+/// Summarizes what a pending batched operation will change, and runs it only once confirmed
+#[element_component]
+pub fn BatchConfirmPanel(hooks: &mut Hooks, batch: PendingBatch) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (_, set_pending) = hooks.consume_context::<PendingBatchState>().unwrap();
+    let PendingBatch { action, targets, on_confirm } = batch;
+
+    let (kinds, count) = {
+        let state = game_client.game_state.lock();
+        let existing = targets.iter().copied().filter(|&id| state.world.exists(id)).collect_vec();
+        (kinds(&state.world, &existing), existing.len())
+    };
+    let missing = targets.len() - count;
+    let mut rows =
+        kinds.iter().take(MAX_KINDS).map(|(kind, count)| Text::el(format!("{count} \u{00d7} {kind}")).small_style()).collect_vec();
+    if kinds.len() > MAX_KINDS {
+        let rest: usize = kinds[MAX_KINDS..].iter().map(|(_, count)| count).sum();
+        rows.push(Text::el(format!("{rest} more of {} other kinds", kinds.len() - MAX_KINDS)).small_style());
+    }
+    if missing > 0 {
+        rows.push(Text::el(format!("{missing} of the entities no longer exist")).small_style());
+    }
+
+    FlowColumn::el([
+        Text::el(format!("{action} {count} entities?")).section_style(),
+        Text::el(format!("This changes {count} entities of {} kinds", kinds.len())).error_text_style(),
+        FlowColumn::el(rows),
+        FlowRow::el([
+            Button::new(format!("{action} {count}"), {
+                let set_pending = set_pending.clone();
+                move |world| {
+                    set_pending(PendingBatchState(None));
+                    on_confirm(world);
+                }
+            })
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Back", move |_| set_pending(PendingBatchState(None))).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,137 @@
+use ambient_core::runtime;
+use ambient_element::{element_component, Element, ElementComponentExt, Group, Hooks};
+use ambient_network::{client::GameClient, hooks::use_remote_persisted_resource};
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, Hotkey, StylesExt, Text, TextInput, STREET};
+use itertools::Itertools;
+use winit::event::{ModifiersState, VirtualKeyCode};
+
+use super::camera_hud::{fly_camera, read_camera, CameraView};
+use crate::{camera_bookmarks, CameraBookmark};
+
+/// The keys which recall the first bookmarks, while holding Alt
+const BOOKMARK_KEYS: [VirtualKeyCode; 9] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+];
+
+fn bookmark_view(bookmark: &CameraBookmark) -> CameraView {
+    CameraView { position: bookmark.position, rotation: bookmark.rotation }
+}
+
+/// Recalls the first nine bookmarks with Alt and a number key, whether or not the panel is open
+#[element_component]
+pub fn BookmarkHotkeys(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (bookmarks, _) = use_remote_persisted_resource(hooks, camera_bookmarks());
+
+    Group(
+        bookmarks
+            .unwrap_or_default()
+            .iter()
+            .zip(BOOKMARK_KEYS)
+            .map(|(bookmark, key)| {
+                let game_client = game_client.clone();
+                let view = bookmark_view(bookmark);
+                Hotkey::new(key, move |world| fly_camera(world.resource(runtime()), game_client.clone(), view), Element::new())
+                    .hotkey_modifier(ModifiersState::ALT)
+                    .el()
+            })
+            .collect_vec(),
+    )
+    .el()
+}
+
+/// Saves the current viewpoint under a name, and lists the saved viewpoints of the scene
+#[element_component]
+pub fn BookmarksPanel(hooks: &mut Hooks, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (bookmarks, set_bookmarks) = use_remote_persisted_resource(hooks, camera_bookmarks());
+    let bookmarks = bookmarks.unwrap_or_default();
+    let (name, set_name) = hooks.use_state(String::new());
+    let no_name = name.is_empty();
+    let runtime = hooks.world.resource(runtime()).clone();
+
+    let saved = bookmarks
+        .iter()
+        .enumerate()
+        .map(|(i, bookmark)| {
+            let label = match BOOKMARK_KEYS.get(i) {
+                Some(_) => format!("{} [Alt + {}]", bookmark.name, i + 1),
+                None => bookmark.name.clone(),
+            };
+
+            FlowRow::el([
+                Button::new(label, {
+                    let game_client = game_client.clone();
+                    let runtime = runtime.clone();
+                    let view = bookmark_view(bookmark);
+                    move |_| fly_camera(&runtime, game_client.clone(), view)
+                })
+                .style(ButtonStyle::Flat)
+                .el(),
+                Button::new("\u{f6bf}", {
+                    let bookmarks = bookmarks.clone();
+                    let set_bookmarks = set_bookmarks.clone();
+                    move |_| {
+                        let mut bookmarks = bookmarks.clone();
+                        bookmarks.remove(i);
+                        set_bookmarks(Some(bookmarks));
+                    }
+                })
+                .tooltip("Delete bookmark")
+                .style(ButtonStyle::Flat)
+                .el(),
+            ])
+            .set(space_between_items(), STREET)
+        })
+        .collect_vec();
+
+    FlowColumn::el([
+        Text::el("Bookmarks").section_style(),
+        FlowRow::el([
+            TextInput::new(
+                name.clone(),
+                cb({
+                    let set_name = set_name.clone();
+                    move |v| set_name(v)
+                }),
+            )
+            .placeholder(Some("Bookmark name"))
+            .el(),
+            Button::new("Save view", move |_| {
+                let view = match read_camera(&game_client.game_state.lock()) {
+                    Some(v) => v,
+                    None => return,
+                };
+
+                // Saving under an existing name moves the bookmark
+                let mut bookmarks = bookmarks.clone();
+                let bookmark = CameraBookmark { name: name.clone(), position: view.position, rotation: view.rotation };
+                match bookmarks.iter_mut().find(|v| v.name == name) {
+                    Some(existing) => *existing = bookmark,
+                    None => bookmarks.push(bookmark),
+                }
+                set_bookmarks(Some(bookmarks));
+                set_name(String::new());
+            })
+            .disabled(no_name)
+            .el(),
+        ])
+        .set(space_between_items(), STREET),
+        if saved.is_empty() {
+            Text::el("No saved bookmarks").small_style()
+        } else {
+            FlowColumn::el(saved).set(space_between_items(), STREET)
+        },
+        Button::new("Close", move |_| on_close()).el(),
+    ])
+    .set(space_between_items(), STREET)
+}
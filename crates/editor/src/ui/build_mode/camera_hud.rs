@@ -0,0 +1,418 @@
+use ambient_core::{
+    bounding::calc_world_bounding_recursive,
+    camera::get_active_camera,
+    main_scene, runtime, selectable,
+    transform::{get_world_transform, rotation, translation},
+};
+use std::time::{Duration, Instant};
+
+use ambient_ecs::{query, EntityId};
+use ambient_element::{element_component, Element, ElementComponentExt, Group, Hooks};
+use ambient_network::{
+    client::{ClientGameState, GameClient},
+    is_remote_entity,
+};
+use ambient_std::{cb, shapes::AABB};
+use ambient_sys::task::RuntimeHandle;
+use ambient_ui::{space_between_items, use_interval, Button, ButtonStyle, Editor, EditorRow, FlowColumn, FlowRow, Hotkey, Text, STREET};
+use glam::{vec2, vec3, EulerRot, Mat3, Quat, Vec3};
+use itertools::Itertools;
+use tokio::time::sleep;
+use winit::event::{ModifiersState, VirtualKeyCode};
+
+use super::teleport::TELEPORT_DISTANCE;
+use crate::ui::EditorPrefs;
+
+/// How long flying the camera to a viewpoint takes
+const FLY_DURATION: Duration = Duration::from_millis(400);
+/// The direction the reset camera looks at the scene from
+const RESET_DIRECTION: Vec3 = vec3(-1., -1., 0.8);
+/// How far the reset camera is from the origin when the scene has no bounds
+const RESET_DISTANCE: f32 = 20.;
+
+/// A viewpoint of the editor camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct CameraView {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+impl CameraView {
+    /// The heading around the up axis and the tilt, in degrees
+    pub fn yaw_pitch(&self) -> (f32, f32) {
+        let (yaw, pitch, _) = self.rotation.to_euler(EulerRot::ZXY);
+        (yaw.to_degrees(), pitch.to_degrees())
+    }
+
+    pub fn with_yaw_pitch(self, yaw: f32, pitch: f32) -> Self {
+        Self { rotation: Quat::from_euler(EulerRot::ZXY, yaw.to_radians(), pitch.to_radians(), 0.), ..self }
+    }
+}
+
+impl std::fmt::Display for CameraView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (yaw, pitch) = self.yaw_pitch();
+        let Vec3 { x, y, z } = self.position;
+        write!(f, "position {x:.2} {y:.2} {z:.2}, yaw {yaw:.1}, pitch {pitch:.1}")
+    }
+}
+
+pub(super) fn read_camera(state: &ClientGameState) -> Option<CameraView> {
+    let camera = get_active_camera(&state.world, main_scene())?;
+    Some(CameraView {
+        position: state.world.get(camera, translation()).ok()?,
+        rotation: state.world.get(camera, rotation()).unwrap_or_default(),
+    })
+}
+
+/// Moves the active camera to `view`. Like teleporting, this only moves the viewpoint
+pub(super) fn write_camera(state: &mut ClientGameState, view: CameraView) {
+    let camera = match get_active_camera(&state.world, main_scene()) {
+        Some(v) => v,
+        None => {
+            tracing::warn!("No active camera to move");
+            return;
+        }
+    };
+
+    let result = state.world.set(camera, translation(), view.position).and_then(|_| state.world.set(camera, rotation(), view.rotation));
+    if let Err(err) = result {
+        tracing::warn!("Failed to move camera: {err:?}");
+    }
+}
+
+/// The view from `position` towards `target`, with the horizon level.
+///
+/// The orientation is derived from the current view, so it holds whichever axes the camera looks
+/// along
+pub(super) fn look_at(state: &ClientGameState, position: Vec3, target: Vec3) -> Option<CameraView> {
+    look_along(state, position, target - position, Vec3::Z)
+}
+
+/// The view from `position` in the direction `dir`, rolled so that `up` points up on screen.
+/// When looking along `up`, the current roll is kept instead
+fn look_along(state: &ClientGameState, position: Vec3, dir: Vec3, up: Vec3) -> Option<CameraView> {
+    let current = read_camera(state)?;
+    let forward = state.center_screen_ray().dir;
+    let screen_up = (state.screen_ray(vec2(0., 0.01)).dir - forward).reject_from(forward).normalize_or_zero();
+
+    let basis = |forward: Vec3, up: Vec3| Mat3::from_cols(forward, up, forward.cross(up));
+    // The camera axes in terms of the basis, which is kept for the new view
+    let in_basis = basis(forward, screen_up).transpose() * Mat3::from_quat(current.rotation);
+
+    let new_forward = dir.normalize_or_zero();
+    let mut new_up = up.reject_from(new_forward).normalize_or_zero();
+    if new_up == Vec3::ZERO {
+        new_up = screen_up.reject_from(new_forward).normalize_or_zero();
+    }
+    if new_forward == Vec3::ZERO || new_up == Vec3::ZERO {
+        return None;
+    }
+
+    Some(CameraView { position, rotation: Quat::from_mat3(&(basis(new_forward, new_up) * in_basis)).normalize() })
+}
+
+/// A view of the whole scene, or of the origin if the scene is empty
+pub(super) fn scene_overview(state: &ClientGameState) -> Option<CameraView> {
+    let bounds = query(selectable())
+        .incl(is_remote_entity())
+        .iter(&state.world, None)
+        .filter_map(|(id, _)| calc_world_bounding_recursive(&state.world, id))
+        .collect_vec();
+
+    let (target, distance) = match AABB::unions(&bounds) {
+        Some(bounds) => (bounds.center(), (bounds.size().length() * 1.2).max(5.)),
+        None => (Vec3::ZERO, RESET_DISTANCE),
+    };
+    look_at(state, target + RESET_DIRECTION.normalize() * distance, target)
+}
+
+/// Moves the active camera to `view` over [`FLY_DURATION`], easing in and out
+pub(super) fn fly_camera(runtime: &RuntimeHandle, game_client: GameClient, view: CameraView) {
+    runtime.spawn(async move {
+        let start = match read_camera(&game_client.game_state.lock()) {
+            Some(v) => v,
+            None => return,
+        };
+
+        let started = Instant::now();
+        loop {
+            let t = (started.elapsed().as_secs_f32() / FLY_DURATION.as_secs_f32()).min(1.);
+            let t = t * t * (3. - 2. * t);
+            write_camera(
+                &mut game_client.game_state.lock(),
+                CameraView { position: start.position.lerp(view.position, t), rotation: start.rotation.slerp(view.rotation, t) },
+            );
+
+            if t >= 1. {
+                break;
+            }
+            sleep(Duration::from_millis(16)).await;
+        }
+    });
+}
+
+/// The local axes the camera can look along, with the local axis kept up on screen for each
+const LOCAL_VIEWS: [(&str, Vec3, Vec3); 6] = [
+    ("+X", Vec3::X, Vec3::Z),
+    ("-X", Vec3::NEG_X, Vec3::Z),
+    ("+Y", Vec3::Y, Vec3::Z),
+    ("-Y", Vec3::NEG_Y, Vec3::Z),
+    ("+Z", Vec3::Z, Vec3::Y),
+    ("-Z", Vec3::NEG_Z, Vec3::Y),
+];
+
+/// The view looking at `id` along its local `axis`, from far enough away to fit it on screen
+fn local_axis_view(state: &ClientGameState, id: EntityId, axis: Vec3, up: Vec3) -> Option<CameraView> {
+    let (_, rot, pos) = get_world_transform(&state.world, id).ok()?.to_scale_rotation_translation();
+    let (center, distance) = match calc_world_bounding_recursive(&state.world, id) {
+        Some(bounds) => (bounds.center(), (bounds.size().length() * 1.2).max(2.)),
+        None => (pos, TELEPORT_DISTANCE),
+    };
+    let dir = rot * axis;
+    look_along(state, center - dir * distance, dir, rot * up)
+}
+
+/// Buttons to look along each local axis of `target`. The view from before is flown back to with
+/// Back, or when the buttons go away
+#[element_component]
+pub fn LocalAxisViews(hooks: &mut Hooks, target: EntityId) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let runtime = hooks.world.resource(runtime()).clone();
+    let stash = hooks.use_ref_with::<Option<CameraView>>(|_| None);
+    let (looking, set_looking) = hooks.use_state(None as Option<&'static str>);
+
+    hooks.use_spawn({
+        let runtime = runtime.clone();
+        let game_client = game_client.clone();
+        let stash = stash.clone();
+        move |_| {
+            Box::new(move |_| {
+                if let Some(view) = stash.lock().take() {
+                    fly_camera(&runtime, game_client, view);
+                }
+            })
+        }
+    });
+
+    let mut items = LOCAL_VIEWS
+        .iter()
+        .map(|&(label, axis, up)| {
+            let runtime = runtime.clone();
+            let game_client = game_client.clone();
+            let stash = stash.clone();
+            let set_looking = set_looking.clone();
+            Button::new(label, move |_| {
+                let (current, view) = {
+                    let state = game_client.game_state.lock();
+                    (read_camera(&state), local_axis_view(&state, target, axis, up))
+                };
+                if let Some(view) = view {
+                    stash.lock().get_or_insert_with(|| current.unwrap_or(view));
+                    fly_camera(&runtime, game_client.clone(), view);
+                    set_looking(Some(label));
+                }
+            })
+            .tooltip(format!("Look along local {label}"))
+            .toggled(looking == Some(label))
+            .el()
+        })
+        .collect_vec();
+    items.push(
+        Button::new("Back", move |_| {
+            if let Some(view) = stash.lock().take() {
+                fly_camera(&runtime, game_client.clone(), view);
+            }
+            set_looking(None);
+        })
+        .disabled(looking.is_none())
+        .style(ButtonStyle::Flat)
+        .el(),
+    );
+
+    FlowRow::el(items).set(space_between_items(), STREET)
+}
+
+/// A direction on screen to step the camera in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CameraStep {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl CameraStep {
+    const ALL: [(CameraStep, VirtualKeyCode); 6] = [
+        (CameraStep::Forward, VirtualKeyCode::Up),
+        (CameraStep::Back, VirtualKeyCode::Down),
+        (CameraStep::Left, VirtualKeyCode::Left),
+        (CameraStep::Right, VirtualKeyCode::Right),
+        (CameraStep::Up, VirtualKeyCode::PageUp),
+        (CameraStep::Down, VirtualKeyCode::PageDown),
+    ];
+
+    /// The world space direction of the step, relative to the current view
+    fn direction(self, state: &ClientGameState) -> Vec3 {
+        let forward = state.center_screen_ray().dir;
+        // Taken from the screen, as looking straight up or down has no horizontal right
+        let right = (state.screen_ray(vec2(0.01, 0.)).dir - forward).reject_from(forward).normalize_or_zero();
+        let up = (state.screen_ray(vec2(0., 0.01)).dir - forward).reject_from(forward).normalize_or_zero();
+
+        match self {
+            CameraStep::Forward => forward,
+            CameraStep::Back => -forward,
+            CameraStep::Left => -right,
+            CameraStep::Right => right,
+            CameraStep::Up => up,
+            CameraStep::Down => -up,
+        }
+    }
+}
+
+/// Dollies the camera with Alt and the up and down arrows, and pans it with Alt and the left and
+/// right arrows or page up and down, by [`EditorPrefs::camera_step`] per press
+#[element_component]
+pub fn CameraStepHotkeys(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+
+    Group(
+        CameraStep::ALL
+            .into_iter()
+            .map(|(step, key)| {
+                let game_client = game_client.clone();
+                Hotkey::new(
+                    key,
+                    move |_| {
+                        let mut state = game_client.game_state.lock();
+                        if let Some(view) = read_camera(&state) {
+                            let position = view.position + step.direction(&state) * prefs.camera_step;
+                            write_camera(&mut state, CameraView { position, ..view });
+                        }
+                    },
+                    Element::new(),
+                )
+                .hotkey_modifier(ModifiersState::ALT)
+                .el()
+            })
+            .collect_vec(),
+    )
+    .el()
+}
+
+/// The names of the headings, clockwise from north, which is world +Y
+const HEADINGS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+/// Arrows pointing ahead, right, behind and left on screen
+const ARROWS: [&str; 4] = ["\u{f062}", "\u{f061}", "\u{f063}", "\u{f060}"];
+
+/// The heading of the camera around the up axis in degrees, clockwise from north, which is world
+/// +Y since Z is up. None while looking straight up or down
+fn camera_heading(state: &ClientGameState) -> Option<f32> {
+    let forward = state.center_screen_ray().dir.truncate();
+    if forward.length_squared() < 1e-6 {
+        return None;
+    }
+    Some(forward.x.atan2(forward.y).to_degrees().rem_euclid(360.))
+}
+
+/// A minimal always visible compass, with the heading of the camera and an arrow pointing north
+#[element_component]
+pub fn Compass(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (heading, set_heading) = hooks.use_state(None as Option<f32>);
+
+    use_interval(hooks, 0.1, move || set_heading(camera_heading(&game_client.game_state.lock())));
+
+    match heading {
+        Some(heading) => {
+            let name = HEADINGS[((heading / 45.).round() as usize) % HEADINGS.len()];
+            // North relative to the view, clockwise
+            let arrow = ARROWS[(((360. - heading) / 90.).round() as usize) % ARROWS.len()];
+            Text::el(format!("\u{f14e} {name} {heading:.0}\u{00b0}   N {arrow}"))
+        }
+        None => Text::el("\u{f14e} Looking straight up or down"),
+    }
+}
+
+/// Shows the camera position and orientation, editable to jump to an exact viewpoint
+#[element_component]
+pub fn CameraHud(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (view, set_view) = hooks.use_state(None as Option<CameraView>);
+    let (stash, set_stash) = hooks.use_state(None as Option<CameraView>);
+
+    use_interval(hooks, 0.25, {
+        let game_client = game_client.clone();
+        move || set_view(read_camera(&game_client.game_state.lock()))
+    });
+
+    let view = match view {
+        Some(v) => v,
+        None => return Text::el("No active camera"),
+    };
+    let (yaw, pitch) = view.yaw_pitch();
+
+    let set_camera = {
+        let game_client = game_client.clone();
+        move |view: CameraView| write_camera(&mut game_client.game_state.lock(), view)
+    };
+
+    FlowColumn::el([
+        EditorRow::el(
+            "Camera",
+            view.position.editor(
+                cb({
+                    let set_camera = set_camera.clone();
+                    move |position| set_camera(CameraView { position, ..view })
+                }),
+                Default::default(),
+            ),
+        ),
+        FlowRow::el([
+            EditorRow::el(
+                "Yaw",
+                yaw.editor(
+                    cb({
+                        let set_camera = set_camera.clone();
+                        move |yaw| set_camera(view.with_yaw_pitch(yaw, pitch))
+                    }),
+                    Default::default(),
+                ),
+            ),
+            EditorRow::el(
+                "Pitch",
+                pitch.editor(
+                    cb({
+                        let set_camera = set_camera.clone();
+                        move |pitch| set_camera(view.with_yaw_pitch(yaw, pitch))
+                    }),
+                    Default::default(),
+                ),
+            ),
+        ])
+        .set(space_between_items(), STREET),
+        FlowRow::el([
+            Button::new("Copy camera", move |_| {
+                set_stash(Some(view));
+                // Also as text, for pasting into issues
+                if let Err(err) = arboard::Clipboard::new().and_then(|mut v| v.set_text(view.to_string())) {
+                    tracing::warn!("Failed to copy the camera to the clipboard: {err}");
+                }
+            })
+            .el(),
+            Button::new("Restore camera", move |_| {
+                if let Some(stash) = stash {
+                    set_camera(stash)
+                }
+            })
+            .disabled(stash.is_none())
+            .style(ButtonStyle::Flat)
+            .el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use ambient_core::{bounding::calc_world_bounding_recursive, runtime};
+use ambient_ecs::{EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gizmos::gizmos;
+use ambient_intent::client_push_intent;
+use ambient_network::{client::GameClient, log_network_result};
+use ambient_std::{cb, line_hash, shapes::AABB, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, EditorRow, FlowColumn, FlowRow, Slider, StylesExt, Text, STREET};
+use glam::{vec3, Vec3};
+use itertools::Itertools;
+
+use super::{overlap::draw_bounds, use_gizmo_scope};
+use crate::{
+    intents::{intent_group, IntentGroup, SelectMode},
+    rpc::{rpc_select, SelectMethod},
+    Selection,
+};
+
+/// The default largest gap between two entities of the same cluster
+const DEFAULT_THRESHOLD: f32 = 2.;
+/// The preview colors, cycled through so that neighbouring clusters can be told apart
+const CLUSTER_COLORS: [Vec3; 6] =
+    [vec3(0.3, 0.3, 1.), vec3(1., 0.6, 0.2), vec3(0.3, 1., 0.4), vec3(1., 0.3, 0.8), vec3(0.2, 0.9, 1.), vec3(1., 1., 0.3)];
+
+fn preview_scope() -> u64 {
+    line_hash!()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cluster {
+    members: Vec<EntityId>,
+    bounds: AABB,
+}
+
+/// The shortest distance between the surfaces of two boxes, or 0 if they overlap
+fn gap(a: &AABB, b: &AABB) -> f32 {
+    (a.min - b.max).max(b.min - a.max).max(Vec3::ZERO).length()
+}
+
+/// Follows `roots` from `i` up to the root of its set, shortening the path on the way
+fn root(roots: &mut [usize], mut i: usize) -> usize {
+    while roots[i] != i {
+        roots[i] = roots[roots[i]];
+        i = roots[i];
+    }
+    i
+}
+
+/// Splits `targets` into clusters of entities whose bounds are at most `threshold` apart.
+///
+/// Entities chain into the same cluster through the entities between them, so a long row of
+/// evenly spaced objects stays together. Entities without bounds are left out
+fn find_clusters(world: &World, targets: &[EntityId], threshold: f32) -> Vec<Cluster> {
+    let bounds = targets.iter().filter_map(|&id| Some((id, calc_world_bounding_recursive(world, id)?))).collect_vec();
+
+    // Union-find over the indices into `bounds`
+    let mut roots = (0..bounds.len()).collect_vec();
+    for (i, (_, a)) in bounds.iter().enumerate() {
+        for (j, (_, b)) in bounds.iter().enumerate().skip(i + 1) {
+            if gap(a, b) <= threshold {
+                let (a, b) = (root(&mut roots, i), root(&mut roots, j));
+                roots[b] = a;
+            }
+        }
+    }
+
+    let roots = (0..bounds.len()).map(|i| root(&mut roots, i)).collect_vec();
+    (0..bounds.len())
+        .into_group_map_by(|&i| roots[i])
+        .into_values()
+        .sorted_by_key(|members| members[0])
+        .map(|members| Cluster {
+            bounds: AABB::unions(&members.iter().map(|&i| bounds[i].1).collect_vec()).unwrap(),
+            members: members.into_iter().map(|i| bounds[i].0).collect_vec(),
+        })
+        .collect_vec()
+}
+
+/// Splits the selection into clusters of nearby entities, previewed as boxes around each cluster.
+///
+/// A cluster can then be selected on its own, or every cluster can be given an empty parent
+#[element_component]
+pub fn ClusterPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (threshold, set_threshold) = hooks.use_state(DEFAULT_THRESHOLD);
+
+    let clusters = hooks.use_memo_with((targets.clone(), threshold), |_, (targets, threshold)| {
+        find_clusters(&game_client.game_state.lock().world, targets, *threshold)
+    });
+
+    use_gizmo_scope(hooks, preview_scope());
+    hooks.use_effect(clusters.clone(), {
+        let game_state = game_client.game_state.clone();
+        move |_, clusters| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(preview_scope());
+            for (cluster, &color) in clusters.iter().zip(CLUSTER_COLORS.iter().cycle()) {
+                draw_bounds(&mut scope, &cluster.bounds, color);
+            }
+
+            Box::new(|_| {})
+        }
+    });
+
+    let select = |members: Vec<EntityId>| {
+        let game_client = game_client.clone();
+        move |world: &mut World| {
+            let game_client = game_client.clone();
+            let method = SelectMethod::Manual(Selection::new(members.clone()));
+            world.resource(runtime()).spawn(async move {
+                log_network_result!(game_client.rpc(rpc_select, (method, SelectMode::Set)).await);
+            });
+        }
+    };
+
+    FlowColumn::el([
+        Text::el("Split into clusters").section_style(),
+        EditorRow::el(
+            "Distance",
+            Slider {
+                value: threshold,
+                on_change: Some(cb(move |v| set_threshold(v))),
+                min: 0.,
+                max: 50.,
+                width: 100.,
+                logarithmic: false,
+                round: Some(1),
+                suffix: Some("m"),
+            }
+            .el(),
+        ),
+        Text::el(format!("{} entities in {} clusters", targets.len(), clusters.len())).small_style(),
+        FlowColumn::el(clusters.iter().enumerate().map(|(i, cluster)| {
+            Button::new(format!("Select cluster {} ({} entities)", i + 1, cluster.members.len()), select(cluster.members.clone()))
+                .style(ButtonStyle::Flat)
+                .el()
+        })),
+        FlowRow::el([
+            Button::new("Group each cluster", {
+                let game_client = game_client.clone();
+                let on_close = on_close.clone();
+                let clusters = clusters.clone();
+                move |world| {
+                    let groups = clusters.iter().map(|cluster| (EntityId::new(), cluster.members.clone())).collect_vec();
+                    world.resource(runtime()).spawn(client_push_intent(
+                        game_client.clone(),
+                        intent_group(),
+                        IntentGroup { groups, select: true },
+                        None,
+                        None,
+                    ));
+                    on_close();
+                }
+            })
+            .tooltip("Parent each cluster to a new empty entity in its middle")
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,121 @@
+use std::{sync::Arc, time::Duration};
+
+use ambient_core::selectable;
+use ambient_ecs::{query, with_component_registry, Component, EntityId, PrimitiveComponent, PrimitiveComponentType, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::{client::GameClient, is_remote_entity};
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, use_interval_deps, Button, DropdownSelect, EditorRow, FlowColumn, StylesExt, Text, STREET};
+use glam::{vec3, Vec3, Vec4};
+use itertools::Itertools;
+
+use super::ghost::use_tints;
+
+/// The colors of the lowest, middle and highest values
+const GRADIENT: [Vec3; 3] = [vec3(0.2, 0.3, 1.), vec3(0.2, 1., 0.3), vec3(1., 0.2, 0.2)];
+
+/// The components which hold a single number, which are the ones that can be colored by
+fn numeric_components() -> Vec<PrimitiveComponent> {
+    with_component_registry(|cr| {
+        cr.all_primitive()
+            .filter(|pc| {
+                matches!(
+                    pc.ty,
+                    PrimitiveComponentType::F32
+                        | PrimitiveComponentType::F64
+                        | PrimitiveComponentType::I32
+                        | PrimitiveComponentType::U32
+                        | PrimitiveComponentType::U64
+                        | PrimitiveComponentType::Bool
+                )
+            })
+            .cloned()
+            .sorted_by_key(component_label)
+            .collect_vec()
+    })
+}
+
+fn component_label(pc: &PrimitiveComponent) -> String {
+    pc.desc.name().unwrap_or_else(|| pc.desc.path())
+}
+
+fn read_number(world: &World, id: EntityId, pc: &PrimitiveComponent) -> Option<f32> {
+    match pc.ty {
+        PrimitiveComponentType::F32 => world.get(id, Component::<f32>::new(pc.desc)).ok(),
+        PrimitiveComponentType::F64 => world.get(id, Component::<f64>::new(pc.desc)).ok().map(|v| v as f32),
+        PrimitiveComponentType::I32 => world.get(id, Component::<i32>::new(pc.desc)).ok().map(|v| v as f32),
+        PrimitiveComponentType::U32 => world.get(id, Component::<u32>::new(pc.desc)).ok().map(|v| v as f32),
+        PrimitiveComponentType::U64 => world.get(id, Component::<u64>::new(pc.desc)).ok().map(|v| v as f32),
+        PrimitiveComponentType::Bool => world.get(id, Component::<bool>::new(pc.desc)).ok().map(|v| if v { 1. } else { 0. }),
+        _ => None,
+    }
+}
+
+/// The color of `t` between 0 and 1 on [`GRADIENT`]
+fn gradient(t: f32) -> Vec4 {
+    let t = t.clamp(0., 1.) * 2.;
+    let color = if t < 1. { GRADIENT[0].lerp(GRADIENT[1], t) } else { GRADIENT[1].lerp(GRADIENT[2], t - 1.) };
+    color.extend(1.)
+}
+
+/// The value of `pc` on each entity which has it, and the range of the values
+fn sample(world: &World, pc: &PrimitiveComponent) -> (Vec<(EntityId, f32)>, Option<(f32, f32)>) {
+    let values = query(selectable())
+        .incl(is_remote_entity())
+        .iter(world, None)
+        .filter_map(|(id, _)| Some((id, read_number(world, id, pc)?)))
+        .filter(|(_, value)| value.is_finite())
+        .collect_vec();
+    let range = values.iter().map(|&(_, value)| value).minmax().into_option();
+    (values, range)
+}
+
+/// Colors the entities in the viewport on a gradient by the value of a component, such as to see
+/// how health or a layer is spread across the scene. Only the local colors are changed, and they
+/// are restored once the panel is closed
+#[element_component]
+pub fn ColorByPanel(hooks: &mut Hooks, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let components = hooks.use_memo_with((), |_, _| Arc::new(numeric_components()));
+    let (component, set_component) = hooks.use_state(None as Option<PrimitiveComponent>);
+    let (sampled, set_sampled) = hooks.use_state((Vec::new(), None) as (Vec<(EntityId, f32)>, Option<(f32, f32)>));
+
+    // Values change while the game runs, so they are sampled again regularly
+    use_interval_deps(hooks, Duration::from_secs(1), true, component.clone(), move |component| match component {
+        Some(pc) => set_sampled(sample(&game_client.game_state.lock().world, pc)),
+        None => set_sampled((Vec::new(), None)),
+    });
+
+    let (values, range) = sampled;
+    let tints: Arc<[(EntityId, Vec4)]> = match range {
+        Some((min, max)) => values.iter().map(|&(id, value)| (id, gradient((value - min) / (max - min).max(f32::EPSILON)))).collect(),
+        None => Arc::from([]),
+    };
+    use_tints(hooks, tints);
+
+    FlowColumn::el([
+        Text::el("Color by component").section_style(),
+        EditorRow::el(
+            "Component",
+            DropdownSelect {
+                content: Text::el(component.as_ref().map_or("None".to_string(), component_label)),
+                on_select: cb({
+                    let components = components.clone();
+                    move |index| set_component(components.get(index).cloned())
+                }),
+                items: components.iter().map(|pc| Text::el(component_label(pc))).collect(),
+                inline: false,
+            }
+            .el(),
+        ),
+        match (&component, range) {
+            (None, _) => Text::el("Pick a number component to color the entities which have it").small_style(),
+            (Some(_), None) => Text::el("No entities have this component").small_style(),
+            (Some(_), Some((min, max))) => {
+                Text::el(format!("{} entities, blue at {min:.3} through green to red at {max:.3}", values.len())).small_style()
+            }
+        },
+        Button::new("Close", move |_| on_close()).el(),
+    ])
+    .set(space_between_items(), STREET)
+}
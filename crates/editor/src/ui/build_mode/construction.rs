@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use ambient_core::bounding::calc_world_bounding_recursive;
+use ambient_ecs::{EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_network::client::GameClient;
+use ambient_std::{line_hash, shapes::AABB, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use glam::{vec2, vec3, Vec2, Vec3, Vec3Swizzles};
+use itertools::Itertools;
+
+use super::use_gizmo_scope;
+use crate::ui::{ConstructionGuides, ConstructionLine};
+
+/// How far each construction line is drawn from its point
+const LINE_LENGTH: f32 = 500.;
+const LINE_COLOR: Vec3 = vec3(1., 0.3, 0.8);
+
+fn construction_scope() -> u64 {
+    line_hash!()
+}
+
+/// Where two lines cross on the plan, if they aren't parallel
+fn intersection(a: &ConstructionLine, b: &ConstructionLine) -> Option<Vec2> {
+    let denom = a.dir.perp_dot(b.dir);
+    if denom.abs() < 1e-6 {
+        return None;
+    }
+    let t = (b.point.xy() - a.point.xy()).perp_dot(b.dir) / denom;
+    Some(a.point.xy() + a.dir * t)
+}
+
+/// Where any two of the lines cross on the plan, which placing and moving snap onto
+pub(super) fn guide_intersections(lines: &[ConstructionLine]) -> Vec<Vec2> {
+    lines.iter().tuple_combinations().filter_map(|(a, b)| intersection(a, b)).collect()
+}
+
+/// Draws the construction lines, and a marker where they cross
+#[element_component]
+pub fn ConstructionGuidesOverlay(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (guides, _) = hooks.consume_context::<ConstructionGuides>().unwrap();
+
+    use_gizmo_scope(hooks, construction_scope());
+    hooks.use_effect(guides, move |_, guides| {
+        let state = game_client.game_state.lock();
+        let mut scope = state.world.resource(gizmos()).scope(construction_scope());
+        for line in &guides.0 {
+            let dir = line.dir.extend(0.) * LINE_LENGTH;
+            scope.draw(GizmoPrimitive::line(line.point - dir, line.point + dir, 0.03).with_color(LINE_COLOR));
+        }
+        let height = guides.0.iter().map(|line| line.point.z).fold(f32::MIN, f32::max);
+        for point in guide_intersections(&guides.0) {
+            scope.draw(GizmoPrimitive::sphere(point.extend(height), 0.15).with_color(LINE_COLOR));
+        }
+
+        Box::new(|_| {})
+    });
+
+    Element::new()
+}
+
+/// Adds construction lines through the selection, and lists them for removal
+#[element_component]
+pub fn ConstructionGuidesPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (guides, set_guides) = hooks.consume_context::<ConstructionGuides>().unwrap();
+
+    let centers = {
+        let state = game_client.game_state.lock();
+        targets.iter().filter_map(|&id| calc_world_bounding_recursive(&state.world, id)).map(|bounds: AABB| bounds.center()).collect_vec()
+    };
+    let midpoint = (!centers.is_empty()).then(|| centers.iter().sum::<Vec3>() / centers.len() as f32);
+    // A line from the first selected to the active entity
+    let through = match (centers.first(), centers.last()) {
+        (Some(&first), Some(&last)) if first.xy().distance(last.xy()) > 1e-3 => {
+            Some(ConstructionLine { point: first, dir: (last.xy() - first.xy()).normalize() })
+        }
+        _ => None,
+    };
+
+    let add = {
+        let guides = guides.clone();
+        let set_guides = set_guides.clone();
+        move |line: Option<ConstructionLine>| {
+            let guides = guides.clone();
+            let set_guides = set_guides.clone();
+            move |_: &mut World| {
+                if let Some(line) = line {
+                    let mut guides = guides.clone();
+                    guides.0.push(line);
+                    set_guides(guides);
+                }
+            }
+        }
+    };
+
+    let lines = guides
+        .0
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let angle = line.dir.y.atan2(line.dir.x).to_degrees();
+            FlowRow::el([
+                Text::el(format!("Through {:.2} {:.2}, at {angle:.0}\u{00b0}", line.point.x, line.point.y)),
+                Button::new("\u{f1f8}", {
+                    let guides = guides.clone();
+                    let set_guides = set_guides.clone();
+                    move |_| {
+                        let mut guides = guides.clone();
+                        guides.0.remove(index);
+                        set_guides(guides);
+                    }
+                })
+                .style(ButtonStyle::Flat)
+                .tooltip("Delete the line")
+                .el(),
+            ])
+            .set(space_between_items(), STREET)
+        })
+        .collect_vec();
+
+    FlowColumn::el([
+        Text::el("Construction lines").section_style(),
+        Text::el("Placing and moving snap to where the lines cross. They are not saved with the scene").small_style(),
+        FlowColumn::el(lines),
+        FlowRow::el([
+            Button::new("Along X", add(midpoint.map(|point| ConstructionLine { point, dir: vec2(1., 0.) })))
+                .disabled(midpoint.is_none())
+                .tooltip("Add a line along X through the selection")
+                .el(),
+            Button::new("Along Y", add(midpoint.map(|point| ConstructionLine { point, dir: vec2(0., 1.) })))
+                .disabled(midpoint.is_none())
+                .tooltip("Add a line along Y through the selection")
+                .el(),
+            Button::new("Through", add(through))
+                .disabled(through.is_none())
+                .tooltip("Add a line from the first selected to the active entity")
+                .el(),
+        ])
+        .set(space_between_items(), STREET),
+        FlowRow::el([
+            Button::new("Clear", move |_| set_guides(ConstructionGuides::default())).disabled(guides.0.is_empty()).el(),
+            Button::new("Close", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -11,6 +11,11 @@ use ambient_std::{cb, Cb};
 use ambient_ui::{fit_horizontal, space_between_items, Button, ButtonStyle, DialogScreen, Fit, FlowColumn, FlowRow, ScrollArea, STREET};
 use itertools::Itertools;
 
+use crate::{
+    intents::SelectMode,
+    rpc::{rpc_select, SelectMethod},
+};
+
 #[derive(Debug, Clone)]
 pub struct EntityBrowser {
     on_select: Cb<dyn Fn(EntityId) + Sync + Send>,
@@ -22,6 +27,19 @@ impl ElementComponent for EntityBrowser {
         let (all_tags, set_all_tags) = hooks.use_state(Vec::new());
         let (selected_tag, set_selected_tag) = hooks.use_state(None);
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+        let runtime = hooks.world.resource(runtime()).clone();
+        let select_tagged = selected_tag.clone().map(|tag| {
+            let game_client = game_client.clone();
+            Button::new(format!("Select all tagged {tag}"), move |_| {
+                let game_client = game_client.clone();
+                let tag = tag.clone();
+                runtime.spawn(async move {
+                    log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Tag(tag), SelectMode::Set)).await);
+                });
+            })
+            .style(ButtonStyle::Primary)
+            .el()
+        });
         hooks.use_spawn(move |_| {
             let state = game_client.game_state.lock();
             let entities = query(selectable())
@@ -66,6 +84,7 @@ impl ElementComponent for EntityBrowser {
             )
             .el()
             .set(space_between_items(), STREET),
+            select_tagged.unwrap_or_default(),
             FlowColumn(
                 entities
                     .into_iter()
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use ambient_core::transform::{get_world_transform, rotation, scale, translation};
+use ambient_ecs::{Component, ComponentEntry, ComponentValue, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::client::GameClient;
+use ambient_std::{cb, Cb};
+use ambient_ui::{
+    space_between_items, Button, ButtonStyle, DropdownSelect, EditorRow, FlowColumn, FlowRow, StylesExt, Text, TextInput, STREET,
+};
+use anyhow::{ensure, Context};
+use glam::{vec3, EulerRot, Quat, Vec3};
+use itertools::Itertools;
+
+use super::batch::{use_batch_guard, PendingBatch};
+use crate::{
+    expression::{Expr, ExprContext, EXPR_HELP},
+    intents::intent_component_changes,
+    organization_color,
+    ui::entity_editor::EntityComponentChange,
+};
+
+/// The organization color the inspector adds by default
+const DEFAULT_COLOR: Vec3 = vec3(1., 0.5, 0.);
+/// How many entities the preview lists
+const PREVIEW_COUNT: usize = 8;
+/// The smallest scale an expression can set, as a zero scale collapses the entity for good
+const MIN_SCALE: f32 = 0.001;
+
+/// A number on an entity which an expression can set
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExprField {
+    PositionX,
+    PositionY,
+    PositionZ,
+    ScaleX,
+    ScaleY,
+    ScaleZ,
+    /// The rotation around the up axis, in degrees
+    Yaw,
+    ColorR,
+    ColorG,
+    ColorB,
+}
+
+impl ExprField {
+    const ALL: [ExprField; 10] = [
+        ExprField::PositionX,
+        ExprField::PositionY,
+        ExprField::PositionZ,
+        ExprField::ScaleX,
+        ExprField::ScaleY,
+        ExprField::ScaleZ,
+        ExprField::Yaw,
+        ExprField::ColorR,
+        ExprField::ColorG,
+        ExprField::ColorB,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            ExprField::PositionX => "Position X",
+            ExprField::PositionY => "Position Y",
+            ExprField::PositionZ => "Position Z",
+            ExprField::ScaleX => "Scale X",
+            ExprField::ScaleY => "Scale Y",
+            ExprField::ScaleZ => "Scale Z",
+            ExprField::Yaw => "Yaw",
+            ExprField::ColorR => "Organization color R",
+            ExprField::ColorG => "Organization color G",
+            ExprField::ColorB => "Organization color B",
+        }
+    }
+
+    fn axis(self) -> usize {
+        match self {
+            ExprField::PositionX | ExprField::ScaleX | ExprField::ColorR | ExprField::Yaw => 0,
+            ExprField::PositionY | ExprField::ScaleY | ExprField::ColorG => 1,
+            ExprField::PositionZ | ExprField::ScaleZ | ExprField::ColorB => 2,
+        }
+    }
+
+    /// Rejects values which would corrupt the entity, and keeps scales away from zero. Negative
+    /// scales are kept as they are, as they mirror the entity. Returns the value to set and
+    /// whether it was clamped
+    fn validate(self, value: f32) -> anyhow::Result<(f32, bool)> {
+        ensure!(value.is_finite(), "The result {value} is not a finite number");
+        Ok(match self {
+            ExprField::ScaleX | ExprField::ScaleY | ExprField::ScaleZ if value.abs() < MIN_SCALE => (MIN_SCALE.copysign(value), true),
+            _ => (value, false),
+        })
+    }
+
+    fn read(self, world: &World, id: EntityId) -> f32 {
+        match self {
+            ExprField::PositionX | ExprField::PositionY | ExprField::PositionZ => {
+                world.get(id, translation()).unwrap_or_default()[self.axis()]
+            }
+            ExprField::ScaleX | ExprField::ScaleY | ExprField::ScaleZ => world.get(id, scale()).unwrap_or(Vec3::ONE)[self.axis()],
+            ExprField::Yaw => {
+                let (yaw, _, _) = world.get(id, rotation()).unwrap_or_default().to_euler(EulerRot::ZXY);
+                yaw.to_degrees()
+            }
+            ExprField::ColorR | ExprField::ColorG | ExprField::ColorB => {
+                world.get(id, organization_color()).unwrap_or(DEFAULT_COLOR)[self.axis()]
+            }
+        }
+    }
+
+    fn change(self, world: &World, id: EntityId, value: f32) -> EntityComponentChange {
+        let with_axis = |mut v: Vec3| {
+            v[self.axis()] = value;
+            v
+        };
+
+        match self {
+            ExprField::PositionX | ExprField::PositionY | ExprField::PositionZ => {
+                set_or_add(world, id, translation(), with_axis(world.get(id, translation()).unwrap_or_default()))
+            }
+            ExprField::ScaleX | ExprField::ScaleY | ExprField::ScaleZ => {
+                set_or_add(world, id, scale(), with_axis(world.get(id, scale()).unwrap_or(Vec3::ONE)))
+            }
+            ExprField::Yaw => {
+                let (_, pitch, roll) = world.get(id, rotation()).unwrap_or_default().to_euler(EulerRot::ZXY);
+                set_or_add(world, id, rotation(), Quat::from_euler(EulerRot::ZXY, value.to_radians(), pitch, roll))
+            }
+            ExprField::ColorR | ExprField::ColorG | ExprField::ColorB => {
+                set_or_add(world, id, organization_color(), with_axis(world.get(id, organization_color()).unwrap_or(DEFAULT_COLOR)))
+            }
+        }
+    }
+}
+
+pub(super) fn set_or_add<T: ComponentValue>(world: &World, id: EntityId, component: Component<T>, value: T) -> EntityComponentChange {
+    let entry = ComponentEntry::new(component, value);
+    if world.has_component(id, component) {
+        EntityComponentChange::Change(entry)
+    } else {
+        EntityComponentChange::Add(entry)
+    }
+}
+
+/// Evaluates `expr` for each of `targets`, returning the old and new value of `field` for each, and
+/// whether the new value was clamped
+fn evaluate(world: &World, targets: &[EntityId], field: ExprField, expr: &Expr) -> anyhow::Result<Vec<(f32, f32, bool)>> {
+    targets
+        .iter()
+        .enumerate()
+        .map(|(index, &uid)| {
+            let value = field.read(world, uid);
+            let position = get_world_transform(world, uid).map(|v| v.transform_point3(Vec3::ZERO)).unwrap_or_default();
+            let ctx = ExprContext { index, count: targets.len(), value, position, uid };
+            let (new, clamped) = field.validate(expr.eval(&ctx)?).with_context(|| format!("Entity {index}"))?;
+            Ok((value, new, clamped))
+        })
+        .collect()
+}
+
+/// Sets a number on each selected entity to the result of an expression, as one undo step
+#[element_component]
+pub fn ExpressionPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (field, set_field) = hooks.use_state(ExprField::PositionZ);
+    let (src, set_src) = hooks.use_state("value".to_string());
+    let guard_batch = use_batch_guard(hooks);
+
+    let values = Expr::parse(&src).and_then(|expr| evaluate(&game_client.game_state.lock().world, &targets, field, &expr));
+
+    let preview = match &values {
+        Ok(values) => {
+            let mut rows = values
+                .iter()
+                .take(PREVIEW_COUNT)
+                .enumerate()
+                .map(|(i, (old, new, clamped))| {
+                    let note = if *clamped { " (clamped)" } else { "" };
+                    Text::el(format!("{i}: {old:.3} \u{2192} {new:.3}{note}")).small_style()
+                })
+                .collect_vec();
+            if values.len() > PREVIEW_COUNT {
+                rows.push(Text::el(format!("and {} more", values.len() - PREVIEW_COUNT)).small_style());
+            }
+            let clamped = values.iter().filter(|(_, _, clamped)| *clamped).count();
+            if clamped > 0 {
+                rows.push(Text::el(format!("{clamped} scales were raised to {MIN_SCALE} to keep them above zero")).error_text_style());
+            }
+            FlowColumn::el(rows)
+        }
+        Err(err) => Text::el(format!("{err:#}")).error_text_style(),
+    };
+
+    FlowColumn::el([
+        Text::el("Set by expression").section_style(),
+        EditorRow::el(
+            "Field",
+            DropdownSelect {
+                content: Text::el(field.label()),
+                on_select: cb(move |index| set_field(ExprField::ALL[index])),
+                items: ExprField::ALL.iter().map(|field| Text::el(field.label())).collect(),
+                inline: false,
+            }
+            .el(),
+        ),
+        TextInput::new(src, cb(move |v| set_src(v))).placeholder(Some("Expression, such as index * 2")).el(),
+        Text::el(EXPR_HELP).small_style(),
+        preview,
+        FlowRow::el([
+            Button::new("Apply", {
+                let on_close = on_close.clone();
+                let values = values.as_ref().ok().cloned();
+                move |world| {
+                    let values = match &values {
+                        Some(v) => v,
+                        None => return,
+                    };
+                    let changes = {
+                        let state = game_client.game_state.lock();
+                        targets.iter().zip(values).map(|(&id, &(_, new, _))| (id, field.change(&state.world, id, new))).collect_vec()
+                    };
+                    guard_batch(
+                        world,
+                        PendingBatch::intent("Set", targets.clone(), game_client.clone(), intent_component_changes(), changes),
+                    );
+                    on_close();
+                }
+            })
+            .disabled(values.is_err())
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_core::selectable;
+use ambient_ecs::{query, EntityId, World};
+use ambient_element::{element_component, Element, Hooks};
+use ambient_network::{client::GameClient, is_remote_entity};
+use ambient_renderer::color;
+use ambient_ui::use_interval_deps;
+use glam::Vec4;
+use itertools::Itertools;
+use parking_lot::Mutex;
+
+use crate::ui::EditorPrefs;
+
+/// The original colors of the ghosted entities, so that they can be restored
+type GhostedColors = Arc<Mutex<HashMap<EntityId, Option<Vec4>>>>;
+
+fn restore(world: &mut World, id: EntityId, original: Option<Vec4>) {
+    if !world.exists(id) {
+        return;
+    }
+
+    match original {
+        Some(original) => world.add_component(id, color(), original).ok(),
+        None => world.remove_component(id, color()).ok(),
+    };
+}
+
+/// Renders all entities except `targets` at a reduced opacity, so that the selection stands out
+/// while the surroundings remain visible.
+///
+/// The opacity is applied through the entity color, so it only has an effect on materials which
+/// support transparency.
+#[element_component]
+pub fn GhostUnselected(hooks: &mut Hooks, targets: Arc<[EntityId]>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let (ghosted, _) = hooks.use_state_with(|_| GhostedColors::default());
+
+    let opacity = prefs.ghost_unselected.then_some(prefs.ghost_opacity);
+
+    // Re-apply periodically to catch newly spawned entities
+    use_interval_deps(hooks, Duration::from_millis(500), true, (targets, opacity.map(f32::to_bits)), {
+        let game_client = game_client.clone();
+        let ghosted = ghosted.clone();
+        move |(targets, _)| {
+            let mut state = game_client.game_state.lock();
+            let world = &mut state.world;
+            let mut ghosted = ghosted.lock();
+
+            let opacity = match opacity {
+                Some(v) => v,
+                None => {
+                    for (id, original) in ghosted.drain() {
+                        restore(world, id, original);
+                    }
+                    return;
+                }
+            };
+
+            let others: HashSet<_> = query(selectable())
+                .incl(is_remote_entity())
+                .iter(world, None)
+                .map(|(id, _)| id)
+                .filter(|id| !targets.contains(id))
+                .collect();
+
+            // Restore the entities which were selected or removed since the last update
+            for id in ghosted.keys().copied().filter(|id| !others.contains(id)).collect_vec() {
+                let original = ghosted.remove(&id).unwrap();
+                restore(world, id, original);
+            }
+
+            for id in others.into_iter().sorted() {
+                let original = *ghosted.entry(id).or_insert_with(|| world.get(id, color()).ok());
+                let ghost = original.unwrap_or(Vec4::ONE) * Vec4::new(1., 1., 1., opacity);
+                world.add_component(id, color(), ghost).ok();
+            }
+        }
+    });
+
+    hooks.use_spawn(move |_| {
+        Box::new(move |_| {
+            let mut state = game_client.game_state.lock();
+            for (id, original) in ghosted.lock().drain() {
+                restore(&mut state.world, id, original);
+            }
+        })
+    });
+
+    Element::new()
+}
+
+/// Tints `ids` with the preview color and opacity of the prefs while mounted, so that entities
+/// which are still being placed stand out from the scene. Ids which don't exist yet, such as copies
+/// waiting on the server, are tinted once they arrive
+pub(super) fn use_preview_tint(hooks: &mut Hooks, ids: Arc<[EntityId]>) {
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let tint = prefs.preview_tint();
+    use_tints(hooks, ids.iter().map(|&id| (id, tint)).collect());
+}
+
+/// Sets the color of each entity in `tints` while mounted, restoring the original colors of the
+/// entities once they are left out or the hook goes away
+pub(super) fn use_tints(hooks: &mut Hooks, tints: Arc<[(EntityId, Vec4)]>) {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (tinted, _) = hooks.use_state_with(|_| GhostedColors::default());
+
+    use_interval_deps(hooks, Duration::from_millis(200), true, tints, {
+        let game_client = game_client.clone();
+        let tinted = tinted.clone();
+        move |tints| {
+            let mut state = game_client.game_state.lock();
+            let world = &mut state.world;
+            let mut tinted = tinted.lock();
+
+            let ids: HashSet<_> = tints.iter().map(|&(id, _)| id).collect();
+            for id in tinted.keys().copied().filter(|id| !ids.contains(id)).collect_vec() {
+                let original = tinted.remove(&id).unwrap();
+                restore(world, id, original);
+            }
+
+            for &(id, tint) in tints.iter().filter(|(id, _)| world.exists(*id)) {
+                tinted.entry(id).or_insert_with(|| world.get(id, color()).ok());
+                world.add_component(id, color(), tint).ok();
+            }
+        }
+    });
+
+    hooks.use_spawn(move |_| {
+        Box::new(move |_| {
+            let mut state = game_client.game_state.lock();
+            for (id, original) in tinted.lock().drain() {
+                restore(&mut state.world, id, original);
+            }
+        })
+    });
+}
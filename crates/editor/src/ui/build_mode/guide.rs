@@ -1,22 +1,32 @@
+use std::{sync::Arc, time::Duration};
+
 use ambient_core::{
     asset_cache,
-    bounding::{local_bounding_aabb, world_bounding_aabb, world_bounding_sphere},
+    bounding::{calc_world_bounding_recursive, local_bounding_aabb, world_bounding_aabb, world_bounding_sphere},
     main_scene, mesh,
     transform::{local_to_world, mesh_to_world},
 };
 use ambient_ecs::{Entity, EntityId, World};
-use ambient_element::{Element, ElementComponent};
+use ambient_element::{element_component, Element, ElementComponent, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
 use ambient_meshes::QuadMeshKey;
 use ambient_network::client::GameClient;
 use ambient_renderer::{color, double_sided, gpu_primitives, material, primitives, renderer_shader, SharedMaterial, StandardShaderKey};
-use ambient_std::{asset_cache::SyncAssetKeyExt, cb, shapes::AABB};
-use glam::{vec2, vec3, vec4, EulerRot, Mat4, Quat, Vec2, Vec3};
-
-use super::grid_material::{GridMaterialKey, GridShaderKey};
+use ambient_std::{asset_cache::SyncAssetKeyExt, cb, line_hash, shapes::AABB};
+use ambient_ui::{use_interval_deps, Text};
+use glam::{vec2, vec3, vec4, EulerRot, Mat4, Quat, Vec2, Vec3, Vec4};
+use itertools::Itertools;
+
+use super::{
+    grid_material::{GridMaterialKey, GridShaderKey},
+    overlap::draw_bounds,
+    use_gizmo_scope,
+};
 use crate::GRID_SIZE;
 
 const BLUEBOARD_SIZE: f32 = 1024.0;
 const LINE_WIDTH: f32 = 0.1;
+const ORIGIN_MARKER_RADIUS: f32 = 0.3;
 
 fn spawn_entity(world: &mut World, mat: SharedMaterial) -> EntityId {
     let assets = world.resource(asset_cache());
@@ -132,24 +142,141 @@ impl ElementComponent for AxisGuide {
         assert!(axis.is_normalized(), "axis: {axis}");
         let view = state.view().unwrap_or_default();
 
-        let camera_pos = view.inverse().transform_point3(Vec3::ZERO);
+        state.world.set(entity, local_to_world(), axis_billboard(view, axis, point)).expect("Entity was despawned");
 
-        let to_camera = point - camera_pos;
+        Element::new()
+    }
+}
 
-        // Rotate the plane to face the axis
-        let rot = Quat::from_rotation_arc(Vec3::Y, axis);
+/// Places a line quad along `axis` through `point`, turned towards the camera
+fn axis_billboard(view: Mat4, axis: Vec3, point: Vec3) -> Mat4 {
+    let camera_pos = view.inverse().transform_point3(Vec3::ZERO);
 
-        let tangent = rot * Vec3::Z;
+    let to_camera = point - camera_pos;
 
-        // Flatten along the axis
-        let to_camera = to_camera.reject_from(axis).normalize_or_zero();
+    // Rotate the plane to face the axis
+    let rot = Quat::from_rotation_arc(Vec3::Y, axis);
 
-        let billboard = Quat::from_rotation_arc(tangent, to_camera);
+    let tangent = rot * Vec3::Z;
 
-        let transform = Mat4::from_scale_rotation_translation(vec3(LINE_WIDTH, BLUEBOARD_SIZE, BLUEBOARD_SIZE), billboard * rot, point);
+    // Flatten along the axis
+    let to_camera = to_camera.reject_from(axis).normalize_or_zero();
 
-        state.world.set(entity, local_to_world(), transform).expect("Entity was despawned");
+    let billboard = Quat::from_rotation_arc(tangent, to_camera);
 
-        Element::new()
+    Mat4::from_scale_rotation_translation(vec3(LINE_WIDTH, BLUEBOARD_SIZE, BLUEBOARD_SIZE), billboard * rot, point)
+}
+
+const WORLD_AXES: [(Vec3, Vec4); 3] =
+    [(Vec3::X, vec4(1., 0.2, 0.2, 1.)), (Vec3::Y, vec4(0.2, 1., 0.2, 1.)), (Vec3::Z, vec4(0.2, 0.2, 1., 1.))];
+
+fn origin_marker_scope() -> u64 {
+    line_hash!()
+}
+
+/// Persistent lines along the world axes through the origin, X red, Y green and Z blue, with a
+/// marker at the origin itself.
+///
+/// The lines are solid rather than dashed like the transform guides, so the two can be told apart.
+#[element_component]
+pub fn WorldAxesGuide(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    let (entities, _) = hooks.use_state_with(|world| {
+        let mut state = game_client.game_state.lock();
+        let assets = world.resource(asset_cache());
+
+        // A single line wider than the quad, which makes it solid
+        let mat = GridMaterialKey {
+            major: vec2(1.0 / (4.0 * BLUEBOARD_SIZE), 0.0),
+            minor: Vec2::ZERO,
+            line_width: 4.0 * BLUEBOARD_SIZE,
+            size: BLUEBOARD_SIZE,
+        }
+        .get(assets);
+
+        WORLD_AXES.map(|(_, axis_color)| {
+            let entity = spawn_entity(&mut state.world, mat.clone());
+            state.world.set(entity, color(), axis_color).expect("Entity was despawned");
+            entity
+        })
+    });
+
+    {
+        let game_state = game_client.game_state.clone();
+        hooks.use_spawn(move |_| {
+            {
+                let state = game_state.lock();
+                let mut scope = state.world.resource(gizmos()).scope(origin_marker_scope());
+                scope.draw(GizmoPrimitive::sphere(Vec3::ZERO, ORIGIN_MARKER_RADIUS).with_color(Vec3::ONE));
+            }
+
+            Box::new(move |_| {
+                let mut state = game_state.lock();
+                for entity in entities {
+                    state.world.despawn(entity);
+                }
+            })
+        });
+    }
+    use_gizmo_scope(hooks, origin_marker_scope());
+
+    // Keep the lines facing the camera as it moves
+    hooks.use_frame(move |_| {
+        let mut state = game_client.game_state.lock();
+        let view = state.view().unwrap_or_default();
+        for (&entity, (axis, _)) in entities.iter().zip(WORLD_AXES) {
+            state.world.set(entity, local_to_world(), axis_billboard(view, axis, Vec3::ZERO)).ok();
+        }
+    });
+
+    Element::new()
+}
+
+const SELECTION_BOUNDS_COLOR: Vec3 = vec3(0.8, 0.8, 0.8);
+
+fn selection_bounds_scope() -> u64 {
+    line_hash!()
+}
+
+/// A box around the whole selection, with its edges from the lowest corner in the colors of the
+/// world axes, and a readout of its size along each axis.
+///
+/// Polled rather than keyed on a transform, so it follows the selection while it is moved
+#[element_component]
+pub fn SelectionBoundsGuide(hooks: &mut Hooks, targets: Arc<[EntityId]>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (size, set_size) = hooks.use_state(None as Option<Vec3>);
+
+    use_interval_deps(hooks, Duration::from_millis(100), true, targets, {
+        let game_state = game_client.game_state.clone();
+        move |targets| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(selection_bounds_scope());
+
+            let bounds = targets.iter().filter_map(|&id| calc_world_bounding_recursive(&state.world, id)).collect_vec();
+            let bounds = match AABB::unions(&bounds) {
+                Some(v) => v,
+                None => {
+                    set_size(None);
+                    return;
+                }
+            };
+
+            draw_bounds(&mut scope, &bounds, SELECTION_BOUNDS_COLOR);
+            let size = bounds.max - bounds.min;
+            for (axis, axis_color) in WORLD_AXES {
+                let end = bounds.min + axis * size;
+                scope.draw(GizmoPrimitive::line(bounds.min, end, 0.04).with_color(axis_color.truncate()));
+            }
+            set_size(Some(size));
+        }
+    });
+
+    use_gizmo_scope(hooks, selection_bounds_scope());
+
+    match size {
+        Some(size) => Text::el(format!("Selection X {:.2} Y {:.2} Z {:.2} m", size.x, size.y, size.z)),
+        None => Text::el("The selection has no bounds"),
     }
 }
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use ambient_core::runtime;
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::{client::GameClient, log_network_result};
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, StylesExt, Text, TextInput, STREET};
+use anyhow::{ensure, Context};
+
+use super::teleport::teleport_to_entities;
+use crate::{
+    intents::SelectMode,
+    rpc::{rpc_select, SelectMethod},
+    Selection,
+};
+
+/// The length of an id in its base64 form, as 16 bytes without padding
+const ID_LENGTH: usize = 22;
+
+/// Parses an id as it appears in logs, either as `AbCd..` or in its debug form `EntityId(AbCd.., 123)`
+fn parse_entity_id(text: &str) -> anyhow::Result<EntityId> {
+    let text = text.trim();
+    let text = match text.strip_prefix("EntityId(") {
+        Some(rest) => rest.split(|c: char| c == ',' || c == ')').next().unwrap_or_default().trim(),
+        None => text,
+    };
+    ensure!(text.len() == ID_LENGTH, "Expected an id of {ID_LENGTH} characters, got {}", text.len());
+    EntityId::from_base64(text).context("Invalid id")
+}
+
+/// Selects the entity with a pasted id and moves the camera to it, for finding entities mentioned
+/// in logs or bug reports
+#[element_component]
+pub fn JumpToEntityPanel(hooks: &mut Hooks, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (text, set_text) = hooks.use_state(String::new());
+    let (warning, set_warning) = hooks.use_state(None as Option<String>);
+
+    let runtime = hooks.world.resource(runtime()).clone();
+    let id = parse_entity_id(&text);
+
+    let submit = {
+        let on_close = on_close.clone();
+        let set_warning = set_warning.clone();
+        Arc::new(move |text: String| {
+            let id = match parse_entity_id(&text) {
+                Ok(v) => v,
+                Err(_) => return,
+            };
+
+            {
+                let mut state = game_client.game_state.lock();
+                if !state.world.exists(id) {
+                    set_warning(Some(format!("No entity with the id {id} exists")));
+                    return;
+                }
+                teleport_to_entities(&mut state, &[id]);
+            }
+
+            let game_client = game_client.clone();
+            runtime.spawn(async move {
+                log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Manual(Selection::new(vec![id])), SelectMode::Set)).await);
+            });
+            on_close();
+        })
+    };
+
+    FlowColumn::el([
+        Text::el("Jump to entity").section_style(),
+        TextInput::new(
+            text.clone(),
+            cb(move |v| {
+                set_text(v);
+                set_warning(None);
+            }),
+        )
+        .on_submit({
+            let submit = submit.clone();
+            move |text| submit(text)
+        })
+        .placeholder(Some("Entity id"))
+        .autofocus()
+        .el(),
+        match (&id, warning) {
+            (_, Some(warning)) => Text::el(warning).error_text_style(),
+            (Ok(_), None) => Text::el("Enter or Go to select the entity and move the camera to it").small_style(),
+            (Err(_), None) if text.trim().is_empty() => Text::el("Paste an id from the logs").small_style(),
+            (Err(err), None) => Text::el(format!("{err:#}")).error_text_style(),
+        },
+        FlowRow::el([
+            Button::new("Go", move |_| submit(text.clone())).disabled(id.is_err()).style(ButtonStyle::Primary).el(),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use ambient_core::transform::get_world_transform;
+use ambient_ecs::{EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::client::GameClient;
+use ambient_prefab::prefab_from_url;
+use ambient_std::Cb;
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use glam::Mat4;
+use itertools::Itertools;
+
+use super::{
+    batch::{use_batch_guard, PendingBatch},
+    transform::current_transforms,
+};
+use crate::intents::{intent_set_transform, IntentTransform, TerrainOffset};
+
+/// An entity of a copied layout, as its transform relative to the midpoint of the copied selection
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutItem {
+    pub transform: Mat4,
+    pub url: Option<String>,
+}
+
+/// The layout last copied, kept across selections so that it can be applied to other entities
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CopiedLayout(pub Option<Arc<[LayoutItem]>>);
+
+/// The targets which have a transform, as the others can neither be copied nor moved
+fn with_transform(world: &World, targets: &[EntityId]) -> Vec<EntityId> {
+    targets.iter().copied().filter(|&id| get_world_transform(world, id).is_ok()).collect_vec()
+}
+
+fn copy_layout(world: &World, targets: &[EntityId]) -> Arc<[LayoutItem]> {
+    let targets = with_transform(world, targets);
+    let initial = current_transforms(world, &targets);
+    let to_relative = Mat4::from_translation(-initial.midpoint);
+    targets
+        .iter()
+        .zip(&initial.transforms)
+        .map(|(&id, &transform)| LayoutItem { transform: to_relative * transform, url: world.get_cloned(id, prefab_from_url()).ok() })
+        .collect()
+}
+
+/// Pairs each item of `layout` with one of `targets`, either in order or with the first unused
+/// target of the same object. Items and targets without a match are left out
+fn match_layout(world: &World, layout: &[LayoutItem], targets: &[EntityId], by_object: bool) -> Vec<(EntityId, Mat4)> {
+    if !by_object {
+        return targets.iter().copied().zip(layout.iter().map(|item| item.transform)).collect_vec();
+    }
+
+    let mut unused = targets.iter().map(|&id| (id, world.get_cloned(id, prefab_from_url()).ok())).collect_vec();
+    layout
+        .iter()
+        .filter_map(|item| {
+            let index = unused.iter().position(|(_, url)| *url == item.url)?;
+            Some((unused.remove(index).0, item.transform))
+        })
+        .collect_vec()
+}
+
+/// Copies the arrangement of the selection relative to its midpoint, and applies a copied
+/// arrangement around the midpoint of another selection
+#[element_component]
+pub fn LayoutPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (CopiedLayout(layout), set_layout) = hooks.consume_context::<CopiedLayout>().unwrap();
+    let (by_object, set_by_object) = hooks.use_state(false);
+    let guard_batch = use_batch_guard(hooks);
+
+    let (matched, midpoint) = {
+        let state = game_client.game_state.lock();
+        let movable = with_transform(&state.world, &targets);
+        let matched = layout.as_ref().map(|layout| match_layout(&state.world, layout, &movable, by_object)).unwrap_or_default();
+        (matched, current_transforms(&state.world, &movable).midpoint)
+    };
+
+    let status = match &layout {
+        None => Text::el("Copy the layout of a selection to apply it to another").small_style(),
+        Some(layout) if matched.len() < layout.len().max(targets.len()) => Text::el(format!(
+            "The layout has {} entities and {} are selected, only {} will be moved",
+            layout.len(),
+            targets.len(),
+            matched.len()
+        ))
+        .error_text_style(),
+        Some(layout) => Text::el(format!("Moves the {} selected entities into the copied layout", layout.len())).small_style(),
+    };
+
+    FlowColumn::el([
+        Text::el("Layout").section_style(),
+        status,
+        FlowRow::el([
+            Button::new("In order", move |_| set_by_object(false)).toggled(!by_object).el(),
+            Button::new("By object", move |_| set_by_object(true))
+                .toggled(by_object)
+                .tooltip("Match entities showing the same object")
+                .el(),
+        ])
+        .set(space_between_items(), STREET),
+        FlowRow::el([
+            Button::new("Copy layout", {
+                let game_client = game_client.clone();
+                let targets = targets.clone();
+                move |_| set_layout(CopiedLayout(Some(copy_layout(&game_client.game_state.lock().world, &targets))))
+            })
+            .disabled(targets.is_empty())
+            .el(),
+            Button::new("Apply layout", {
+                let on_close = on_close.clone();
+                move |world| {
+                    let (entities, transforms): (Vec<_>, Vec<_>) =
+                        matched.iter().map(|&(id, transform)| (id, Mat4::from_translation(midpoint) * transform)).unzip();
+                    let intent = IntentTransform { entities: entities.clone(), transforms, terrain_offset: TerrainOffset::Update };
+                    guard_batch(
+                        world,
+                        PendingBatch::intent("Apply layout to", entities.into(), game_client.clone(), intent_set_transform(), intent),
+                    );
+                    on_close();
+                }
+            })
+            .disabled(matched.is_empty())
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Close", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use ambient_core::{
+    mouse_position, selectable,
+    transform::{get_world_position, local_to_world, translation},
+    window_scale_factor,
+};
+use ambient_ecs::{query, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_input::MouseButton;
+use ambient_network::{
+    client::{ClientGameState, GameClient},
+    is_remote_entity,
+};
+use ambient_std::color::Color;
+use ambient_ui::{
+    layout::{height, width},
+    space_between_items, use_interval, FlowColumn, StylesExt, Text, UIBase, UIExt, STREET,
+};
+use glam::{vec2, vec3, Vec2, Vec3, Vec3Swizzles};
+use itertools::Itertools;
+
+use super::{camera_hud::read_camera, teleport::teleport_camera};
+use crate::organization_color;
+
+/// The width and height of the map, in logical pixels
+const MAP_SIZE: f32 = 200.;
+/// Entities are merged into one dot per cell of a grid this many cells across, so that the map
+/// stays cheap to draw in large scenes
+const MAP_CELLS: f32 = 64.;
+const DOT_COLOR: Vec3 = vec3(0.6, 0.6, 0.6);
+const CAMERA_COLOR: Vec3 = vec3(1., 0.8, 0.2);
+
+/// The part of the world the map covers, as the center and the distance from it to each edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MapArea {
+    center: Vec3,
+    half_size: f32,
+}
+
+impl MapArea {
+    /// Where `p` lies on the map, with north up
+    fn to_map(self, p: Vec3) -> Vec2 {
+        (vec2(p.x - self.center.x, self.center.y - p.y) / self.half_size * 0.5 + 0.5) * MAP_SIZE
+    }
+
+    fn to_world(self, p: Vec2) -> Vec3 {
+        let p = (p / MAP_SIZE - 0.5) * 2. * self.half_size;
+        vec3(self.center.x + p.x, self.center.y - p.y, self.center.z)
+    }
+}
+
+/// A dot on the map, standing in for every entity in a cell
+#[derive(Debug, Clone, PartialEq)]
+struct MapDot {
+    position: Vec2,
+    color: Vec3,
+    count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MapContent {
+    area: MapArea,
+    dots: Vec<MapDot>,
+    /// Points along the heading and the left and right edges of the view of the camera
+    camera: Vec<Vec2>,
+}
+
+fn read_map(state: &ClientGameState) -> Option<MapContent> {
+    let positions = query((selectable(), local_to_world()))
+        .incl(is_remote_entity())
+        .iter(&state.world, None)
+        .map(|(id, (_, transform))| (id, transform.w_axis.truncate()))
+        .collect_vec();
+    let camera = read_camera(state)?;
+
+    let (min, max) = positions
+        .iter()
+        .map(|(_, p)| *p)
+        .chain([camera.position])
+        .fold((Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)), |(min, max), p| (min.min(p), max.max(p)));
+    let area = MapArea { center: (min + max) / 2., half_size: ((max - min).xy().max_element() / 2. * 1.1).max(10.) };
+
+    let cells = positions.iter().fold(HashMap::<(i32, i32), (Vec2, Vec3, usize)>::new(), |mut cells, &(id, p)| {
+        let position = area.to_map(p);
+        let cell = (position / MAP_SIZE * MAP_CELLS).floor();
+        let color = state.world.get(id, organization_color()).unwrap_or(DOT_COLOR);
+        let entry = cells.entry((cell.x as i32, cell.y as i32)).or_insert((Vec2::ZERO, Vec3::ZERO, 0));
+        entry.0 += position;
+        entry.1 += color;
+        entry.2 += 1;
+        cells
+    });
+    let dots = cells
+        .into_values()
+        .map(|(position, color, count)| MapDot { position: position / count as f32, color: color / count as f32, count })
+        .collect();
+
+    let camera = {
+        let origin = area.to_map(camera.position);
+        [vec2(-1., 0.), vec2(0., 0.), vec2(1., 0.)]
+            .into_iter()
+            .filter_map(|clip| {
+                let dir = state.screen_ray(clip).dir.xy().normalize_or_zero();
+                (dir != Vec2::ZERO).then_some(dir)
+            })
+            .flat_map(|dir| (1..=5).map(move |step| origin + vec2(dir.x, -dir.y) * step as f32 * 4.))
+            .chain([origin])
+            .collect()
+    };
+
+    Some(MapContent { area, dots, camera })
+}
+
+fn square(center: Vec2, size: f32, color: Vec3, z: f32) -> Element {
+    UIBase
+        .el()
+        .with_background(Color::from(color.extend(1.)))
+        .set(translation(), (center - size / 2.).extend(z))
+        .set(width(), size)
+        .set(height(), size)
+}
+
+fn camera_target(world: &World, area: MapArea, map: EntityId) -> Vec3 {
+    let map_offset = get_world_position(world, map).unwrap_or_default().xy();
+    let scl = *world.resource(window_scale_factor()) as f32;
+    area.to_world(*world.resource(mouse_position()) / scl - map_offset)
+}
+
+/// A top-down map of the scene with a dot for where entities are, in their organization color, and
+/// where the camera is and what it sees. Clicking the map moves the camera there
+#[element_component]
+pub fn Minimap(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (content, set_content) = hooks.use_state_with(|_| read_map(&game_client.game_state.lock()));
+
+    use_interval(hooks, 0.5, {
+        let game_client = game_client.clone();
+        move || set_content(read_map(&game_client.game_state.lock()))
+    });
+
+    let content = match content {
+        Some(v) => v,
+        None => return Text::el("No camera to show on the map"),
+    };
+
+    let dots = content.dots.iter().map(|dot| {
+        let size = (2. + (dot.count as f32).log2()).min(6.);
+        square(dot.position, size, dot.color, -0.01)
+    });
+    let camera = content.camera.iter().map(|&p| square(p, 3., CAMERA_COLOR, -0.02));
+
+    let area = content.area;
+    FlowColumn::el([
+        Text::el("Map").section_style(),
+        UIBase
+            .el()
+            .with_background(Color::rgba(0., 0., 0., 0.5))
+            .set(width(), MAP_SIZE)
+            .set(height(), MAP_SIZE)
+            .with_clickarea()
+            .on_mouse_down(move |world, id, button| {
+                if button == MouseButton::Left {
+                    teleport_camera(&mut game_client.game_state.lock(), camera_target(world, area, id));
+                }
+            })
+            .el()
+            .children(dots.chain(camera).collect()),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use ambient_core::{get_mouse_clip_space_position, runtime};
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_std::{
+    cb, line_hash,
+    shapes::{Plane, RayIntersectable},
+    Cb,
+};
+use ambient_ui::{space_between_items, Button, Editor, EditorRow, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use glam::{vec2, Mat3, Mat4, Quat, Vec3};
+use itertools::Itertools;
+use winit::event::VirtualKeyCode;
+
+use super::{
+    spawn_guard::GuardedSpawnButton,
+    transform::{axes_frame, current_transforms},
+    use_gizmo_scope,
+};
+use crate::{
+    intents::{intent_duplicate, IntentDuplicate},
+    ui::EditorPrefs,
+};
+
+const AXES: [Vec3; 3] = [Vec3::X, Vec3::Y, Vec3::Z];
+
+fn preview_scope() -> u64 {
+    line_hash!()
+}
+
+/// Mirrors `transforms` across the plane through `point` with the unit `normal`.
+///
+/// The copies keep a positive scale, as negative scales are not supported by physics, so the
+/// placement and orientation are mirrored but the shape of an asymmetric object is not
+fn mirror_transforms(transforms: &[Mat4], point: Vec3, normal: Vec3) -> Vec<Mat4> {
+    // Householder reflection
+    let reflect = Mat3::IDENTITY - 2. * Mat3::from_cols(normal * normal.x, normal * normal.y, normal * normal.z);
+
+    transforms
+        .iter()
+        .map(|transform| {
+            let (scl, rot, pos) = transform.to_scale_rotation_translation();
+            let pos = point + reflect * (pos - point);
+            let rot = Quat::from_mat3(&(reflect * Mat3::from_quat(rot) * reflect));
+            Mat4::from_scale_rotation_translation(scl, rot.normalize(), pos)
+        })
+        .collect_vec()
+}
+
+/// Duplicates the selection mirrored across a plane, with a preview of the copies drawn as gizmos
+#[element_component]
+pub fn MirrorPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let initial = hooks.use_memo_with(targets.clone(), |_, targets| current_transforms(&game_client.game_state.lock().world, targets));
+    let center = initial.bounds.map(|v| v.center()).unwrap_or(initial.midpoint);
+    let (axis, set_axis) = hooks.use_state(0_usize);
+    let (point, set_point) = hooks.use_state(center);
+
+    // The plane follows the axes of the coordinate space, such as the orientation of the last
+    // selected entity
+    let normal = match (initial.transforms.last(), targets.last()) {
+        (Some(&transform), Some(&active)) => {
+            let frame = axes_frame(&game_client.game_state.lock().world, prefs.coordinates, active, transform);
+            frame.transform_vector3(AXES[axis]).normalize()
+        }
+        _ => AXES[axis],
+    };
+    let copies = mirror_transforms(&initial.transforms, point, normal);
+
+    use_gizmo_scope(hooks, preview_scope());
+    hooks.use_effect((copies.clone(), initial.transforms.clone(), point, normal, prefs.preview_color), {
+        let game_state = game_client.game_state.clone();
+        move |_, (copies, transforms, point, normal, preview_color)| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(preview_scope());
+
+            scope.draw(GizmoPrimitive::wire_rect(*point, vec2(5., 5.), 0., 0., 0.03, *normal).with_color(*preview_color));
+            for (original, copy) in transforms.iter().zip(copies) {
+                let copy = copy.transform_point3(Vec3::ZERO);
+                scope.draw(GizmoPrimitive::line(original.transform_point3(Vec3::ZERO), copy, 0.01).with_color(*preview_color));
+                scope.draw(GizmoPrimitive::sphere(copy, 0.25).with_color(*preview_color));
+            }
+
+            Box::new(|_| {})
+        }
+    });
+
+    let axis_button = |index: usize, label: &str| {
+        let set_axis = set_axis.clone();
+        Button::new(label.to_string(), move |_| set_axis(index)).toggled(axis == index).el()
+    };
+
+    FlowColumn::el([
+        Text::el("Duplicate and mirror").section_style(),
+        EditorRow::el("Plane normal", FlowRow::el([axis_button(0, "X"), axis_button(1, "Y"), axis_button(2, "Z")])),
+        EditorRow::el("Plane point", point.editor(set_point.clone(), Default::default())),
+        FlowRow::el([
+            Button::new("Through center", {
+                let set_point = set_point.clone();
+                move |_| set_point(center)
+            })
+            .el(),
+            Button::new("Through cursor", {
+                let game_client = game_client.clone();
+                move |world| {
+                    // Where the cursor hits the horizontal plane through the selection
+                    let ray = game_client.game_state.lock().screen_ray(get_mouse_clip_space_position(world));
+                    let plane = Plane::from_normal_and_point(Vec3::Z, center).expect("Invalid mirror plane");
+                    if let Some(dist) = plane.ray_intersect(ray) {
+                        set_point(prefs.snap(ray.origin + ray.dir * dist));
+                    }
+                }
+            })
+            .tooltip("Hover the viewport and press M")
+            .hotkey(VirtualKeyCode::M)
+            .el(),
+        ])
+        .set(space_between_items(), STREET),
+        FlowRow::el([
+            GuardedSpawnButton::el(
+                "Create".to_string(),
+                copies.len(),
+                cb({
+                    let on_close = on_close.clone();
+                    let copies = copies.clone();
+                    move |world| {
+                        world.resource(runtime()).spawn(client_push_intent(
+                            game_client.clone(),
+                            intent_duplicate(),
+                            IntentDuplicate::batch(targets.iter().copied().zip(copies.clone()), true),
+                            None,
+                            None,
+                        ));
+                        on_close();
+                    }
+                }),
+            ),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -1,49 +1,158 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
-use ambient_core::{asset_cache, async_ecs::async_run, get_mouse_clip_space_position, runtime};
-use ambient_ecs::{Component, ComponentValue, EntityId};
-use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
-use ambient_input::{event_keyboard_input, MouseButton};
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    bounding::calc_world_bounding_recursive,
+    get_mouse_clip_space_position, runtime,
+    transform::{get_world_transform, rotation, scale, translation},
+    window::WindowCtl,
+    window_ctl,
+};
+use ambient_ecs::{Component, ComponentValue, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_gizmos::gizmos;
+use ambient_input::{event_keyboard_input, picking::picker_intersecting, MouseButton};
 use ambient_intent::{client_push_intent, rpc_undo_head_exact};
-use ambient_network::client::GameClient;
+use ambient_network::client::{ClientGameState, GameClient};
 use ambient_sys::task::RuntimeHandle;
 use derive_more::Display;
 use futures_signals::signal::SignalExt;
+use glam::{Mat4, Quat, Vec2, Vec3};
 use itertools::Itertools;
+use parking_lot::Mutex;
 
 use ambient_std::{
     asset_url::{select_asset, AssetType},
-    cb, friendly_id, Cb,
+    cb, friendly_id,
+    shapes::AABB,
+    Cb,
 };
 use ambient_ui::{
     command_modifier,
     layout::{docking, width, Docking},
-    margin, padding, space_between_items, use_interval_deps, Borders, Button, ButtonStyle, Dock, FlowRow, Hotkey, ScreenContainer,
-    Separator, StylesExt, STREET,
+    margin, padding, space_between_items, use_interval, use_interval_deps, Borders, Button, ButtonStyle, Dock, DropdownSelect, FlowRow,
+    Hotkey, ScreenContainer, StylesExt, Text, STREET,
 };
 use tokio::time::sleep;
-use winit::event::{ElementState, VirtualKeyCode};
+use winit::{
+    event::{ElementState, ModifiersState, VirtualKeyCode},
+    window::CursorIcon,
+};
 
-use super::{terrain_mode::GenerateTerrainButton, EditorPlayerInputHandler, EditorPrefs};
+use super::{
+    terrain_mode::GenerateTerrainButton, ActionThrottles, CoordinateSpace, DefaultTransformMode, DeleteHotkey, EditorPlayerInputHandler,
+    EditorPrefs, OverlapCheck,
+};
 use crate::{
-    intents::{intent_delete, intent_duplicate, intent_spawn_object, IntentDuplicate, IntentSpawnObject, SelectMode},
+    intents::{
+        intent_delete, intent_duplicate, intent_spawn_anchor, intent_spawn_object, IntentDuplicate, IntentSpawnAnchor, IntentSpawnObject,
+        SelectMode, SurfaceConstraint,
+    },
+    transform_pinned,
     ui::use_player_selection,
     Selection, GRID_SIZE,
 };
 
+mod align_axis;
+mod anchor;
+mod apply_transform;
+mod arrange;
+mod batch;
+mod bookmarks;
+mod camera_hud;
+mod cluster;
+mod color_by;
+mod construction;
 mod entity_browser;
+mod expression;
+mod ghost;
 mod grid_material;
 mod guide;
+mod jump_to;
+mod layout;
+mod minimap;
+mod mirror;
+mod nudge;
+mod object_url;
+mod overlap;
+mod palette;
+mod paste_table;
+mod pin;
+mod pivot;
+mod prefs_screen;
+mod radial_array;
+mod recorder;
+mod reference_image;
+mod replace_object;
+mod review;
 mod select_area;
 mod selection_panel;
+mod session;
+mod set_origin;
+mod spawn_at;
+mod spawn_guard;
+mod stamp;
+mod tags;
+mod teleport;
+mod toast;
+mod toolbar;
 mod transform;
+mod volume;
 
+use anchor::*;
+use camera_hud::*;
+use ghost::*;
 use guide::*;
+use overlap::*;
+use pin::*;
+use pivot::*;
 use select_area::*;
 use selection_panel::*;
+use tags::*;
+use teleport::*;
+use toast::*;
+use toolbar::*;
 use transform::*;
 
-use self::entity_browser::EntityBrowserScreen;
+use self::{
+    align_axis::AlignAxisPanel,
+    apply_transform::ApplyTransformPanel,
+    arrange::ArrangePanel,
+    batch::{use_batch_guard, BatchConfirmPanel, PendingBatch, PendingBatchState},
+    bookmarks::{BookmarkHotkeys, BookmarksPanel},
+    cluster::ClusterPanel,
+    color_by::ColorByPanel,
+    construction::{ConstructionGuidesOverlay, ConstructionGuidesPanel},
+    entity_browser::EntityBrowserScreen,
+    expression::ExpressionPanel,
+    jump_to::JumpToEntityPanel,
+    layout::{CopiedLayout, LayoutPanel},
+    minimap::Minimap,
+    mirror::MirrorPanel,
+    nudge::NudgePanel,
+    object_url::ObjectUrlValidator,
+    palette::PalettePanel,
+    paste_table::PasteTableScreen,
+    prefs_screen::EditorPrefsScreen,
+    radial_array::RadialArrayPanel,
+    recorder::RecorderPanel,
+    reference_image::{ReferenceImageOverlay, ReferenceImagePanel},
+    replace_object::ReplaceObjectPanel,
+    review::{ReviewMarkers, ReviewMarkersPanel},
+    session::SessionKeeper,
+    set_origin::SetOriginPanel,
+    spawn_at::SpawnAtPanel,
+    stamp::StampController,
+    volume::VolumeController,
+};
 
 /// An editor can only be in one action at a time.
 /// They can be confirmed or aborted.
@@ -51,15 +160,41 @@ use self::entity_browser::EntityBrowserScreen;
 /// **Note**: Storing an EditorAction in the ecs may cause events to not undo for a time after the
 /// entity is removed.
 ///
-/// This is due to the builtin drop/removed events queue keeping the value alive
+/// This is due to the builtin drop/removed events queue keeping the value alive. Cancelling
+/// retries the undo until the last throttled push has arrived, and the progress is shown by
+/// [`PendingUndoIndicator`].
 pub struct EditorAction<T: ComponentValue> {
     id: Option<String>,
     client: GameClient,
     runtime: RuntimeHandle,
     tx: futures_signals::signal::Sender<Option<(String, T)>>,
     intent: Component<T>,
+    throttle: Duration,
+    /// The last pushed argument, so that it can be finalized on confirm
+    last: Option<T>,
+    /// Set for deferred actions, which only move a local preview until they are confirmed
+    preview: Option<DeferredPreview<T>>,
+    /// The translation, rotation and scale of each entity moved by the preview, so that they can
+    /// be put back when the action is cancelled
+    replaced: HashMap<EntityId, (Vec3, Quat, Vec3)>,
 }
 
+/// Computes the entities a deferred action moves for an argument, along with their new world
+/// transforms
+pub type DeferredPreview<T> = Arc<dyn Fn(&World, &T) -> Vec<(EntityId, Mat4)> + Sync + Send>;
+
+/// How many times a cancelled action tries to undo its intent before giving up
+const CANCEL_ATTEMPTS: usize = 5;
+
+/// How long newly selected entities which don't resolve are retried every frame, before leaving
+/// them to the periodic resolve
+const MISSING_RETRY_PERIOD: Duration = Duration::from_secs(2);
+
+/// Cancelled actions which have not been undone yet
+static PENDING_CANCELS: AtomicUsize = AtomicUsize::new(0);
+/// Cancelled actions which could not be undone
+static FAILED_CANCELS: AtomicUsize = AtomicUsize::new(0);
+
 impl<T: ComponentValue> std::fmt::Debug for EditorAction<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("EditorAction").field("id", &self.id).field("intent", &self.intent).finish()
@@ -67,7 +202,9 @@ impl<T: ComponentValue> std::fmt::Debug for EditorAction<T> {
 }
 
 impl<T: ComponentValue> EditorAction<T> {
-    pub fn new(runtime: RuntimeHandle, client: GameClient, intent: Component<T>, throttle: Duration) -> Self {
+    /// Creates an action which pushes `intent`, throttled according to its kind of operation
+    pub fn new(runtime: RuntimeHandle, client: GameClient, intent: Component<T>, throttles: &ActionThrottles) -> Self {
+        let throttle = throttles.get(intent.index());
         let (tx, rx) = futures_signals::signal::channel(None);
 
         {
@@ -84,28 +221,109 @@ impl<T: ComponentValue> EditorAction<T> {
             });
         }
 
-        Self { client, id: None, runtime, intent, tx }
+        Self { client, id: None, runtime, intent, tx, throttle, last: None, preview: None, replaced: HashMap::new() }
+    }
+
+    /// Defers the action when `preview` is given: pushed arguments only move a local preview,
+    /// and a single intent is pushed on confirm rather than streaming them.
+    ///
+    /// This keeps dragging smooth on slow connections, at the cost of other players not seeing
+    /// the drag until it is released
+    pub fn deferred(mut self, preview: Option<DeferredPreview<T>>) -> Self {
+        self.preview = preview;
+        self
     }
 
     #[tracing::instrument(skip_all, level = "info")]
     pub fn push_intent(&mut self, arg: T) {
+        self.last = Some(arg.clone());
+
+        if let Some(preview) = &self.preview {
+            let mut state = self.client.game_state.lock();
+            let world = &mut state.world;
+            for (id, transform) in preview(world, &arg) {
+                self.replaced.entry(id).or_insert_with(|| {
+                    (
+                        world.get(id, translation()).unwrap_or_default(),
+                        world.get(id, rotation()).unwrap_or_default(),
+                        world.get(id, scale()).unwrap_or(Vec3::ONE),
+                    )
+                });
+                let (scl, rot, pos) = transform.to_scale_rotation_translation();
+                world.set_if_changed(id, translation(), pos).ok();
+                world.set_if_changed(id, rotation(), rot).ok();
+                world.set_if_changed(id, scale(), scl).ok();
+            }
+            return;
+        }
+
         let id = self.id.get_or_insert_with(friendly_id).clone();
         let _ = self.tx.send(Some((id, arg)));
     }
 
     #[tracing::instrument(level = "info")]
     pub fn confirm(&mut self) {
-        self.id = None
+        if self.preview.is_some() {
+            // The preview is left in place, the server sends the same transforms back
+            if let Some(arg) = self.last.take() {
+                let _ = self.tx.send(Some((friendly_id(), arg)));
+            }
+            self.replaced.clear();
+        }
+        self.id = None;
+        self.last = None;
+    }
+
+    /// Confirms the action, first pushing the argument returned by `finalize` in place of the
+    /// last pushed argument, if any
+    pub fn confirm_with(&mut self, finalize: impl FnOnce(T) -> Option<T>) {
+        if let Some(arg) = self.last.clone().and_then(finalize) {
+            self.push_intent(arg);
+        }
+        self.confirm()
     }
 
     #[tracing::instrument(level = "info")]
     pub fn cancel(&self) {
+        if !self.replaced.is_empty() {
+            // Nothing was pushed, so only the preview has to be undone
+            let mut state = self.client.game_state.lock();
+            for (&id, &(pos, rot, scl)) in &self.replaced {
+                state.world.set_if_changed(id, translation(), pos).ok();
+                state.world.set_if_changed(id, rotation(), rot).ok();
+                state.world.set_if_changed(id, scale(), scl).ok();
+            }
+        }
+
         let id = self.id.clone();
         if let Some(id) = id {
             tracing::info!("Cancelling action: {id}");
             let client = self.client.clone();
+            let throttle = self.throttle;
+            PENDING_CANCELS.fetch_add(1, Ordering::SeqCst);
             self.runtime.spawn(async move {
-                client.rpc(rpc_undo_head_exact, id).await.unwrap();
+                // The last push may still be waiting on the throttle, or be queued behind the
+                // removal of an entity, in which case it is not the head yet
+                let mut undone = false;
+                for _ in 0..CANCEL_ATTEMPTS {
+                    match client.rpc(rpc_undo_head_exact, id.clone()).await {
+                        Ok(Some(())) => {
+                            undone = true;
+                            break;
+                        }
+                        Ok(None) => sleep(throttle.max(Duration::from_millis(50))).await,
+                        Err(err) => {
+                            tracing::error!("Failed to cancel action {id}: {err:?}");
+                            break;
+                        }
+                    }
+                }
+
+                if !undone {
+                    tracing::warn!("Action {id} could not be undone");
+                    FAILED_CANCELS.fetch_add(1, Ordering::SeqCst);
+                }
+                PENDING_CANCELS.fetch_sub(1, Ordering::SeqCst);
             });
         }
     }
@@ -128,97 +346,264 @@ impl ElementComponent for EditorBuildMode {
 
         let set_select_mode = hooks.provide_context(|| SelectMode::Set);
         let set_srt_mode = hooks.provide_context(|| None as Option<TransformMode>);
+        hooks.provide_context(ToolbarGroupsState::default);
+        hooks.provide_context(TagFilter::default);
+        let set_selection_lock = hooks.provide_context(SelectionLock::default);
+        hooks.provide_context(PivotEntity::default);
+        let set_selection_pivot = hooks.provide_context(SelectionPivot::default);
+        hooks.use_effect(selection.clone(), move |_, _| {
+            set_selection_pivot(SelectionPivot::default());
+            Box::new(|_| {})
+        });
+        hooks.provide_context(CopiedLayout::default);
+        hooks.provide_context(PendingBatchState::default);
+        let (PendingBatchState(pending_batch), _) = hooks.consume_context::<PendingBatchState>().unwrap();
+        let guard_batch = use_batch_guard(hooks);
+        let (PivotEntity(pivot_entity), _) = hooks.consume_context::<PivotEntity>().unwrap();
+        let (SelectionLock(selection_locked), _) = hooks.consume_context::<SelectionLock>().unwrap();
+        let (select_mode, _) = hooks.consume_context::<SelectMode>().unwrap();
+        // The mode used while neither Shift nor Control is held, so that adding to or removing from the
+        // selection doesn't require holding a modifier
+        let (sticky_select_mode, set_sticky_select_mode) = hooks.use_state(SelectMode::Set);
+        let (TagFilter(tag_filter), _) = hooks.consume_context::<TagFilter>().unwrap();
         let (screen, set_screen) = hooks.use_state(None);
 
         let targets = hooks.use_ref_with::<Arc<[EntityId]>>(|_| Arc::from([]));
+        let last_srt_mode = hooks.use_ref_with::<Option<TransformMode>>(|_| None);
+        let (missing, set_missing) = hooks.use_state(Vec::<EntityId>::new());
+        // The selection whose missing entities are retried every frame, and since when
+        let retry_since = hooks.use_ref_with::<Option<(Selection, Instant)>>(|_| None);
         let rerender = hooks.use_rerender_signal();
+        let (prefs, set_prefs) = hooks.consume_context::<EditorPrefs>().unwrap();
+        let (toast, set_toast) = hooks.use_state(None as Option<String>);
+        let (tool_panel, set_tool_panel) = hooks.use_state(None as Option<ToolPanel>);
+        // The last submitted nudge, offered again for repeating it
+        let (last_nudge, set_last_nudge) = hooks.use_state("0 0 0".to_string());
+        let (validator, _) = hooks.use_state_with(|_| ObjectUrlValidator::default());
+        // Opens `panel` in place of the one which is open, or closes it if it's the one open
+        let toggle_panel = |panel: ToolPanel| {
+            let set_tool_panel = set_tool_panel.clone();
+            move |_: &mut World| set_tool_panel(if tool_panel == Some(panel) { None } else { Some(panel) })
+        };
 
         {
             let game_state = game_client.game_state.clone();
             let targets = targets.clone();
-            let mut prev = None;
+            let prev = Arc::new(Mutex::new(None));
 
-            let update_targets = move |selection: &Selection| {
+            // Invoked with the uids of the selection which no longer resolve, such as when they
+            // were deleted by another client or failed to load
+            let on_missing: Cb<dyn Fn(Vec<EntityId>) + Sync + Send> = cb(move |missing| set_missing(missing));
+
+            let update_targets = Arc::new(move |selection: &Selection| {
                 profiling::scope!("update_targets");
                 let state = game_state.lock();
 
-                let res = selection.iter().filter(|id| state.world.exists(*id)).collect_vec();
+                let (res, missing): (Vec<_>, Vec<_>) = selection.iter().partition(|id| state.world.exists(*id));
 
+                let mut prev = prev.lock();
                 if Some(&res) != prev.as_ref() {
                     tracing::info!("Resolving targets: {selection:?} => {res:?}");
-                    prev = Some(res.clone());
+                    if !missing.is_empty() {
+                        tracing::warn!("Failed to resolve selected entities: {missing:?}");
+                    }
+                    *prev = Some(res.clone());
                     *targets.lock() = res.into();
+                    on_missing(missing);
                     rerender();
                 }
-            };
+            });
+
+            // Resolves immediately when the selection changes, and periodically to catch external deletions
+            use_interval_deps(
+                hooks,
+                prefs.resolve_interval,
+                true,
+                (selection.clone(), prefs.resolve_interval),
+                closure!(clone update_targets, |(selection, _)| update_targets(selection)),
+            );
 
-            use_interval_deps(hooks, Duration::from_millis(2000), true, selection.clone(), update_targets);
+            // Newly selected entities may not have been replicated yet, so keep resolving every
+            // frame for a short while rather than waiting for the next tick. Ones which still don't
+            // resolve are most likely gone, and are left to the tick
+            let mut retry_since = retry_since.lock();
+            if missing.is_empty() {
+                *retry_since = None;
+            } else {
+                let since = match retry_since.take() {
+                    Some((retried, since)) if retried == selection => since,
+                    _ => Instant::now(),
+                };
+                *retry_since = Some((selection.clone(), since));
+                let selection = selection.clone();
+                hooks.use_frame(move |_| {
+                    if since.elapsed() < MISSING_RETRY_PERIOD {
+                        update_targets(&selection)
+                    }
+                });
+            }
         }
-        hooks.use_world_event(move |_world, event| {
+        hooks.use_world_event(closure!(clone set_select_mode, |_world, event| {
             if let Some(event) = event.get_ref(event_keyboard_input()) {
                 match event.keycode {
                     Some(VirtualKeyCode::LShift) => {
                         if event.state == ElementState::Pressed {
                             set_select_mode(SelectMode::Add);
                         } else {
-                            set_select_mode(SelectMode::Set);
+                            set_select_mode(sticky_select_mode);
                         }
                     }
                     Some(VirtualKeyCode::LControl) => {
                         if event.state == ElementState::Pressed {
                             set_select_mode(SelectMode::Remove);
                         } else {
-                            set_select_mode(SelectMode::Set);
+                            set_select_mode(sticky_select_mode);
                         }
                     }
                     _ => {}
                 }
             }
-        });
+        }));
 
         // Make sure to get the value *after* the `use_interval_deps`
         let targets = targets.lock();
+        let open_panel = match tool_panel {
+            Some(panel) if !panel.needs_selection() || !targets.is_empty() => {
+                let close = cb(closure!(clone set_tool_panel, || set_tool_panel(None)));
+                let key = format!("{selection:?}");
+                let el = match panel {
+                    ToolPanel::RadialArray => RadialArrayPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::Cluster => ClusterPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::Arrange => ArrangePanel::el(targets.clone(), close).key(key),
+                    ToolPanel::AlignAxis => AlignAxisPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::Layout => LayoutPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::ApplyTransform => ApplyTransformPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::Mirror => MirrorPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::Expression => ExpressionPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::SpawnAt => SpawnAtPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::Replace => {
+                        let active = targets[targets.len() - 1];
+                        ReplaceObjectPanel::el(active, close).key(format!("{active:?}"))
+                    }
+                    ToolPanel::Palette => PalettePanel::el(targets.clone(), close),
+                    ToolPanel::SetOrigin => SetOriginPanel::el(targets.clone(), close).key(key),
+                    ToolPanel::Nudge => NudgePanel::el(
+                        targets.clone(),
+                        last_nudge,
+                        cb(closure!(clone set_tool_panel, |submitted: Option<String>| {
+                            if let Some(submitted) = submitted {
+                                set_last_nudge(submitted);
+                            }
+                            set_tool_panel(None);
+                        })),
+                    )
+                    .key(key),
+                    ToolPanel::JumpTo => JumpToEntityPanel::el(close),
+                    ToolPanel::Review => ReviewMarkersPanel::el(close),
+                    ToolPanel::ColorBy => ColorByPanel::el(close),
+                    ToolPanel::Recorder => RecorderPanel::el(close),
+                    ToolPanel::Bookmarks => BookmarksPanel::el(close),
+                    ToolPanel::ReferenceImage => ReferenceImagePanel::el(close),
+                    ToolPanel::Construction => ConstructionGuidesPanel::el(targets.clone(), close),
+                };
+                side_panel(el, panel.side())
+            }
+            _ => Element::new(),
+        };
 
         Dock(vec![
             EditorPlayerInputHandler.el(),
-            ScreenContainer(screen).el(),
-            if !selection.is_empty() {
-                SelectionPanel { selection: selection.clone(), set_selection: set_selection.clone() }
-                    .el()
-                    .set(width(), 300.)
-                    .set(docking(), Docking::Right)
+            ModeCursor.el(),
+            GhostUnselected::el(targets.clone()),
+            PinnedMarkers::el(),
+            ReviewMarkers::el(),
+            OrganizationOutlines::el(targets.clone(), prefs.selection_highlight),
+            if prefs.overlaps != OverlapCheck::Off { OverlapHighlights::el(targets.clone(), prefs.overlaps) } else { Element::new() },
+            AnchorGlyphs::el(),
+            ReferenceImageOverlay::el(),
+            ConstructionGuidesOverlay::el(),
+            if let Some(id) = pivot_entity { PivotMarker::el(id) } else { Element::new() },
+            SessionKeeper::el(selection.clone(), cb(closure!(clone set_toast, |message| set_toast(Some(message))))),
+            BookmarkHotkeys::el(),
+            CameraStepHotkeys::el(),
+            SelectVisibleHotkeys::el(),
+            if prefs.show_world_axes { WorldAxesGuide::el() } else { Element::new() },
+            PendingUndoIndicator.el().set(docking(), Docking::Bottom).set(margin(), Borders::even(STREET)),
+            Hotkey::new(
+                prefs.input_layout.select_mode_key(),
+                move |_| {
+                    let next = next_sticky_select_mode(sticky_select_mode);
+                    // A held modifier keeps overriding until it is released
+                    if select_mode_label(select_mode) == select_mode_label(sticky_select_mode) {
+                        set_select_mode(next);
+                    }
+                    set_sticky_select_mode(next);
+                },
+                Element::new(),
+            )
+            .el(),
+            if !matches!(sticky_select_mode, SelectMode::Set) {
+                let key = prefs.input_layout.select_mode_key();
+                Text::el(format!("\u{f245} Selecting: {} ({key:?} to cycle)", select_mode_label(sticky_select_mode)))
+                    .small_style()
                     .floating_panel()
-                    .set(margin(), Borders::even(STREET))
                     .set(padding(), Borders::even(STREET))
+                    .set(docking(), Docking::Bottom)
+                    .set(margin(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
+            ScreenContainer(screen).el(),
+            if !selection.is_empty() {
+                side_panel(
+                    SelectionPanel { selection: selection.clone(), set_selection: set_selection.clone(), missing }.el(),
+                    Docking::Right,
+                )
             } else {
                 Element::new()
             },
             FlowRow({
-                let mut items = vec![
+                let mut create = vec![
                     Button::new("\u{f405}", {
                         let set_srt_mode = set_srt_mode.clone();
                         let game_client = game_client.clone();
+                        let set_toast = set_toast.clone();
+                        let validator = validator.clone();
+                        let targets = targets.clone();
                         move |world| {
                             let set_srt_mode = set_srt_mode.clone();
                             let game_client = game_client.clone();
+                            let set_toast = set_toast.clone();
+                            let validator = validator.clone();
+                            let targets = targets.clone();
                             let async_run = world.resource(async_run()).clone();
                             select_asset(world.resource(asset_cache()), AssetType::Prefab, move |object_url| {
                                 tracing::info!("got object_url: {object_url:?}");
                                 if let Some(object_url) = object_url.random().cloned() {
                                     async_run.run(move |world| {
                                         let set_srt_mode = set_srt_mode.clone();
-                                        let ray = {
-                                            game_client.game_state.lock().screen_ray(get_mouse_clip_space_position(world))
-                                        };
-                                        let position = ray.origin + ray.dir * 10.;
+                                        let (position, beside) = spawn_position(
+                                            &game_client.game_state.lock(),
+                                            &targets,
+                                            prefs,
+                                            get_mouse_clip_space_position(world),
+                                        );
+                                        let assets = world.resource(asset_cache()).clone();
                                         world.resource(runtime()).spawn(async move {
+                                            if let Err(err) = validator.validate(&assets, &object_url).await {
+                                                set_toast(Some(err));
+                                                return;
+                                            }
+
                                             client_push_intent(game_client, intent_spawn_object(), IntentSpawnObject {
                                                 object_url,
                                                 entity_id: EntityId::new(),
                                                 position,
                                                 select: true
                                             }, None, Some(Box::new(move || {
-                                                set_srt_mode(Some(TransformMode::Place));
+                                                if !beside {
+                                                    set_srt_mode(Some(TransformMode::Place));
+                                                }
                                             }))).await;
                                         });
                                     });
@@ -228,9 +613,32 @@ impl ElementComponent for EditorBuildMode {
                     })
                     .tooltip("Browse prefabs")
                     .style(ButtonStyle::Primary)
-                    .hotkey(VirtualKeyCode::Tab)
-                    .el(),
-                    Separator { vertical: true }.el(),
+                    .hotkey(VirtualKeyCode::Tab),
+                    Button::new("\u{f13d}", {
+                        let set_srt_mode = set_srt_mode.clone();
+                        let game_client = game_client.clone();
+                        let targets = targets.clone();
+                        move |world| {
+                            let set_srt_mode = set_srt_mode.clone();
+                            let (position, beside) =
+                                spawn_position(&game_client.game_state.lock(), &targets, prefs, get_mouse_clip_space_position(world));
+                            world.resource(runtime()).spawn(client_push_intent(
+                                game_client.clone(),
+                                intent_spawn_anchor(),
+                                IntentSpawnAnchor { entity_id: EntityId::new(), position, select: true },
+                                None,
+                                Some(Box::new(move || {
+                                    if !beside {
+                                        set_srt_mode(Some(TransformMode::Place))
+                                    }
+                                })),
+                            ));
+                        }
+                    })
+                    .tooltip("Create anchor"),
+                ];
+                let mut arrange = Vec::new();
+                let mut view = vec![
                     Button::new("\u{f03a}", {
                         let set_selection = set_selection.clone();
                         let set_screen = set_screen.clone();
@@ -254,12 +662,121 @@ impl ElementComponent for EditorBuildMode {
                             ));
                         }
                     })
-                    .tooltip("Browse entities")
-                    .el(),
+                    .tooltip("Browse entities"),
+                    Button::new("\u{f002}", toggle_panel(ToolPanel::JumpTo))
+                        .tooltip("Jump to entity by id")
+                        .toggled(tool_panel == Some(ToolPanel::JumpTo)),
+                    Button::new("\u{f024}", toggle_panel(ToolPanel::Review))
+                        .tooltip("Entities marked for review")
+                        .toggled(tool_panel == Some(ToolPanel::Review)),
+                    Button::new("\u{f1fc}", toggle_panel(ToolPanel::ColorBy))
+                        .tooltip("Color by component")
+                        .toggled(tool_panel == Some(ToolPanel::ColorBy)),
+                    Button::new("\u{f013}", {
+                        let set_screen = set_screen.clone();
+                        move |_| {
+                            set_screen(Some(
+                                EditorPrefsScreen {
+                                    on_back: cb({
+                                        let set_screen = set_screen.clone();
+                                        move || set_screen(None)
+                                    }),
+                                }
+                                .el(),
+                            ));
+                        }
+                    })
+                    .tooltip("Preferences"),
+                    Button::new("\u{f8d9}", toggle_panel(ToolPanel::Recorder))
+                        .tooltip("Macros")
+                        .toggled(tool_panel == Some(ToolPanel::Recorder)),
+                    Button::new("\u{f070}", {
+                        let set_prefs = set_prefs.clone();
+                        move |_| set_prefs(EditorPrefs { ghost_unselected: !prefs.ghost_unselected, ..prefs })
+                    })
+                    .tooltip("Ghost unselected entities")
+                    .toggled(prefs.ghost_unselected),
+                    Button::new("\u{f1b2}", {
+                        let set_prefs = set_prefs.clone();
+                        move |_| set_prefs(EditorPrefs { show_world_axes: !prefs.show_world_axes, ..prefs })
+                    })
+                    .tooltip("Show world axes")
+                    .toggled(prefs.show_world_axes),
+                    Button::new("\u{f030}", {
+                        let set_prefs = set_prefs.clone();
+                        move |_| set_prefs(EditorPrefs { show_camera_hud: !prefs.show_camera_hud, ..prefs })
+                    })
+                    .tooltip("Camera position")
+                    .toggled(prefs.show_camera_hud),
+                    Button::new("\u{f14e}", {
+                        let set_prefs = set_prefs.clone();
+                        move |_| set_prefs(EditorPrefs { show_compass: !prefs.show_compass, ..prefs })
+                    })
+                    .tooltip("Compass")
+                    .toggled(prefs.show_compass),
+                    Button::new("\u{f545}", {
+                        let set_prefs = set_prefs.clone();
+                        move |_| set_prefs(EditorPrefs { show_selection_bounds: !prefs.show_selection_bounds, ..prefs })
+                    })
+                    .tooltip("Show selection size")
+                    .toggled(prefs.show_selection_bounds),
+                    Button::new("\u{f279}", {
+                        let set_prefs = set_prefs.clone();
+                        move |_| set_prefs(EditorPrefs { show_minimap: !prefs.show_minimap, ..prefs })
+                    })
+                    .tooltip("Map")
+                    .toggled(prefs.show_minimap),
+                    Button::new("\u{f015}", {
+                        let game_client = game_client.clone();
+                        move |world| {
+                            let view = scene_overview(&game_client.game_state.lock());
+                            match view {
+                                Some(view) => fly_camera(world.resource(runtime()), game_client.clone(), view),
+                                None => tracing::warn!("No active camera to reset"),
+                            }
+                        }
+                    })
+                    .tooltip("Reset camera to view the scene")
+                    .hotkey(VirtualKeyCode::Home),
+                    Button::new("\u{f02e}", toggle_panel(ToolPanel::Bookmarks))
+                        .tooltip("Camera bookmarks")
+                        .toggled(tool_panel == Some(ToolPanel::Bookmarks)),
+                    Button::new("\u{f03e}", toggle_panel(ToolPanel::ReferenceImage))
+                        .tooltip("Reference image")
+                        .toggled(tool_panel == Some(ToolPanel::ReferenceImage)),
+                    Button::new("\u{f546}", toggle_panel(ToolPanel::Construction))
+                        .tooltip("Construction lines")
+                        .toggled(tool_panel == Some(ToolPanel::Construction)),
+                    Button::new("\u{f5fd}", move |_| {
+                        let next = OverlapCheck::ALL.iter().position(|&v| v == prefs.overlaps).map_or(0, |i| i + 1);
+                        set_prefs(EditorPrefs { overlaps: OverlapCheck::ALL[next % OverlapCheck::ALL.len()], ..prefs })
+                    })
+                    .tooltip(format!("Highlight overlaps: {}", prefs.overlaps.label()))
+                    .toggled(prefs.overlaps != OverlapCheck::Off),
+                    Button::new(if selection_locked { "\u{f023}" } else { "\u{f09c}" }, move |_| {
+                        set_selection_lock(SelectionLock(!selection_locked))
+                    })
+                    .tooltip(if selection_locked { "Unlock selection" } else { "Lock selection" })
+                    .toggled(selection_locked)
+                    .hotkey(VirtualKeyCode::L),
+                    Button::new("\u{f06e}", {
+                        let game_client = game_client.clone();
+                        move |world| select_visible(world, game_client.clone(), select_mode, tag_filter.clone())
+                    })
+                    .tooltip("Select all visible")
+                    .hotkey(VirtualKeyCode::V),
+                    Button::new("\u{f3c5}", {
+                        let game_client = game_client.clone();
+                        move |world| {
+                            let mouse_clip_pos = get_mouse_clip_space_position(world);
+                            world.resource(runtime()).spawn(teleport_to_cursor(game_client.clone(), mouse_clip_pos));
+                        }
+                    })
+                    .tooltip("Teleport to cursor")
+                    .hotkey(VirtualKeyCode::J),
                 ];
                 if !selection.is_empty() {
-                    items.extend([
-                        Separator { vertical: true }.el(),
+                    create.extend([
                         Button::new(
                             "\u{f68e}",
                             closure!(clone game_client, clone targets, clone set_srt_mode, |world| {
@@ -267,8 +784,11 @@ impl ElementComponent for EditorBuildMode {
                                 let game_client = game_client.clone();
 
                                 tracing::info!("Duplicating {targets:?}");
+                                // With snapping on, the copies land on the next grid cell rather than on top of the originals
+                                let transforms =
+                                    prefs.snap.and_then(|size| next_cell_transforms(&game_client.game_state.lock(), &targets, size));
                                 world.resource(runtime()).spawn(
-                                    client_push_intent(game_client, intent_duplicate(), IntentDuplicate { new_uids: targets.iter().map(|_| EntityId::new()).collect(), entities: targets.to_vec(), select: true }, None, Some(Box::new(move || {
+                                    client_push_intent(game_client, intent_duplicate(), IntentDuplicate { new_uids: targets.iter().map(|_| EntityId::new()).collect(), entities: targets.to_vec(), select: true, transforms }, None, Some(Box::new(move || {
                                         tracing::info!("Entering translate move");
 
 
@@ -279,27 +799,132 @@ impl ElementComponent for EditorBuildMode {
                         )
                             .tooltip("Duplicate")
                             .hotkey(VirtualKeyCode::D)
-                            .hotkey_modifier(command_modifier())
-                            .el(),
-                        Button::new("\u{f6bf}", {
+                            .hotkey_modifier(command_modifier()),
+                        {
+                            let delete = Button::new("\u{f6bf}", {
+                                let targets = targets.clone();
+                                let game_client = game_client.clone();
+                                move |world| {
+                                    let batch = PendingBatch::intent(
+                                        "Delete",
+                                        targets.clone(),
+                                        game_client.clone(),
+                                        intent_delete(),
+                                        targets.to_vec(),
+                                    );
+                                    guard_batch(world, batch);
+                                }
+                            })
+                            .tooltip("Delete");
+                            match prefs.delete_hotkey {
+                                DeleteHotkey::Backspace => delete.hotkey(VirtualKeyCode::Back),
+                                DeleteHotkey::ShiftBackspace => delete.hotkey(VirtualKeyCode::Back).hotkey_modifier(ModifiersState::SHIFT),
+                                DeleteHotkey::None => delete,
+                            }
+                        },
+                    ]);
+                    arrange.extend([
+                        Button::new("\u{f0ea}", {
                             let targets = targets.clone();
-                            move |world| {
-                                world.resource(runtime()).spawn(client_push_intent(
-                                    game_client.clone(),
-                                    intent_delete(),
-                                    targets.to_vec(),
-                                    None,
-                                    None,
+                            let set_screen = set_screen.clone();
+                            move |_| {
+                                set_screen(Some(
+                                    PasteTableScreen {
+                                        targets: targets.clone(),
+                                        on_back: cb({
+                                            let set_screen = set_screen.clone();
+                                            move || set_screen(None)
+                                        }),
+                                    }
+                                    .el(),
                                 ));
                             }
                         })
-                            .tooltip("Delete")
-                            .hotkey(VirtualKeyCode::Back)
-                            .el(),
-                        Separator { vertical: true }.el(),
-                        TransformControls { targets: targets.clone() }.el().key(format!("{selection:?}")),
-                    ])
+                            .tooltip("Paste transforms from a table"),
+                        Button::new("\u{f1ce}", toggle_panel(ToolPanel::RadialArray))
+                            .tooltip("Radial array")
+                            .toggled(tool_panel == Some(ToolPanel::RadialArray)),
+                        Button::new("\u{f0c5}\u{f07e}", toggle_panel(ToolPanel::Mirror))
+                            .tooltip("Duplicate and mirror")
+                            .toggled(tool_panel == Some(ToolPanel::Mirror)),
+                        Button::new("\u{f1ec}", toggle_panel(ToolPanel::Expression))
+                            .tooltip("Set by expression")
+                            .toggled(tool_panel == Some(ToolPanel::Expression)),
+                        Button::new("\u{f0c5}\u{f276}", toggle_panel(ToolPanel::SpawnAt))
+                            .tooltip("Spawn at each selected")
+                            .toggled(tool_panel == Some(ToolPanel::SpawnAt)),
+                        Button::new("\u{f362}", toggle_panel(ToolPanel::Replace))
+                            .tooltip("Replace all instances of the object")
+                            .toggled(tool_panel == Some(ToolPanel::Replace)),
+                        Button::new("\u{f074}", toggle_panel(ToolPanel::Palette))
+                            .tooltip("Replace each with a random object from a palette")
+                            .toggled(tool_panel == Some(ToolPanel::Palette)),
+                        Button::new("\u{f05b}\u{f1b2}", toggle_panel(ToolPanel::SetOrigin))
+                            .tooltip("Set origin")
+                            .toggled(tool_panel == Some(ToolPanel::SetOrigin)),
+                        Button::new(
+                            "\u{f1b2}\u{f00c}",
+                            toggle_panel(ToolPanel::ApplyTransform),
+                        )
+                        .tooltip("Apply rotation or scale")
+                        .toggled(tool_panel == Some(ToolPanel::ApplyTransform)),
+                        Button::new("\u{f047}", toggle_panel(ToolPanel::Nudge))
+                            .tooltip("Nudge by a typed offset")
+                            .hotkey(VirtualKeyCode::N)
+                            .hotkey_modifier(ModifiersState::SHIFT)
+                            .toggled(tool_panel == Some(ToolPanel::Nudge)),
+                        Button::new("\u{f247}", toggle_panel(ToolPanel::Cluster))
+                            .tooltip("Split into clusters by distance")
+                            .toggled(tool_panel == Some(ToolPanel::Cluster)),
+                        Button::new("\u{f009}", toggle_panel(ToolPanel::Arrange))
+                            .tooltip("Arrange on grid")
+                            .toggled(tool_panel == Some(ToolPanel::Arrange)),
+                        Button::new("\u{f4d7}", toggle_panel(ToolPanel::AlignAxis))
+                            .tooltip("Align an axis to the world")
+                            .toggled(tool_panel == Some(ToolPanel::AlignAxis)),
+                        Button::new("\u{f328}", toggle_panel(ToolPanel::Layout))
+                            .tooltip("Copy and apply layout")
+                            .toggled(tool_panel == Some(ToolPanel::Layout)),
+                    ]);
+                    view.push(
+                        Button::new("\u{f05b}", {
+                            let game_client = game_client.clone();
+                            let targets = targets.clone();
+                            move |_| teleport_to_entities(&mut game_client.game_state.lock(), &targets)
+                        })
+                        .tooltip("Teleport to selection")
+                        .hotkey(VirtualKeyCode::J)
+                        .hotkey_modifier(ModifiersState::SHIFT),
+                    );
+                    for (icon, tooltip, hotkey, down) in [
+                        ("\u{f062}", "Select parents", VirtualKeyCode::LBracket, false),
+                        ("\u{f063}", "Select children", VirtualKeyCode::RBracket, true),
+                    ] {
+                        let game_client = game_client.clone();
+                        let targets = targets.clone();
+                        let set_selection = set_selection.clone();
+                        view.push(
+                            Button::new(icon, move |_| {
+                                let stepped = hierarchy_step(&game_client.game_state.lock().world, &targets, down);
+                                if let Some(selection) = stepped {
+                                    set_selection(selection);
+                                }
+                            })
+                            .tooltip(tooltip)
+                            .hotkey(hotkey),
+                        );
+                    }
                 }
+
+                let mut items = vec![ToolbarGroup::el(ToolbarGroupKind::Create, create)];
+                if !selection.is_empty() {
+                    items.push(TransformControls { targets: targets.clone(), last_srt_mode }.el().key(format!("{selection:?}")));
+                }
+                items.extend([
+                    ToolbarGroup::el(ToolbarGroupKind::Arrange, arrange),
+                    ToolbarGroup::el(ToolbarGroupKind::View, view),
+                    TagFilterSelect::el(),
+                ]);
                 items
             })
                 .el()
@@ -308,6 +933,58 @@ impl ElementComponent for EditorBuildMode {
                 .set(space_between_items(), STREET)
                 .set(margin(), Borders::even(STREET))
                 .set(padding(), Borders::even(STREET)),
+            open_panel,
+            if let Some(batch) = pending_batch {
+                side_panel(BatchConfirmPanel::el(batch), Docking::Left)
+            } else {
+                Element::new()
+            },
+            if prefs.show_camera_hud {
+                CameraHud::el()
+                    .floating_panel()
+                    .set(docking(), Docking::Bottom)
+                    .set(margin(), Borders::even(STREET))
+                    .set(padding(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
+            if prefs.show_selection_bounds && !targets.is_empty() {
+                SelectionBoundsGuide::el(targets.clone())
+                    .floating_panel()
+                    .set(docking(), Docking::Bottom)
+                    .set(margin(), Borders::even(STREET))
+                    .set(padding(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
+            if prefs.show_minimap {
+                Minimap::el()
+                    .floating_panel()
+                    .set(docking(), Docking::Right)
+                    .set(margin(), Borders::even(STREET))
+                    .set(padding(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
+            if prefs.show_compass {
+                Compass::el()
+                    .floating_panel()
+                    .set(docking(), Docking::Top)
+                    .set(margin(), Borders::even(STREET))
+                    .set(padding(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
+            if let Some(message) = toast {
+                ErrorToast::el(message.clone(), cb(closure!(clone set_toast, || set_toast(None))))
+                    .key(message)
+                    .floating_panel()
+                    .set(docking(), Docking::Bottom)
+                    .set(margin(), Borders::even(STREET))
+                    .set(padding(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
             GenerateTerrainButton.el()
                 .set(margin(), Borders::even(STREET)),
             SelectArea.el(),
@@ -326,23 +1003,249 @@ enum TransformMode {
     Scale,
     #[display(fmt = "Place")]
     Place,
+    #[display(fmt = "Clone stamp")]
+    Stamp,
+    #[display(fmt = "Resize volume")]
+    Volume,
+}
+
+/// The tool panel which is open, as only one is shown at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToolPanel {
+    RadialArray,
+    Cluster,
+    Arrange,
+    AlignAxis,
+    Layout,
+    ApplyTransform,
+    Mirror,
+    Expression,
+    SpawnAt,
+    Replace,
+    Palette,
+    SetOrigin,
+    Nudge,
+    JumpTo,
+    Review,
+    ColorBy,
+    Recorder,
+    Bookmarks,
+    ReferenceImage,
+    Construction,
+}
+impl ToolPanel {
+    /// Whether the panel acts on the selection, and so is hidden while nothing is selected
+    fn needs_selection(self) -> bool {
+        !matches!(
+            self,
+            Self::JumpTo | Self::Review | Self::ColorBy | Self::Recorder | Self::Bookmarks | Self::ReferenceImage | Self::Construction
+        )
+    }
+    fn side(self) -> Docking {
+        match self {
+            Self::JumpTo => Docking::Top,
+            Self::Recorder | Self::Bookmarks | Self::ReferenceImage | Self::Construction => Docking::Right,
+            _ => Docking::Left,
+        }
+    }
+}
+
+/// Lays out a tool panel docked to `side` of the viewport
+fn side_panel(el: Element, side: Docking) -> Element {
+    el.set(width(), 300.).set(docking(), side).floating_panel().set(margin(), Borders::even(STREET)).set(padding(), Borders::even(STREET))
+}
+
+/// Clears what was drawn to the gizmo `scope` once the element is removed
+fn use_gizmo_scope(hooks: &mut Hooks, scope: u64) {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    hooks.use_spawn(move |_| {
+        Box::new(move |_| {
+            // Getting the scope clears it
+            game_client.game_state.lock().world.resource(gizmos()).scope(scope);
+        })
+    });
+}
+
+/// Shows when cancelled actions are still being undone, and warns when one could not be undone so
+/// that the change doesn't silently persist
+#[element_component]
+fn PendingUndoIndicator(hooks: &mut Hooks) -> Element {
+    let (counts, set_counts) = hooks.use_state((0, 0));
+    let (seen_failed, set_seen_failed) = hooks.use_state_with(|_| FAILED_CANCELS.load(Ordering::SeqCst));
+
+    use_interval(hooks, 0.1, move || {
+        set_counts((PENDING_CANCELS.load(Ordering::SeqCst), FAILED_CANCELS.load(Ordering::SeqCst)));
+    });
+
+    let (pending, failed) = counts;
+    if failed > seen_failed {
+        FlowRow::el([
+            Text::el("A cancelled action could not be undone, use Undo to revert it").error_text_style(),
+            Button::new("Dismiss", move |_| set_seen_failed(failed)).style(ButtonStyle::Flat).el(),
+        ])
+        .floating_panel()
+        .set(padding(), Borders::even(STREET))
+        .set(space_between_items(), STREET)
+    } else if pending > 0 {
+        Text::el("\u{f017} Undoing cancelled action").small_style().floating_panel().set(padding(), Borders::even(STREET))
+    } else {
+        Element::new()
+    }
+}
+
+/// Where to create a new object: beside the selection as set by [`EditorPrefs::spawn_placement`],
+/// or under the cursor. Returns whether it is beside the selection, in which case it is left where
+/// it is rather than placed with the mouse.
+///
+/// The offset is the size of the selection plus the gap, from the origin of the active entity, so
+/// that copies of an object line up next to each other whatever their pivot
+fn spawn_position(state: &ClientGameState, targets: &[EntityId], prefs: EditorPrefs, mouse_clip_pos: Vec2) -> (Vec3, bool) {
+    let bounds = targets.iter().filter_map(|&id| calc_world_bounding_recursive(&state.world, id)).collect_vec();
+    let origin = targets.last().and_then(|&id| get_world_transform(&state.world, id).ok()).map(|v| v.transform_point3(Vec3::ZERO));
+    match (prefs.spawn_placement.direction(), AABB::unions(&bounds), origin) {
+        (Some(dir), Some(bounds), Some(origin)) => (prefs.snap(origin + dir * (bounds.size().dot(dir.abs()) + prefs.spawn_gap)), true),
+        _ => {
+            let ray = state.screen_ray(mouse_clip_pos);
+            // Snap the initial position too, so placed objects start out aligned
+            (prefs.snap(ray.origin + ray.dir * 10.), false)
+        }
+    }
+}
+
+impl TransformMode {
+    fn from_default(mode: DefaultTransformMode) -> Option<Self> {
+        match mode {
+            DefaultTransformMode::None => None,
+            DefaultTransformMode::Translate => Some(TransformMode::Translate),
+            DefaultTransformMode::Rotate => Some(TransformMode::Rotate),
+            DefaultTransformMode::Scale => Some(TransformMode::Scale),
+        }
+    }
+
+    fn cursor_icon(self) -> CursorIcon {
+        match self {
+            TransformMode::Translate => CursorIcon::Move,
+            TransformMode::Rotate => CursorIcon::Grab,
+            TransformMode::Scale => CursorIcon::NwseResize,
+            TransformMode::Place | TransformMode::Stamp => CursorIcon::Crosshair,
+            TransformMode::Volume => CursorIcon::Move,
+        }
+    }
+}
+
+/// Changes the cursor to reflect what a click in the viewport will do in the active transform mode
+#[element_component]
+fn ModeCursor(hooks: &mut Hooks) -> Element {
+    let (srt_mode, _) = hooks.consume_context::<Option<TransformMode>>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let hovered = hooks.use_ref_with::<Option<EntityId>>(|_| None);
+
+    let icon = if prefs.mode_cursors { srt_mode.map(TransformMode::cursor_icon) } else { None };
+
+    hooks.use_effect(icon, |world, &icon| {
+        if let Some(icon) = icon {
+            world.resource(window_ctl()).send(WindowCtl::SetCursorIcon(icon)).ok();
+        }
+
+        Box::new(move |world| {
+            if icon.is_some() {
+                world.resource(window_ctl()).send(WindowCtl::SetCursorIcon(CursorIcon::Default)).ok();
+            }
+        })
+    });
+
+    if let Some(icon) = icon {
+        // Hovering UI elements such as buttons resets the cursor, so restore it whenever the hovered
+        // element changes
+        hooks.use_frame(move |world| {
+            let current = world.resource(picker_intersecting()).map(|v| v.entity);
+            let mut hovered = hovered.lock();
+            if *hovered != current {
+                *hovered = current;
+                world.resource(window_ctl()).send(WindowCtl::SetCursorIcon(icon)).ok();
+            }
+        });
+    }
+
+    Element::new()
 }
 
 #[derive(Debug, Clone)]
 pub struct TransformControls {
     targets: Arc<[EntityId]>,
+    /// The most recently used mode, kept across selections
+    last_srt_mode: Arc<Mutex<Option<TransformMode>>>,
 }
 impl ElementComponent for TransformControls {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
-        let Self { targets } = *self;
+        let Self { targets, last_srt_mode } = *self;
+        let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
 
         let (srt_mode, set_srt_mode) = hooks.consume_context::<Option<TransformMode>>().unwrap();
 
         let (prefs, set_prefs) = hooks.consume_context::<EditorPrefs>().unwrap();
+        let pivot = use_pivot(hooks);
+        let (_, set_selection_pivot) = hooks.consume_context::<SelectionPivot>().unwrap();
+        let set_pivot = cb(move |pivot| set_selection_pivot(SelectionPivot(pivot)));
+        let (PivotEntity(pivot_entity), set_pivot_entity) = hooks.consume_context::<PivotEntity>().unwrap();
+        // Cleared on each change of mode, including leaving the transform modes
+        let set_constraint = hooks.provide_context(AxisConstraint::default);
+        hooks.use_effect(srt_mode, move |_, _| {
+            set_constraint(AxisConstraint::default());
+            Box::new(|_| {})
+        });
+        let active = targets.last().copied();
+        // Objects which were just spawned can't be transformed until they have loaded
+        let (loading, set_loading) = hooks.use_state_with(|_| loading_entities(&game_client.game_state.lock().world, &targets));
+        use_interval_deps(hooks, Duration::from_millis(250), false, targets.clone(), {
+            let game_client = game_client.clone();
+            let last = Arc::new(Mutex::new(loading.clone()));
+            move |targets| {
+                let now = loading_entities(&game_client.game_state.lock().world, targets);
+                if *last.lock() != now {
+                    *last.lock() = now.clone();
+                    set_loading(now);
+                }
+            }
+        });
+
+        // The controls are re-created for each selection, so this restores the mode once the new
+        // selection is shown
+        hooks.use_spawn({
+            let last_srt_mode = last_srt_mode.clone();
+            let set_srt_mode = set_srt_mode.clone();
+            move |_| {
+                let last_srt_mode = *last_srt_mode.lock();
+                let default_mode = TransformMode::from_default(prefs.default_transform_mode);
+                if prefs.keep_transform_mode && srt_mode.is_none() && last_srt_mode.is_some() {
+                    set_srt_mode(last_srt_mode);
+                } else if srt_mode.is_none() && default_mode.is_some() {
+                    set_srt_mode(default_mode);
+                }
+                Box::new(|_| {})
+            }
+        });
+
+        if srt_mode.is_some() {
+            *last_srt_mode.lock() = srt_mode;
+        }
         let set = set_prefs.clone();
         let set_snap_mode = move |snap| (set)(EditorPrefs { snap, ..prefs });
-        let set_global_coordinates = move |use_global| (set_prefs)(EditorPrefs { use_global_coordinates: use_global, ..prefs });
+        let set_coordinates: Cb<dyn Fn(CoordinateSpace) + Sync + Send> = {
+            let set_prefs = set_prefs.clone();
+            cb(move |coordinates| (set_prefs)(EditorPrefs { coordinates, ..prefs }))
+        };
+        let set_show_grid = {
+            let set_prefs = set_prefs.clone();
+            move |show_grid| (set_prefs)(EditorPrefs { show_grid, ..prefs })
+        };
+        let set_snap_in_parent = {
+            let set_prefs = set_prefs.clone();
+            move |snap_in_parent| (set_prefs)(EditorPrefs { snap_in_parent, ..prefs })
+        };
+        let set_surface = move |surface| (set_prefs)(EditorPrefs { surface, ..prefs });
 
+        let mode_keys = prefs.input_layout.mode_keys();
         let mode_button = |mode, icon, hotkey| {
             Button::new(
                 icon, // \u{f047}",
@@ -355,7 +1258,7 @@ impl ElementComponent for TransformControls {
             .toggled(srt_mode == Some(mode))
         };
 
-        let mut items = vec![
+        let buttons = vec![
             Button::new(
                 "",
                 closure!(clone set_snap_mode, |_| {
@@ -368,22 +1271,62 @@ impl ElementComponent for TransformControls {
             )
             .tooltip("Snap to grid")
             .hotkey(VirtualKeyCode::H)
-            .toggled(prefs.snap.is_some())
-            .el(),
-            // TODO: Dropdown for `local/global`
-            Button::new("", move |_| {
-                set_global_coordinates(!prefs.use_global_coordinates);
+            .toggled(prefs.snap.is_some()),
+            Button::new("\u{f00a}", move |_| set_show_grid(!prefs.show_grid))
+                .tooltip("Show grid")
+                .hotkey(VirtualKeyCode::H)
+                .hotkey_modifier(ModifiersState::SHIFT)
+                .toggled(prefs.show_grid),
+            Button::new("\u{f247}", move |_| set_snap_in_parent(!prefs.snap_in_parent))
+                .tooltip("Snap in parent space")
+                .toggled(prefs.snap_in_parent),
+            Button::new("\u{f1e5}", move |_| {
+                let next = SurfaceConstraint::ALL.iter().position(|&v| v == prefs.surface).map_or(0, |i| i + 1);
+                set_surface(SurfaceConstraint::ALL[next % SurfaceConstraint::ALL.len()]);
             })
-            .tooltip("Align to world space")
-            .hotkey(VirtualKeyCode::U)
-            .toggled(prefs.use_global_coordinates)
+            .tooltip(format!("Surface: {}", prefs.surface.label()))
+            .hotkey(VirtualKeyCode::G)
+            .toggled(prefs.surface != SurfaceConstraint::Free),
+            mode_button(TransformMode::Translate, "", mode_keys[0]),
+            mode_button(TransformMode::Rotate, "北", mode_keys[1]),
+            mode_button(TransformMode::Scale, "ﬕ", mode_keys[2]),
+            mode_button(TransformMode::Place, "", mode_keys[3]),
+            mode_button(TransformMode::Stamp, "\u{f0c5}", mode_keys[4]),
+            mode_button(TransformMode::Volume, "\u{f065}", mode_keys[5]),
+        ];
+        let pin_pivot = Button::new("\u{f08d}", move |_| set_pivot_entity(PivotEntity(if pivot_entity.is_some() { None } else { active })))
+            .tooltip(if pivot_entity.is_some() {
+                "Unpin the pivot"
+            } else {
+                "Pin the pivot to the active entity. Alt-click another entity to move it there"
+            })
+            .toggled(pivot_entity.is_some())
+            .el();
+        let mut items = vec![
+            ToolbarGroup::el(ToolbarGroupKind::Transform, buttons),
+            if pivot_entity.is_some() { Text::el(format!("Pivot: {}", pivot.label())) } else { PivotSelect::el(pivot, set_pivot) },
+            pin_pivot,
+            // U cycles through the spaces
+            Hotkey::new(
+                VirtualKeyCode::U,
+                closure!(clone set_coordinates, |_| set_coordinates(prefs.coordinates.next())),
+                DropdownSelect {
+                    content: Text::el(format!("Axes: {}", prefs.coordinates.label())),
+                    items: CoordinateSpace::ALL.iter().map(|space| Text::el(space.label())).collect(),
+                    on_select: cb(move |index| set_coordinates(CoordinateSpace::ALL[index])),
+                    inline: false,
+                }
+                .el(),
+            )
             .el(),
-            Separator { vertical: true }.el(),
-            mode_button(TransformMode::Translate, "", VirtualKeyCode::Key1).el(),
-            mode_button(TransformMode::Rotate, "北", VirtualKeyCode::Key2).el(),
-            mode_button(TransformMode::Scale, "ﬕ", VirtualKeyCode::Key3).el(),
-            mode_button(TransformMode::Place, "", VirtualKeyCode::Key4).el(),
         ];
+        if !loading.is_empty() {
+            items.push(Text::el(format!("\u{f110} {} loading", loading.len())).small_style());
+        }
+        // Looking along the local axes is for working in local space
+        if let (CoordinateSpace::Local, Some(active)) = (prefs.coordinates, active) {
+            items.push(LocalAxisViews::el(active));
+        }
 
         let on_click: Cb<dyn Fn(MouseButton) + Sync + Send> = cb({
             let set_srt_mode = set_srt_mode.clone();
@@ -393,17 +1336,29 @@ impl ElementComponent for TransformControls {
         });
 
         if srt_mode.is_some() {
+            // Stamping copies the selection rather than moving it, so pinned entities are included
+            let all_targets = targets.clone();
+            // Pinned and loading entities stay selected, but are left out of the transform
+            let targets: Arc<[EntityId]> = {
+                let state = game_client.game_state.lock();
+                targets.iter().copied().filter(|&id| !state.world.has_component(id, transform_pinned()) && !loading.contains(&id)).collect()
+            };
+
             items.extend(vec![
                 match (targets.is_empty(), srt_mode) {
                     (false, Some(TransformMode::Translate)) => TranslationController { targets, on_click }.el(),
-                    (false, Some(TransformMode::Scale)) => ScaleController { targets, on_click }.el(),
-                    (false, Some(TransformMode::Rotate)) => RotateController { targets, on_click }.el(),
+                    (false, Some(TransformMode::Scale)) => ScaleController { targets, pivot, on_click }.el(),
+                    (false, Some(TransformMode::Rotate)) => RotateController { targets, pivot, on_click }.el(),
                     (false, Some(TransformMode::Place)) => PlaceController { targets, on_click }.el(),
+                    (_, Some(TransformMode::Stamp)) if !all_targets.is_empty() => StampController::el(all_targets),
+                    (false, Some(TransformMode::Volume)) => VolumeController::el(targets),
                     _ => Element::new(),
                 },
                 Hotkey::new(
                     VirtualKeyCode::Escape,
                     move |_| {
+                        // Leaving the mode explicitly stops it from being restored on the next selection
+                        *last_srt_mode.lock() = None;
                         set_srt_mode(None);
                         // The editors are responsible for undoing the intents
                     },
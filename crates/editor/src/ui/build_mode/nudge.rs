@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use ambient_core::runtime;
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, StylesExt, Text, TextInput, STREET};
+use anyhow::{ensure, Context};
+use glam::Vec3;
+use itertools::Itertools;
+
+use super::transform::{axes_frame, current_transforms};
+use crate::{
+    intents::{intent_translate, IntentTranslate, SurfaceConstraint},
+    transform_pinned,
+    ui::{CoordinateSpace, EditorPrefs},
+};
+
+/// Parses an offset such as `0 0 2.5`, separated by spaces or commas
+fn parse_offset(text: &str) -> anyhow::Result<Vec3> {
+    let values = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse::<f32>().with_context(|| format!("Invalid number {v}")))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    ensure!(values.len() == 3, "Expected three numbers for x, y and z, got {}", values.len());
+    // "inf" and "NaN" parse as numbers, but would send the selection out of the world
+    ensure!(values.iter().all(|v| v.is_finite()), "Expected finite numbers");
+    Ok(Vec3::from_slice(&values))
+}
+
+/// Moves the selection by a typed offset as one undo step, in global or local coordinates
+/// depending on the preferences.
+///
+/// `on_close` is given the submitted text, so that it can be offered again the next time
+#[element_component]
+pub fn NudgePanel(
+    hooks: &mut Hooks,
+    targets: Arc<[EntityId]>,
+    initial: String,
+    on_close: Cb<dyn Fn(Option<String>) + Sync + Send>,
+) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let (text, set_text) = hooks.use_state(initial);
+
+    let runtime = hooks.world.resource(runtime()).clone();
+
+    let offset = parse_offset(&text);
+
+    let submit = {
+        let on_close = on_close.clone();
+        Arc::new(move |text: String| {
+            let offset = match parse_offset(&text) {
+                Ok(v) => v,
+                Err(_) => return,
+            };
+
+            let intent = {
+                let state = game_client.game_state.lock();
+                // Pinned entities don't move, so they must not count towards the midpoint either
+                let targets = targets.iter().copied().filter(|&id| !state.world.has_component(id, transform_pinned())).collect_vec();
+                let initial = current_transforms(&state.world, &targets);
+                let offset = match (initial.transforms.last(), targets.last()) {
+                    (Some(&transform), Some(&active)) => {
+                        axes_frame(&state.world, prefs.coordinates, active, transform).transform_vector3(offset)
+                    }
+                    _ => offset,
+                };
+                IntentTranslate { targets, position: initial.midpoint + offset, surface: SurfaceConstraint::Free, round_to: None }
+            };
+            runtime.spawn(client_push_intent(game_client.clone(), intent_translate(), intent, None, None));
+            on_close(Some(text));
+        })
+    };
+
+    FlowColumn::el([
+        Text::el(match prefs.coordinates {
+            CoordinateSpace::World => "Nudge by (global)",
+            CoordinateSpace::Local => "Nudge by (local to active)",
+            CoordinateSpace::Parent => "Nudge by (in the parent of active)",
+        })
+        .section_style(),
+        TextInput::new(text.clone(), cb(move |v| set_text(v)))
+            .on_submit({
+                let submit = submit.clone();
+                move |text| submit(text)
+            })
+            .placeholder(Some("x y z"))
+            .autofocus()
+            .el(),
+        match &offset {
+            Ok(_) => Text::el("Enter or Apply to move, the value is kept for next time").small_style(),
+            Err(err) => Text::el(format!("{err:#}")).error_text_style(),
+        },
+        FlowRow::el([
+            Button::new("Apply", move |_| submit(text.clone())).disabled(offset.is_err()).style(ButtonStyle::Primary).el(),
+            Button::new("Cancel", move |_| on_close(None)).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
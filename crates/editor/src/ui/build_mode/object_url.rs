@@ -0,0 +1,37 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ambient_prefab::{prefab_main_url, PrefabFromUrl};
+use ambient_std::{
+    asset_cache::{AssetCache, AsyncAssetKeyExt},
+    asset_url::AssetUrl,
+};
+use parking_lot::Mutex;
+
+/// Checks that object urls can be loaded before they are spawned, as a failed load otherwise only
+/// shows up in the logs.
+///
+/// The results are cached per url.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ObjectUrlValidator {
+    cache: Arc<Mutex<HashMap<String, Result<(), String>>>>,
+}
+
+impl ObjectUrlValidator {
+    pub async fn validate(&self, assets: &AssetCache, object_url: &str) -> Result<(), String> {
+        if let Some(res) = self.cache.lock().get(object_url) {
+            return res.clone();
+        }
+
+        let res = match AssetUrl::parse(prefab_main_url(object_url.to_string())) {
+            Ok(url) => PrefabFromUrl(url).get(assets).await.map(|_| ()).map_err(|err| format!("Failed to load {object_url}: {err:#}")),
+            Err(err) => Err(format!("Invalid object url {object_url:?}: {err:#}")),
+        };
+
+        if let Err(err) = &res {
+            tracing::warn!("{err}");
+        }
+
+        self.cache.lock().insert(object_url.to_string(), res.clone());
+        res
+    }
+}
@@ -0,0 +1,102 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use ambient_core::{bounding::calc_world_bounding_recursive, selectable};
+use ambient_ecs::{query, EntityId, World};
+use ambient_element::{element_component, Element, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive, GizmoScope};
+use ambient_network::client::GameClient;
+use ambient_std::{line_hash, shapes::AABB};
+use ambient_ui::use_interval_deps;
+use glam::{vec3, Vec3};
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+
+use super::use_gizmo_scope;
+use crate::ui::OverlapCheck;
+
+/// How far bounds have to reach into each other to count as overlapping, so that objects which
+/// merely touch, such as stamped fences, are not flagged
+const OVERLAP_TOLERANCE: f32 = 0.01;
+const OVERLAP_COLOR: Vec3 = vec3(1., 0.15, 0.15);
+
+fn overlap_scope() -> u64 {
+    line_hash!()
+}
+
+/// Finds the entities whose bounds overlap the bounds of another entity.
+///
+/// When `only_with` is given, only overlaps which involve at least one of those entities count
+fn find_overlaps(world: &World, only_with: Option<&HashSet<EntityId>>) -> Vec<(EntityId, AABB)> {
+    let shrink = Vec3::splat(OVERLAP_TOLERANCE);
+    let bounds = query(selectable())
+        .iter(world, None)
+        .filter_map(|(id, _)| Some((id, calc_world_bounding_recursive(world, id)?)))
+        .map(|(id, bounds)| (id, AABB { min: bounds.min + shrink, max: bounds.max - shrink }))
+        .sorted_by_key(|(_, bounds)| OrderedFloat(bounds.min.x))
+        .collect_vec();
+
+    // Sweep along x, so that only the boxes whose x ranges overlap are compared
+    let mut overlapping = HashSet::new();
+    for (i, (a, a_bounds)) in bounds.iter().enumerate() {
+        for (b, b_bounds) in bounds[i + 1..].iter().take_while(|(_, b_bounds)| b_bounds.min.x <= a_bounds.max.x) {
+            let relevant = only_with.map_or(true, |only_with| only_with.contains(a) || only_with.contains(b));
+            if relevant && a_bounds.intersect_aabb(b_bounds) {
+                overlapping.insert(*a);
+                overlapping.insert(*b);
+            }
+        }
+    }
+
+    bounds
+        .into_iter()
+        .filter(|(id, _)| overlapping.contains(id))
+        .map(|(id, bounds)| (id, AABB { min: bounds.min - shrink, max: bounds.max + shrink }))
+        .collect_vec()
+}
+
+/// Draws the twelve edges of `bounds`
+pub(super) fn draw_bounds(scope: &mut GizmoScope, bounds: &AABB, color: Vec3) {
+    let corner = |x: bool, y: bool, z: bool| {
+        vec3(
+            if x { bounds.max.x } else { bounds.min.x },
+            if y { bounds.max.y } else { bounds.min.y },
+            if z { bounds.max.z } else { bounds.min.z },
+        )
+    };
+
+    for a in [false, true] {
+        for b in [false, true] {
+            scope.draw(GizmoPrimitive::line(corner(false, a, b), corner(true, a, b), 0.02).with_color(color));
+            scope.draw(GizmoPrimitive::line(corner(a, false, b), corner(a, true, b), 0.02).with_color(color));
+            scope.draw(GizmoPrimitive::line(corner(a, b, false), corner(a, b, true), 0.02).with_color(color));
+        }
+    }
+}
+
+/// Outlines the entities whose bounds overlap another entity, to catch clipping placements
+#[element_component]
+pub fn OverlapHighlights(hooks: &mut Hooks, targets: Arc<[EntityId]>, check: OverlapCheck) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    use_interval_deps(hooks, Duration::from_millis(500), true, (targets, check), {
+        let game_state = game_client.game_state.clone();
+        move |(targets, check)| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(overlap_scope());
+
+            let overlaps = match check {
+                OverlapCheck::Off => return,
+                OverlapCheck::Scene => find_overlaps(&state.world, None),
+                OverlapCheck::Selection => find_overlaps(&state.world, Some(&targets.iter().copied().collect())),
+            };
+
+            for (_, bounds) in overlaps {
+                draw_bounds(&mut scope, &bounds, OVERLAP_COLOR);
+            }
+        }
+    });
+
+    use_gizmo_scope(hooks, overlap_scope());
+
+    Element::new()
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use ambient_core::{asset_cache, runtime};
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_std::{
+    asset_url::{select_asset, AssetType},
+    cb, Cb,
+};
+use ambient_ui::{space_between_items, Button, ButtonStyle, EditorRow, FlowColumn, FlowRow, Slider, StylesExt, Text, U64Input, STREET};
+
+use crate::intents::{intent_scatter_objects, palette_picks, IntentScatterObjects};
+
+/// Replaces each selected entity with an object picked at random from a weighted palette, keeping
+/// their transforms, as one undo step
+#[element_component]
+pub fn PalettePanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (palette, set_palette) = hooks.use_state(Vec::<(String, f32)>::new());
+    let (seed, set_seed) = hooks.use_state(0u64);
+
+    let weights = palette.iter().map(|(_, weight)| *weight).collect::<Vec<_>>();
+    let picks = palette_picks(targets.len(), &weights, seed).ok();
+
+    let entries = palette
+        .iter()
+        .enumerate()
+        .map(|(index, (url, weight))| {
+            let count = picks.as_ref().map_or(0, |picks| picks.iter().filter(|&&pick| pick == index).count());
+            FlowRow::el([
+                Text::el(url.clone()).small_style(),
+                Slider {
+                    value: *weight,
+                    on_change: Some(cb({
+                        let palette = palette.clone();
+                        let set_palette = set_palette.clone();
+                        move |weight| {
+                            let mut palette = palette.clone();
+                            palette[index].1 = weight;
+                            set_palette(palette);
+                        }
+                    })),
+                    min: 0.,
+                    max: 10.,
+                    width: 100.,
+                    logarithmic: false,
+                    round: Some(1),
+                    suffix: None,
+                }
+                .el(),
+                Text::el(format!("\u{00d7}{count}")),
+                Button::new("\u{f00d}", {
+                    let palette = palette.clone();
+                    let set_palette = set_palette.clone();
+                    move |_| {
+                        let mut palette = palette.clone();
+                        palette.remove(index);
+                        set_palette(palette);
+                    }
+                })
+                .style(ButtonStyle::Flat)
+                .tooltip("Remove from the palette")
+                .el(),
+            ])
+            .set(space_between_items(), STREET)
+        })
+        .collect::<Vec<_>>();
+
+    FlowColumn::el([
+        Text::el("Random from palette").section_style(),
+        Text::el(format!("Replaces each of the {} selected with an object from the palette", targets.len())).small_style(),
+        FlowColumn::el(entries).set(space_between_items(), STREET),
+        Button::new("Add object", {
+            let palette = palette.clone();
+            let set_palette = set_palette.clone();
+            move |world| {
+                let palette = palette.clone();
+                let set_palette = set_palette.clone();
+                select_asset(world.resource(asset_cache()), AssetType::Prefab, move |object_url| {
+                    if let Some(object_url) = object_url.random().cloned() {
+                        if !palette.iter().any(|(url, _)| *url == object_url) {
+                            let mut palette = palette.clone();
+                            palette.push((object_url, 1.));
+                            set_palette(palette);
+                        }
+                    }
+                });
+            }
+        })
+        .style(ButtonStyle::Flat)
+        .el(),
+        EditorRow::el(
+            "Seed",
+            FlowRow::el([
+                U64Input::new(seed, {
+                    let set_seed = set_seed.clone();
+                    move |seed| set_seed(seed)
+                })
+                .el(),
+                Button::new("\u{f074}", move |_| set_seed(seed.wrapping_add(1))).tooltip("Next seed").el(),
+            ])
+            .set(space_between_items(), STREET),
+        ),
+        FlowRow::el([
+            Button::new("Replace", {
+                let on_close = on_close.clone();
+                move |world| {
+                    world.resource(runtime()).spawn(client_push_intent(
+                        game_client.clone(),
+                        intent_scatter_objects(),
+                        IntentScatterObjects { targets: targets.to_vec(), palette: palette.clone(), seed },
+                        None,
+                        None,
+                    ));
+                    on_close();
+                }
+            })
+            .disabled(picks.is_none())
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
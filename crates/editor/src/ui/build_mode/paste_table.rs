@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use ambient_core::{runtime, transform::get_world_transform};
+use ambient_ecs::EntityId;
+use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_std::Cb;
+use ambient_ui::{
+    fit_horizontal, space_between_items, Button, ButtonStyle, DialogScreen, Fit, FlowColumn, FlowRow, StylesExt, Text, STREET,
+};
+use anyhow::Context;
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use itertools::Itertools;
+
+use crate::intents::{intent_duplicate, intent_set_transform, IntentDuplicate, IntentTransform, TerrainOffset};
+
+/// A single row of a pasted transform table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableRow {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+/// Parses a comma or tab separated table, such as one copied from a spreadsheet.
+///
+/// Each row is either `x, y, z` or `x, y, z, rx, ry, rz` where the rotation is given as euler
+/// angles in degrees. Empty lines are skipped, and so is the first non-empty line if it is a
+/// header. Values which aren't finite are rejected.
+pub fn parse_transform_table(text: &str) -> anyhow::Result<Vec<TableRow>> {
+    let mut rows = Vec::new();
+    let mut first = true;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let is_first = std::mem::replace(&mut first, false);
+
+        let separator = if line.contains('\t') { '\t' } else { ',' };
+        let fields = line.split(separator).map(|v| v.trim().parse::<f32>()).collect::<Result<Vec<_>, _>>();
+
+        let fields = match fields {
+            Ok(v) => v,
+            // Allow a header row
+            Err(_) if is_first => continue,
+            Err(err) => return Err(err).with_context(|| format!("Row {}: invalid number in {line:?}", i + 1)),
+        };
+        if let Some(value) = fields.iter().find(|v| !v.is_finite()) {
+            anyhow::bail!("Row {}: {value} is not a finite number", i + 1);
+        }
+
+        let row = match fields[..] {
+            [x, y, z] => TableRow { position: Vec3::new(x, y, z), rotation: Quat::IDENTITY },
+            [x, y, z, rx, ry, rz] => TableRow {
+                position: Vec3::new(x, y, z),
+                rotation: Quat::from_euler(EulerRot::XYZ, rx.to_radians(), ry.to_radians(), rz.to_radians()),
+            },
+            _ => anyhow::bail!("Row {}: expected 3 or 6 columns, found {}", i + 1, fields.len()),
+        };
+
+        rows.push(row);
+    }
+
+    if rows.is_empty() {
+        anyhow::bail!("The table contains no rows");
+    }
+
+    Ok(rows)
+}
+
+fn read_clipboard_table() -> Result<Vec<TableRow>, String> {
+    let text = arboard::Clipboard::new().and_then(|mut v| v.get_text()).map_err(|err| format!("Failed to read clipboard: {err}"))?;
+    parse_transform_table(&text).map_err(|err| format!("{err:#}"))
+}
+
+/// Positions the selection, or spawns copies of the active object, according to a table of
+/// transforms read from the clipboard
+#[derive(Debug, Clone)]
+pub struct PasteTableScreen {
+    pub targets: Arc<[EntityId]>,
+    pub on_back: Cb<dyn Fn() + Sync + Send>,
+}
+
+impl ElementComponent for PasteTableScreen {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let Self { targets, on_back } = *self;
+        let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+        let (table, set_table) = hooks.use_state_with(|_| read_clipboard_table());
+
+        // Keep the scale of each entity, as the table only describes position and rotation
+        let scales = {
+            let state = game_client.game_state.lock();
+            targets
+                .iter()
+                .map(|&id| get_world_transform(&state.world, id).map(|v| v.to_scale_rotation_translation().0).unwrap_or(Vec3::ONE))
+                .collect_vec()
+        };
+
+        let actions = match &table {
+            Ok(rows) => {
+                let matches_selection = rows.len() == targets.len();
+
+                FlowColumn::el([
+                    Text::el(format!("{} rows, {} selected entities", rows.len(), targets.len())),
+                    FlowRow::el([
+                        Button::new("Apply to selection", {
+                            let game_client = game_client.clone();
+                            let targets = targets.clone();
+                            let rows = rows.clone();
+                            let scales = scales.clone();
+                            let on_back = on_back.clone();
+                            move |world| {
+                                let transforms = rows
+                                    .iter()
+                                    .zip_eq(&scales)
+                                    .map(|(row, &scale)| Mat4::from_scale_rotation_translation(scale, row.rotation, row.position))
+                                    .collect_vec();
+
+                                world.resource(runtime()).spawn(client_push_intent(
+                                    game_client.clone(),
+                                    intent_set_transform(),
+                                    IntentTransform { entities: targets.to_vec(), transforms, terrain_offset: TerrainOffset::Update },
+                                    None,
+                                    None,
+                                ));
+                                on_back();
+                            }
+                        })
+                        .disabled(!matches_selection)
+                        .tooltip("The number of rows must match the number of selected entities")
+                        .el(),
+                        Button::new("Spawn copies of active", {
+                            let game_client = game_client.clone();
+                            let targets = targets.clone();
+                            let rows = rows.clone();
+                            let on_back = on_back.clone();
+                            move |world| {
+                                let active = *targets.last().unwrap();
+                                let scale = *scales.last().unwrap();
+                                let copies = rows
+                                    .iter()
+                                    .map(|row| (active, Mat4::from_scale_rotation_translation(scale, row.rotation, row.position)));
+
+                                world.resource(runtime()).spawn(client_push_intent(
+                                    game_client.clone(),
+                                    intent_duplicate(),
+                                    IntentDuplicate::batch(copies, true),
+                                    None,
+                                    None,
+                                ));
+                                on_back();
+                            }
+                        })
+                        .disabled(targets.is_empty())
+                        .el(),
+                    ])
+                    .set(space_between_items(), STREET),
+                ])
+                .set(space_between_items(), STREET)
+            }
+            Err(err) => Text::el(err.clone()).error_text_style(),
+        };
+
+        DialogScreen(
+            FlowColumn::el([
+                FlowRow::el([
+                    Button::new("Back", move |_| on_back()).style(ButtonStyle::Primary).el(),
+                    Button::new("Read clipboard", move |_| set_table(read_clipboard_table())).el(),
+                ])
+                .set(space_between_items(), STREET),
+                Text::el("Paste rows of `x, y, z` or `x, y, z, rx, ry, rz` (degrees), separated by commas or tabs").small_style(),
+                actions,
+            ])
+            .set(space_between_items(), STREET)
+            .set(fit_horizontal(), Fit::Parent),
+        )
+        .el()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::Vec3;
+
+    use super::parse_transform_table;
+
+    #[test]
+    fn header_is_skipped_after_leading_empty_lines() {
+        let rows = parse_transform_table("\n  \nx\ty\tz\n1\t2\t3\n4\t5\t6\n").unwrap();
+        assert_eq!(rows.iter().map(|v| v.position).collect::<Vec<_>>(), vec![Vec3::new(1., 2., 3.), Vec3::new(4., 5., 6.)]);
+    }
+
+    #[test]
+    fn only_the_first_line_can_be_a_header() {
+        let err = parse_transform_table("x, y, z\n1, 2, 3\nx, y, z\n").unwrap_err();
+        assert!(format!("{err:#}").contains("Row 3"), "{err:#}");
+    }
+
+    #[test]
+    fn non_finite_values_are_rejected() {
+        for (text, row) in [("1, 2, 3\nnan, 0, 0", "Row 2"), ("x, y, z\n\n1, inf, 3", "Row 3"), ("1, 2, 3, 0, -inf, 0", "Row 1")] {
+            let err = parse_transform_table(text).unwrap_err();
+            assert!(format!("{err:#}").contains(row), "{err:#}");
+        }
+    }
+}
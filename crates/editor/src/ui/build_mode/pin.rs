@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use ambient_core::bounding::calc_world_bounding_recursive;
+use ambient_ecs::query;
+use ambient_element::{element_component, Element, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_network::client::GameClient;
+use ambient_std::line_hash;
+use ambient_ui::use_interval_deps;
+use glam::{vec3, Vec3};
+
+use super::use_gizmo_scope;
+use crate::transform_pinned;
+
+const PIN_HEIGHT: f32 = 0.6;
+const PIN_COLOR: Vec3 = vec3(1., 0.5, 0.1);
+
+fn pin_scope() -> u64 {
+    line_hash!()
+}
+
+/// Draws a pin above each entity whose transform is pinned
+#[element_component]
+pub fn PinnedMarkers(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    use_interval_deps(hooks, Duration::from_millis(200), true, (), {
+        let game_state = game_client.game_state.clone();
+        move |_| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(pin_scope());
+            for (id, _) in query(transform_pinned()).iter(&state.world, None) {
+                let bounds = match calc_world_bounding_recursive(&state.world, id) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                let base = vec3(bounds.center().x, bounds.center().y, bounds.max.z);
+                let head = base + Vec3::Z * PIN_HEIGHT;
+                scope.draw(GizmoPrimitive::line(base, head, 0.03).with_color(PIN_COLOR));
+                scope.draw(GizmoPrimitive::sphere(head, 0.12).with_color(PIN_COLOR));
+            }
+        }
+    });
+
+    use_gizmo_scope(hooks, pin_scope());
+
+    Element::new()
+}
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use ambient_core::transform::get_world_position;
+use ambient_ecs::{EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_network::client::GameClient;
+use ambient_std::{cb, line_hash, Cb};
+use ambient_ui::{use_interval_deps, DropdownSelect, Text};
+use glam::{ivec3, vec3, IVec3, Vec3};
+use itertools::Itertools;
+
+use super::{transform::IntialState, use_gizmo_scope};
+
+const PIVOT_MARKER_COLOR: Vec3 = vec3(1., 0.85, 0.1);
+
+fn pivot_marker_scope() -> u64 {
+    line_hash!()
+}
+
+/// The entity which rotating and scaling pivot around, in place of the pivot picked for each
+/// selection. It is kept across selections until cleared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PivotEntity(pub Option<EntityId>);
+
+/// The pivot picked for the current selection, shared by the transform controls and the
+/// selection panel. It goes back to the midpoint whenever the selection changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectionPivot(pub Pivot);
+impl Default for SelectionPivot {
+    fn default() -> Self {
+        Self(Pivot::Midpoint)
+    }
+}
+
+/// The pivot the selection is rotated and scaled around, where a pinned [`PivotEntity`] takes
+/// over from the [`SelectionPivot`]
+pub(super) fn use_pivot(hooks: &mut Hooks) -> Pivot {
+    let (SelectionPivot(pivot), _) = hooks.consume_context::<SelectionPivot>().unwrap();
+    let (PivotEntity(pivot_entity), _) = hooks.consume_context::<PivotEntity>().unwrap();
+    pivot_entity.map_or(pivot, Pivot::Entity)
+}
+
+/// The point which the selection is rotated and scaled around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pivot {
+    /// The average position of the selected entities
+    Midpoint,
+    /// A feature of the combined bounding box of the selection, given as the side of each axis.
+    ///
+    /// Zero on every axis is the center, zero on one axis is the midpoint of an edge, and no zeros
+    /// is a corner.
+    Bounds(IVec3),
+    /// The origin of an entity, which doesn't have to be selected
+    Entity(EntityId),
+}
+
+impl Pivot {
+    /// The midpoint, the bounds center, the corners and then the edge midpoints
+    fn all() -> Vec<Pivot> {
+        let sides = (-1..=1).flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| ivec3(x, y, z)))).collect_vec();
+        let corners = sides.iter().filter(|side| zero_axes(**side) == 0);
+        let edges = sides.iter().filter(|side| zero_axes(**side) == 1);
+
+        [Pivot::Midpoint, Pivot::Bounds(IVec3::ZERO)].into_iter().chain(corners.chain(edges).map(|&side| Pivot::Bounds(side))).collect_vec()
+    }
+
+    pub(super) fn label(self) -> String {
+        match self {
+            Pivot::Midpoint => "Midpoint".to_string(),
+            Pivot::Entity(_) => "Pinned entity".to_string(),
+            Pivot::Bounds(side) if side == IVec3::ZERO => "Bounds center".to_string(),
+            Pivot::Bounds(side) => {
+                let sides = side
+                    .to_array()
+                    .into_iter()
+                    .zip(["X", "Y", "Z"])
+                    .filter(|(side, _)| *side != 0)
+                    .map(|(side, axis)| format!("{}{axis}", if side < 0 { "-" } else { "+" }))
+                    .join(" ");
+
+                if zero_axes(side) == 0 {
+                    format!("Corner {sides}")
+                } else {
+                    format!("Edge {sides}")
+                }
+            }
+        }
+    }
+
+    /// Resolves the pivot in world space. Falls back to the midpoint when the selection has no bounds,
+    /// or the pinned entity no longer exists
+    pub fn point(self, world: &World, state: &IntialState) -> Vec3 {
+        match (self, state.bounds) {
+            (Pivot::Bounds(side), Some(bounds)) => bounds.center() + bounds.size() * 0.5 * side.as_vec3(),
+            (Pivot::Entity(id), _) => get_world_position(world, id).unwrap_or(state.midpoint),
+            _ => state.midpoint,
+        }
+    }
+}
+
+fn zero_axes(side: IVec3) -> usize {
+    side.to_array().iter().filter(|&&v| v == 0).count()
+}
+
+/// Picks which point of the selection bounds to use as the pivot
+#[element_component]
+pub fn PivotSelect(_: &mut Hooks, pivot: Pivot, set_pivot: Cb<dyn Fn(Pivot) + Sync + Send>) -> Element {
+    let pivots = Pivot::all();
+
+    DropdownSelect {
+        content: Text::el(format!("Pivot: {}", pivot.label())),
+        items: pivots.iter().map(|pivot| Text::el(pivot.label())).collect(),
+        on_select: cb(move |index| set_pivot(pivots[index])),
+        inline: false,
+    }
+    .el()
+}
+
+/// Marks the origin of the pinned pivot entity, so that it is clear what rotating and scaling pivot
+/// around
+#[element_component]
+pub fn PivotMarker(hooks: &mut Hooks, id: EntityId) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    use_interval_deps(hooks, Duration::from_millis(100), true, id, {
+        let game_state = game_client.game_state.clone();
+        move |&id| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(pivot_marker_scope());
+            if let Ok(position) = get_world_position(&state.world, id) {
+                scope.draw(GizmoPrimitive::torus(position, 0.4, 0.05).with_color(PIVOT_MARKER_COLOR));
+                scope.draw(GizmoPrimitive::sphere(position, 0.1).with_color(PIVOT_MARKER_COLOR));
+            }
+        }
+    });
+    use_gizmo_scope(hooks, pivot_marker_scope());
+
+    Element::new()
+}
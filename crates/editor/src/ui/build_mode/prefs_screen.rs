@@ -0,0 +1,396 @@
+use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_std::{cb, Cb};
+use ambient_ui::{
+    fit_horizontal, space_between_items, Button, ButtonStyle, Checkbox, CountInput, DialogScreen, DropdownSelect, Editor, EditorRow, Fit,
+    FlowColumn, FlowRow, ScrollArea, Slider, StylesExt, Text, STREET,
+};
+
+use crate::ui::{
+    ActionThrottles, CommitRounding, DefaultTransformMode, DeleteHotkey, DoubleClickAction, EditorPrefs, InputLayout, ScaleSnap,
+    SelectionHighlight, SnapIncrement, SpawnPlacement,
+};
+
+/// Lists the editor preferences which don't have a dedicated toggle in the toolbar
+#[derive(Debug, Clone)]
+pub struct EditorPrefsScreen {
+    pub on_back: Cb<dyn Fn() + Sync + Send>,
+}
+
+impl ElementComponent for EditorPrefsScreen {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let Self { on_back } = *self;
+        let (prefs, set_prefs) = hooks.consume_context::<EditorPrefs>().unwrap();
+        let (confirm_reset, set_confirm_reset) = hooks.use_state(false);
+
+        let reset = if confirm_reset {
+            FlowRow::el([
+                Text::el("Reset every preference, including the delete hotkey?").error_text_style(),
+                Button::new("Reset", {
+                    let set_prefs = set_prefs.clone();
+                    let set_confirm_reset = set_confirm_reset.clone();
+                    move |_| {
+                        set_prefs(EditorPrefs::default());
+                        set_confirm_reset(false);
+                    }
+                })
+                .style(ButtonStyle::Primary)
+                .el(),
+                Button::new("Cancel", move |_| set_confirm_reset(false)).el(),
+            ])
+            .set(space_between_items(), STREET)
+        } else {
+            Button::new("Reset to defaults", move |_| set_confirm_reset(true))
+                .disabled(prefs == EditorPrefs::default())
+                .style(ButtonStyle::Flat)
+                .el()
+        };
+
+        DialogScreen(
+            ScrollArea(
+                FlowColumn::el([
+                    Button::new("Back", move |_| on_back()).style(ButtonStyle::Primary).el(),
+                    Text::el("Editor preferences").header_style(),
+                    reset,
+                    EditorRow::el(
+                        "Mode cursors",
+                        Checkbox::new(prefs.mode_cursors, {
+                            let set_prefs = set_prefs.clone();
+                            move |mode_cursors| set_prefs(EditorPrefs { mode_cursors, ..prefs })
+                        })
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Keep transform mode across selections",
+                        Checkbox::new(prefs.keep_transform_mode, {
+                            let set_prefs = set_prefs.clone();
+                            move |keep_transform_mode| set_prefs(EditorPrefs { keep_transform_mode, ..prefs })
+                        })
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Mode on selection",
+                        DropdownSelect {
+                            content: Text::el(prefs.default_transform_mode.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { default_transform_mode: DefaultTransformMode::ALL[index], ..prefs })
+                            }),
+                            items: DefaultTransformMode::ALL.iter().map(|mode| Text::el(mode.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Mode after box select",
+                        DropdownSelect {
+                            content: Text::el(prefs.box_select_mode.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { box_select_mode: DefaultTransformMode::ALL[index], ..prefs })
+                            }),
+                            items: DefaultTransformMode::ALL.iter().map(|mode| Text::el(mode.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Delete key",
+                        DropdownSelect {
+                            content: Text::el(prefs.delete_hotkey.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { delete_hotkey: DeleteHotkey::ALL[index], ..prefs })
+                            }),
+                            items: DeleteHotkey::ALL.iter().map(|hotkey| Text::el(hotkey.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Create new objects",
+                        DropdownSelect {
+                            content: Text::el(prefs.spawn_placement.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { spawn_placement: SpawnPlacement::ALL[index], ..prefs })
+                            }),
+                            items: SpawnPlacement::ALL.iter().map(|placement| Text::el(placement.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Gap beside selection",
+                        Slider {
+                            value: prefs.spawn_gap,
+                            on_change: Some(cb({
+                                let set_prefs = set_prefs.clone();
+                                move |spawn_gap| set_prefs(EditorPrefs { spawn_gap, ..prefs })
+                            })),
+                            min: 0.,
+                            max: 10.,
+                            width: 100.,
+                            logarithmic: false,
+                            round: Some(2),
+                            suffix: Some("m"),
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Mode hotkeys",
+                        DropdownSelect {
+                            content: Text::el(prefs.input_layout.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { input_layout: InputLayout::ALL[index], ..prefs })
+                            }),
+                            items: InputLayout::ALL.iter().map(|layout| Text::el(layout.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Defer transforms until released",
+                        Checkbox::new(prefs.deferred_transforms, {
+                            let set_prefs = set_prefs.clone();
+                            move |deferred_transforms| set_prefs(EditorPrefs { deferred_transforms, ..prefs })
+                        })
+                        .el(),
+                    ),
+                    Text::el("Smoother on slow connections, but others only see the result").small_style(),
+                    EditorRow::el(
+                        "Snap increment",
+                        DropdownSelect {
+                            content: Text::el(prefs.snap_increment.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { snap_increment: SnapIncrement::ALL[index], ..prefs })
+                            }),
+                            items: SnapIncrement::ALL.iter().map(|increment| Text::el(increment.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Scale snapping",
+                        DropdownSelect {
+                            content: Text::el(prefs.scale_snap.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { scale_snap: ScaleSnap::ALL[index], ..prefs })
+                            }),
+                            items: ScaleSnap::ALL.iter().map(|snap| Text::el(snap.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Round committed transforms",
+                        DropdownSelect {
+                            content: Text::el(prefs.rounding.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { rounding: CommitRounding::ALL[index], ..prefs })
+                            }),
+                            items: CommitRounding::ALL.iter().map(|rounding| Text::el(rounding.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Double-click action",
+                        DropdownSelect {
+                            content: Text::el(prefs.double_click.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { double_click: DoubleClickAction::ALL[index], ..prefs })
+                            }),
+                            items: DoubleClickAction::ALL.iter().map(|action| Text::el(action.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Selection outlines",
+                        DropdownSelect {
+                            content: Text::el(prefs.selection_highlight.label()),
+                            on_select: cb({
+                                let set_prefs = set_prefs.clone();
+                                move |index| set_prefs(EditorPrefs { selection_highlight: SelectionHighlight::ALL[index], ..prefs })
+                            }),
+                            items: SelectionHighlight::ALL.iter().map(|highlight| Text::el(highlight.label())).collect(),
+                            inline: false,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Ghost opacity",
+                        Slider {
+                            value: prefs.ghost_opacity,
+                            on_change: Some(cb({
+                                let set_prefs = set_prefs.clone();
+                                move |ghost_opacity| set_prefs(EditorPrefs { ghost_opacity, ..prefs })
+                            })),
+                            min: 0.,
+                            max: 1.,
+                            width: 100.,
+                            logarithmic: false,
+                            round: Some(2),
+                            suffix: None,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Preview color",
+                        prefs.preview_color.editor(
+                            cb({
+                                let set_prefs = set_prefs.clone();
+                                move |preview_color| set_prefs(EditorPrefs { preview_color, ..prefs })
+                            }),
+                            Default::default(),
+                        ),
+                    ),
+                    EditorRow::el(
+                        "Preview opacity",
+                        Slider {
+                            value: prefs.preview_opacity,
+                            on_change: Some(cb({
+                                let set_prefs = set_prefs.clone();
+                                move |preview_opacity| set_prefs(EditorPrefs { preview_opacity, ..prefs })
+                            })),
+                            min: 0.05,
+                            max: 1.,
+                            width: 100.,
+                            logarithmic: false,
+                            round: Some(2),
+                            suffix: None,
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Click threshold",
+                        Slider {
+                            value: prefs.click_threshold,
+                            on_change: Some(cb({
+                                let set_prefs = set_prefs.clone();
+                                move |click_threshold| set_prefs(EditorPrefs { click_threshold, ..prefs })
+                            })),
+                            min: 0.,
+                            max: 20.,
+                            width: 100.,
+                            logarithmic: false,
+                            round: Some(0),
+                            suffix: Some("px"),
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Box select threshold",
+                        Slider {
+                            value: prefs.box_select_threshold,
+                            on_change: Some(cb({
+                                let set_prefs = set_prefs.clone();
+                                move |box_select_threshold| set_prefs(EditorPrefs { box_select_threshold, ..prefs })
+                            })),
+                            min: 0.,
+                            max: 30.,
+                            width: 100.,
+                            logarithmic: false,
+                            round: Some(0),
+                            suffix: Some("px"),
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Camera step",
+                        Slider {
+                            value: prefs.camera_step,
+                            on_change: Some(cb({
+                                let set_prefs = set_prefs.clone();
+                                move |camera_step| set_prefs(EditorPrefs { camera_step, ..prefs })
+                            })),
+                            min: 0.01,
+                            max: 10.,
+                            width: 100.,
+                            logarithmic: true,
+                            round: Some(2),
+                            suffix: Some("m"),
+                        }
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Spawn warning threshold",
+                        CountInput::new(prefs.spawn_warning, {
+                            let set_prefs = set_prefs.clone();
+                            move |spawn_warning| set_prefs(EditorPrefs { spawn_warning, ..prefs })
+                        })
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Batch confirm threshold",
+                        CountInput::new(prefs.batch_warning, {
+                            let set_prefs = set_prefs.clone();
+                            move |batch_warning| set_prefs(EditorPrefs { batch_warning, ..prefs })
+                        })
+                        .el(),
+                    ),
+                    EditorRow::el(
+                        "Selection refresh interval",
+                        prefs.resolve_interval.editor(
+                            cb({
+                                let set_prefs = set_prefs.clone();
+                                move |resolve_interval| set_prefs(EditorPrefs { resolve_interval, ..prefs })
+                            }),
+                            Default::default(),
+                        ),
+                    ),
+                    EditorRow::el(
+                        "Autosave interval",
+                        prefs.autosave_interval.editor(
+                            cb({
+                                let set_prefs = set_prefs.clone();
+                                move |autosave_interval| set_prefs(EditorPrefs { autosave_interval, ..prefs })
+                            }),
+                            Default::default(),
+                        ),
+                    ),
+                    Text::el("Action throttling").section_style(),
+                    EditorRow::el(
+                        "Dragging",
+                        prefs.throttles.drag.editor(
+                            cb({
+                                let set_prefs = set_prefs.clone();
+                                move |drag| set_prefs(EditorPrefs { throttles: ActionThrottles { drag, ..prefs.throttles }, ..prefs })
+                            }),
+                            Default::default(),
+                        ),
+                    ),
+                    EditorRow::el(
+                        "Placing",
+                        prefs.throttles.place.editor(
+                            cb({
+                                let set_prefs = set_prefs.clone();
+                                move |place| set_prefs(EditorPrefs { throttles: ActionThrottles { place, ..prefs.throttles }, ..prefs })
+                            }),
+                            Default::default(),
+                        ),
+                    ),
+                    EditorRow::el(
+                        "Discrete edits",
+                        prefs.throttles.discrete.editor(
+                            cb(move |discrete| {
+                                set_prefs(EditorPrefs { throttles: ActionThrottles { discrete, ..prefs.throttles }, ..prefs })
+                            }),
+                            Default::default(),
+                        ),
+                    ),
+                ])
+                .set(space_between_items(), STREET)
+                .set(fit_horizontal(), Fit::Parent),
+            )
+            .el()
+            .set(fit_horizontal(), Fit::Parent),
+        )
+        .el()
+    }
+}
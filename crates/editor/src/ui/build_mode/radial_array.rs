@@ -0,0 +1,119 @@
+use std::{f32::consts::TAU, sync::Arc};
+
+use ambient_core::runtime;
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_std::{cb, line_hash, Cb};
+use ambient_ui::{space_between_items, Button, Checkbox, CountInput, Editor, EditorRow, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use glam::{vec3, Mat4, Quat, Vec3};
+use itertools::Itertools;
+
+use super::{spawn_guard::GuardedSpawnButton, transform::current_transforms, use_gizmo_scope};
+use crate::{
+    intents::{intent_duplicate, IntentDuplicate},
+    ui::EditorPrefs,
+};
+
+/// How many line segments the preview ring is drawn with
+const RING_SEGMENTS: usize = 64;
+/// The default distance from the selection to the center of the ring
+const DEFAULT_RADIUS: f32 = 5.;
+
+fn preview_scope() -> u64 {
+    line_hash!()
+}
+
+/// Computes the world transforms of `count - 1` copies of `transforms`, evenly spaced around the
+/// vertical axis through `center`.
+///
+/// The copies are ordered copy by copy, matching the order of `transforms`. When `face_outward` is
+/// set each copy is also rotated along with its position.
+fn radial_array_transforms(transforms: &[Mat4], center: Vec3, count: u32, face_outward: bool) -> Vec<Mat4> {
+    (1..count)
+        .flat_map(|i| {
+            let rotation = Quat::from_rotation_z(TAU * i as f32 / count as f32);
+            transforms.iter().map(move |&transform| {
+                if face_outward {
+                    Mat4::from_translation(center) * Mat4::from_quat(rotation) * Mat4::from_translation(-center) * transform
+                } else {
+                    let (scl, rot, pos) = transform.to_scale_rotation_translation();
+                    Mat4::from_scale_rotation_translation(scl, rot, center + rotation * (pos - center))
+                }
+            })
+        })
+        .collect_vec()
+}
+
+/// Duplicates the selection around a center point, with a preview of the copies drawn as gizmos
+#[element_component]
+pub fn RadialArrayPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let initial = hooks.use_memo_with(targets.clone(), |_, targets| current_transforms(&game_client.game_state.lock().world, targets));
+    let (count, set_count) = hooks.use_state(6_u32);
+    let (center, set_center) = hooks.use_state(initial.midpoint - Vec3::X * DEFAULT_RADIUS);
+    let (face_outward, set_face_outward) = hooks.use_state(true);
+
+    let count = count.max(2);
+    let copies = radial_array_transforms(&initial.transforms, center, count, face_outward);
+
+    use_gizmo_scope(hooks, preview_scope());
+    hooks.use_effect((copies.clone(), center, initial.midpoint, prefs.preview_color), {
+        let game_state = game_client.game_state.clone();
+        move |_, (copies, center, midpoint, preview_color)| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(preview_scope());
+
+            let radius = (*midpoint - *center).truncate().length();
+            let ring = (0..=RING_SEGMENTS)
+                .map(|i| {
+                    let angle = TAU * i as f32 / RING_SEGMENTS as f32;
+                    *center + vec3(angle.cos(), angle.sin(), 0.) * radius
+                })
+                .collect_vec();
+            for (start, end) in ring.into_iter().tuple_windows() {
+                scope.draw(GizmoPrimitive::line(start, end, 0.02).with_color(*preview_color));
+            }
+
+            for copy in copies {
+                scope.draw(GizmoPrimitive::sphere(copy.transform_point3(Vec3::ZERO), 0.25).with_color(*preview_color));
+            }
+
+            Box::new(|_| {})
+        }
+    });
+
+    FlowColumn::el([
+        Text::el("Radial array").section_style(),
+        EditorRow::el("Count", CountInput::new(count, move |v| set_count(v)).min(2).el()),
+        EditorRow::el("Center", center.editor(set_center, Default::default())),
+        EditorRow::el("Face outward", Checkbox::new(face_outward, move |v| set_face_outward(v)).el()),
+        FlowRow::el([
+            GuardedSpawnButton::el(
+                "Create".to_string(),
+                copies.len(),
+                cb({
+                    let on_close = on_close.clone();
+                    let copies = copies.clone();
+                    move |world| {
+                        let entities = (1..count).flat_map(|_| targets.iter().copied());
+                        world.resource(runtime()).spawn(client_push_intent(
+                            game_client.clone(),
+                            intent_duplicate(),
+                            IntentDuplicate::batch(entities.zip(copies.clone()), true),
+                            None,
+                            None,
+                        ));
+                        on_close();
+                    }
+                }),
+            ),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
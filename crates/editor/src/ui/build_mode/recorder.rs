@@ -0,0 +1,131 @@
+use ambient_core::runtime;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::{client::GameClient, hooks::use_remote_persisted_resource, log_network_result};
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, Button, ButtonStyle, Editor, EditorRow, FlowColumn, FlowRow, StylesExt, Text, TextInput, STREET};
+use chrono::{DateTime, Utc};
+use glam::Vec3;
+use itertools::Itertools;
+
+use crate::{
+    editor_macros,
+    recording::{rpc_play_macro, rpc_recorded_steps, rpc_start_recording, EditorMacro},
+};
+
+/// Records the operations of a part of the session as a named macro, and replays the macros saved
+/// with the scene at an offset
+#[element_component]
+pub fn RecorderPanel(hooks: &mut Hooks, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (macros, set_macros) = use_remote_persisted_resource(hooks, editor_macros());
+    let macros = macros.unwrap_or_default();
+    let (name, set_name) = hooks.use_state(String::new());
+    let (recording, set_recording) = hooks.use_state(None as Option<DateTime<Utc>>);
+    let (offset, set_offset) = hooks.use_state(Vec3::ZERO);
+    let runtime = hooks.world.resource(runtime()).clone();
+
+    let record = if let Some(since) = recording {
+        FlowRow::el([
+            Text::el(format!("\u{f111} Recording {name}")).error_text_style(),
+            Button::new("Stop", {
+                let game_client = game_client.clone();
+                let runtime = runtime.clone();
+                let macros = macros.clone();
+                let set_macros = set_macros.clone();
+                let name = name.clone();
+                move |_| {
+                    set_recording(None);
+
+                    let game_client = game_client.clone();
+                    let mut macros = macros.clone();
+                    let set_macros = set_macros.clone();
+                    let name = name.clone();
+                    runtime.spawn(async move {
+                        let steps = match game_client.rpc(rpc_recorded_steps, since).await {
+                            Ok(Some(v)) => v,
+                            Ok(None) => return,
+                            Err(err) => {
+                                tracing::warn!("Failed to read the recorded operations: {err:?}");
+                                return;
+                            }
+                        };
+
+                        // Recording again under the same name replaces the macro
+                        macros.retain(|v| v.name != name);
+                        macros.push(EditorMacro { name, steps });
+                        set_macros(Some(macros));
+                    });
+                }
+            })
+            .style(ButtonStyle::Primary)
+            .el(),
+        ])
+    } else {
+        FlowRow::el([
+            TextInput::new(name.clone(), cb(move |v| set_name(v))).placeholder(Some("Macro name")).el(),
+            Button::new("\u{f111} Record", {
+                let game_client = game_client.clone();
+                let runtime = runtime.clone();
+                move |_| {
+                    let game_client = game_client.clone();
+                    let set_recording = set_recording.clone();
+                    runtime.spawn(async move {
+                        match game_client.rpc(rpc_start_recording, ()).await {
+                            Ok(since) => set_recording(since),
+                            Err(err) => tracing::warn!("Failed to start recording: {err:?}"),
+                        }
+                    });
+                }
+            })
+            .disabled(name.is_empty())
+            .el(),
+        ])
+    }
+    .set(space_between_items(), STREET);
+
+    let saved = macros
+        .iter()
+        .enumerate()
+        .map(|(i, editor_macro)| {
+            FlowRow::el([
+                Text::el(format!("{} ({} operations)", editor_macro.name, editor_macro.steps.len())),
+                Button::new("Play", {
+                    let game_client = game_client.clone();
+                    let runtime = runtime.clone();
+                    let steps = editor_macro.steps.clone();
+                    move |_| {
+                        let game_client = game_client.clone();
+                        let steps = steps.clone();
+                        runtime.spawn(async move {
+                            log_network_result!(game_client.rpc(rpc_play_macro, (steps, offset)).await);
+                        });
+                    }
+                })
+                .disabled(recording.is_some())
+                .el(),
+                Button::new("\u{f6bf}", {
+                    let macros = macros.clone();
+                    let set_macros = set_macros.clone();
+                    move |_| {
+                        let mut macros = macros.clone();
+                        macros.remove(i);
+                        set_macros(Some(macros));
+                    }
+                })
+                .tooltip("Delete macro")
+                .style(ButtonStyle::Flat)
+                .el(),
+            ])
+            .set(space_between_items(), STREET)
+        })
+        .collect_vec();
+
+    FlowColumn::el([
+        Text::el("Macros").section_style(),
+        record,
+        EditorRow::el("Playback offset", offset.editor(set_offset, Default::default())),
+        if saved.is_empty() { Text::el("No saved macros").small_style() } else { FlowColumn::el(saved).set(space_between_items(), STREET) },
+        Button::new("Close", move |_| on_close()).el(),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use ambient_core::{
+    asset_cache,
+    bounding::{local_bounding_aabb, world_bounding_aabb, world_bounding_sphere},
+    main_scene, mesh,
+    transform::{local_to_world, mesh_to_world},
+};
+use ambient_ecs::Entity;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gpu::{
+    std_assets::{DefaultNormalMapViewKey, PixelTextureViewKey},
+    texture::TextureView,
+    texture_loaders::TextureFromUrl,
+};
+use ambient_meshes::UnitQuadMeshKey;
+use ambient_network::{client::GameClient, hooks::use_remote_persisted_resource};
+use ambient_renderer::{
+    color, double_sided, gpu_primitives, material,
+    materials::pbr_material::{get_pbr_shader_unlit, PbrMaterial, PbrMaterialConfig, PbrMaterialParams},
+    primitives, renderer_shader, SharedMaterial,
+};
+use ambient_std::{
+    asset_cache::{AssetCache, AsyncAssetKeyExt, SyncAssetKeyExt},
+    asset_url::{select_asset, AbsAssetUrl, AssetType},
+    cb,
+    shapes::AABB,
+    Cb,
+};
+use ambient_ui::{space_between_items, Button, ButtonStyle, Editor, EditorRow, FlowColumn, FlowRow, Slider, StylesExt, Text, STREET};
+use glam::{vec3, vec4, Mat4, Quat, Vec3, Vec4};
+
+use crate::{reference_image, ReferenceImage};
+
+fn image_material(assets: &AssetCache, texture: Arc<TextureView>, opacity: f32) -> SharedMaterial {
+    SharedMaterial::new(PbrMaterial::new(
+        assets.clone(),
+        PbrMaterialConfig {
+            source: "Reference image".to_string(),
+            name: "Reference image".to_string(),
+            params: PbrMaterialParams { base_color_factor: vec4(1., 1., 1., opacity), alpha_cutoff: 0., ..Default::default() },
+            base_color: texture,
+            normalmap: DefaultNormalMapViewKey.get(assets),
+            metallic_roughness: PixelTextureViewKey::white().get(assets),
+            transparent: Some(true),
+            double_sided: Some(true),
+            // Let the scene draw over it
+            depth_write_enabled: Some(false),
+        },
+    ))
+}
+
+/// Shows the reference image of the scene in the viewport, once it has loaded
+#[element_component]
+pub fn ReferenceImageOverlay(hooks: &mut Hooks) -> Element {
+    let (image, _) = use_remote_persisted_resource(hooks, reference_image());
+    match image {
+        Some(image) if image.url.is_some() => ReferenceImageLoader::el(image.clone()).key(format!("{:?}", image.url)),
+        _ => Element::new(),
+    }
+}
+
+#[element_component]
+fn ReferenceImageLoader(hooks: &mut Hooks, image: ReferenceImage) -> Element {
+    let url = match image.url.clone() {
+        Some(v) => v,
+        None => return Element::new(),
+    };
+
+    let texture = hooks
+        .use_async(|w| {
+            let assets = w.resource(asset_cache()).clone();
+            async move {
+                let url = AbsAssetUrl::parse(url).ok()?;
+                let texture = TextureFromUrl { url, format: wgpu::TextureFormat::Rgba8UnormSrgb }.get(&assets).await.ok()?;
+                Some(Arc::new(texture.create_view(&Default::default())))
+            }
+        })
+        .flatten();
+
+    match texture {
+        Some(texture) => ReferenceImageQuad::el(texture, image),
+        None => Element::new(),
+    }
+}
+
+#[element_component]
+fn ReferenceImageQuad(hooks: &mut Hooks, texture: Arc<TextureView>, image: ReferenceImage) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let assets = hooks.world.resource(asset_cache()).clone();
+
+    let (entity, _) = hooks.use_state_with(|_| {
+        let aabb = AABB { min: vec3(-0.5, -0.5, 0.), max: vec3(0.5, 0.5, 0.) };
+        Entity::new()
+            .with(mesh(), UnitQuadMeshKey.get(&assets))
+            .with_default(local_to_world())
+            .with_default(mesh_to_world())
+            .with(primitives(), vec![])
+            .with_default(gpu_primitives())
+            .with(main_scene(), ())
+            .with(local_bounding_aabb(), aabb)
+            .with(world_bounding_sphere(), aabb.to_sphere())
+            .with(world_bounding_aabb(), aabb)
+            .with(color(), Vec4::ONE)
+            .with(double_sided(), true)
+            .with(material(), image_material(&assets, texture.clone(), image.opacity))
+            .with(renderer_shader(), cb(get_pbr_shader_unlit))
+            .spawn(&mut game_client.game_state.lock().world)
+    });
+
+    {
+        let game_state = game_client.game_state.clone();
+        hooks.use_spawn(move |_| {
+            Box::new(move |_| {
+                game_state.lock().world.despawn(entity);
+            })
+        });
+    }
+
+    hooks.use_effect(image.opacity, {
+        let game_client = game_client.clone();
+        let texture = texture.clone();
+        move |_, &opacity| {
+            game_client.game_state.lock().world.set(entity, material(), image_material(&assets, texture, opacity)).ok();
+            Box::new(|_| {})
+        }
+    });
+
+    let size = texture.texture.size;
+    let aspect = size.height as f32 / size.width.max(1) as f32;
+    let transform = Mat4::from_scale_rotation_translation(
+        vec3(image.width, image.width * aspect, 1.),
+        Quat::from_rotation_z(image.yaw.to_radians()),
+        image.position,
+    );
+    hooks.use_effect(transform, move |_, &transform| {
+        game_client.game_state.lock().world.set(entity, local_to_world(), transform).ok();
+        Box::new(|_| {})
+    });
+
+    Element::new()
+}
+
+/// Picks the reference image and where it lies
+#[element_component]
+pub fn ReferenceImagePanel(hooks: &mut Hooks, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (image, set_image) = use_remote_persisted_resource(hooks, reference_image());
+    let image = image.unwrap_or_default();
+
+    let set = {
+        let image = image.clone();
+        move |update: &dyn Fn(&mut ReferenceImage)| {
+            let mut image = image.clone();
+            update(&mut image);
+            set_image(Some(image));
+        }
+    };
+
+    FlowColumn::el([
+        Text::el("Reference image").section_style(),
+        Text::el("Saved with the scene, and only shown in the editor").small_style(),
+        EditorRow::el(
+            "Image",
+            Button::new(image.url.clone().unwrap_or_else(|| "Pick image".to_string()), {
+                let set = set.clone();
+                move |world| {
+                    let set = set.clone();
+                    select_asset(world.resource(asset_cache()), AssetType::Image, move |url| {
+                        if let Some(url) = url.random().cloned() {
+                            set(&|image| image.url = Some(url.clone()));
+                        }
+                    });
+                }
+            })
+            .style(ButtonStyle::Flat)
+            .el(),
+        ),
+        EditorRow::el(
+            "Opacity",
+            Slider {
+                value: image.opacity,
+                on_change: Some(cb({
+                    let set = set.clone();
+                    move |opacity| set(&|image| image.opacity = opacity)
+                })),
+                min: 0.,
+                max: 1.,
+                width: 100.,
+                logarithmic: false,
+                round: Some(2),
+                suffix: None,
+            }
+            .el(),
+        ),
+        EditorRow::el(
+            "Width",
+            Slider {
+                value: image.width,
+                on_change: Some(cb({
+                    let set = set.clone();
+                    move |width| set(&|image| image.width = width)
+                })),
+                min: 1.,
+                max: 1000.,
+                width: 100.,
+                logarithmic: true,
+                round: Some(1),
+                suffix: Some("m"),
+            }
+            .el(),
+        ),
+        EditorRow::el(
+            "Rotation",
+            Slider {
+                value: image.yaw,
+                on_change: Some(cb({
+                    let set = set.clone();
+                    move |yaw| set(&|image| image.yaw = yaw)
+                })),
+                min: -180.,
+                max: 180.,
+                width: 100.,
+                logarithmic: false,
+                round: Some(0),
+                suffix: Some("\u{00b0}"),
+            }
+            .el(),
+        ),
+        EditorRow::el(
+            "Position",
+            image.position.editor(
+                cb({
+                    let set = set.clone();
+                    move |position: Vec3| set(&|image| image.position = position)
+                }),
+                Default::default(),
+            ),
+        ),
+        FlowRow::el([
+            Button::new("Remove", {
+                let set = set.clone();
+                move |_| set(&|image| image.url = None)
+            })
+            .disabled(image.url.is_none())
+            .el(),
+            Button::new("Close", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use ambient_core::{asset_cache, selectable};
+use ambient_ecs::{query, EntityId};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::{client::GameClient, is_remote_entity};
+use ambient_prefab::prefab_from_url;
+use ambient_std::{
+    asset_url::{select_asset, AssetType},
+    Cb,
+};
+use ambient_ui::{space_between_items, Button, ButtonStyle, EditorRow, FlowColumn, FlowRow, StylesExt, Text, STREET};
+
+use super::batch::{use_batch_guard, PendingBatch};
+use crate::intents::intent_replace_object;
+
+/// Replaces every instance of the object of the active entity with another object, keeping their
+/// transforms, as one undo step
+#[element_component]
+pub fn ReplaceObjectPanel(hooks: &mut Hooks, active: EntityId, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (target, set_target) = hooks.use_state(None as Option<String>);
+    let guard_batch = use_batch_guard(hooks);
+
+    let (source, instances) = {
+        let state = game_client.game_state.lock();
+        match state.world.get_cloned(active, prefab_from_url()) {
+            Ok(source) => {
+                let instances: Arc<[EntityId]> = query((selectable(), prefab_from_url()))
+                    .incl(is_remote_entity())
+                    .iter(&state.world, None)
+                    .filter(|(_, (_, url))| **url == source)
+                    .map(|(id, _)| id)
+                    .collect();
+                (source, instances)
+            }
+            Err(_) => {
+                return FlowColumn::el([
+                    Text::el("Replace object").section_style(),
+                    Text::el("The active entity was not spawned from an object"),
+                    Button::new("Close", move |_| on_close()).el(),
+                ])
+                .set(space_between_items(), STREET)
+            }
+        }
+    };
+
+    let count = instances.len();
+    let can_replace = target.as_ref().map_or(false, |target| *target != source);
+
+    FlowColumn::el([
+        Text::el("Replace object").section_style(),
+        EditorRow::el("Replace", Text::el(source.clone()).small_style()),
+        EditorRow::el(
+            "With",
+            Button::new(target.clone().unwrap_or_else(|| "Pick object".to_string()), move |world| {
+                let set_target = set_target.clone();
+                select_asset(world.resource(asset_cache()), AssetType::Prefab, move |object_url| {
+                    if let Some(object_url) = object_url.random().cloned() {
+                        set_target(Some(object_url));
+                    }
+                });
+            })
+            .style(ButtonStyle::Flat)
+            .el(),
+        ),
+        if let Some(target) = &target {
+            Text::el(format!("{count} instances will be replaced with {target}"))
+        } else {
+            Text::el(format!("{count} instances in the scene")).small_style()
+        },
+        FlowRow::el([
+            Button::new("Replace all", {
+                let on_close = on_close.clone();
+                let target = target.clone();
+                move |world| {
+                    if let Some(target) = target.clone() {
+                        let batch = PendingBatch::intent(
+                            "Replace",
+                            instances.clone(),
+                            game_client.clone(),
+                            intent_replace_object(),
+                            (source.clone(), target),
+                        );
+                        guard_batch(world, batch);
+                        on_close();
+                    }
+                }
+            })
+            .disabled(!can_replace)
+            .style(ButtonStyle::Primary)
+            .el(),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
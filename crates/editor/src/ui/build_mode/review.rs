@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use ambient_core::{bounding::calc_world_bounding_recursive, name, runtime};
+use ambient_ecs::{query, EntityId};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_gizmos::{gizmos, Cuboid};
+use ambient_network::{client::GameClient, log_network_result};
+use ambient_std::{line_hash, Cb};
+use ambient_ui::{space_between_items, use_interval, use_interval_deps, Button, ButtonStyle, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use glam::{vec3, Vec3};
+use itertools::Itertools;
+
+use super::{teleport::teleport_to_entities, use_gizmo_scope};
+use crate::{
+    intents::SelectMode,
+    review_marker,
+    rpc::{rpc_select, SelectMethod},
+    Selection,
+};
+
+const MARKER_COLOR: Vec3 = vec3(1., 0.1, 0.6);
+const MARKER_THICKNESS: f32 = 0.05;
+/// How much larger than the entity the box is drawn, so that it doesn't hide inside the surface
+const MARKER_MARGIN: f32 = 0.1;
+
+fn marker_scope() -> u64 {
+    line_hash!()
+}
+
+/// Draws a box around each entity marked for review, so that flagged spots stand out in the scene
+/// whether they are selected or not
+#[element_component]
+pub fn ReviewMarkers(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    use_interval_deps(hooks, Duration::from_millis(200), true, (), {
+        let game_state = game_client.game_state.clone();
+        move |_| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(marker_scope());
+            for (id, _) in query(review_marker()).iter(&state.world, None) {
+                if let Some(bounds) = calc_world_bounding_recursive(&state.world, id) {
+                    let extents = bounds.size() / 2. + MARKER_MARGIN;
+                    scope.draw(Cuboid::new(bounds.center(), extents, MARKER_COLOR, MARKER_THICKNESS));
+                }
+            }
+        }
+    });
+
+    use_gizmo_scope(hooks, marker_scope());
+
+    Element::new()
+}
+
+/// The entities marked for review, with the name and note of each
+fn marked_entities(game_client: &GameClient) -> Vec<(EntityId, String)> {
+    let state = game_client.game_state.lock();
+    query(review_marker())
+        .iter(&state.world, None)
+        .map(|(id, note)| {
+            let name = state.world.get_cloned(id, name()).unwrap_or_else(|_| format!("Entity {id}"));
+            (id, if note.is_empty() { name } else { format!("{name}: {note}") })
+        })
+        .sorted_by(|a, b| a.1.cmp(&b.1))
+        .collect_vec()
+}
+
+/// Lists the entities marked for review, to select each one and move the camera to it
+#[element_component]
+pub fn ReviewMarkersPanel(hooks: &mut Hooks, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (marked, set_marked) = hooks.use_state_with(|_| marked_entities(&game_client));
+
+    use_interval(hooks, 0.5, {
+        let game_client = game_client.clone();
+        move || set_marked(marked_entities(&game_client))
+    });
+
+    let rows = marked
+        .into_iter()
+        .map(|(id, label)| {
+            let game_client = game_client.clone();
+            Button::new(label, move |world| {
+                teleport_to_entities(&mut game_client.game_state.lock(), &[id]);
+                let game_client = game_client.clone();
+                world.resource(runtime()).spawn(async move {
+                    log_network_result!(
+                        game_client.rpc(rpc_select, (SelectMethod::Manual(Selection::new(vec![id])), SelectMode::Set)).await
+                    );
+                });
+            })
+            .style(ButtonStyle::Flat)
+            .el()
+        })
+        .collect_vec();
+
+    FlowColumn::el([
+        Text::el("Review markers").section_style(),
+        if rows.is_empty() {
+            Text::el("Nothing is marked, use Mark for review on an entity to flag it").small_style()
+        } else {
+            FlowColumn::el(rows)
+        },
+        FlowRow::el([Button::new("Close", move |_| on_close()).el()]).set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
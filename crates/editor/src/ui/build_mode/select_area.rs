@@ -1,24 +1,44 @@
+use std::time::{Duration, Instant};
+
 use ambient_core::{
-    mouse_position, runtime,
+    hierarchy::{children, parent},
+    mouse_position, runtime, selectable,
     transform::{get_world_position, translation},
     window_logical_size, window_scale_factor,
 };
-use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_ecs::{query, ArchetypeFilter, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponent, ElementComponentExt, Group, Hooks};
 use ambient_input::{event_mouse_input, event_mouse_motion, MouseButton};
-use ambient_network::{client::GameClient, log_network_result};
-use ambient_std::{color::Color, math::interpolate};
+use ambient_network::{client::GameClient, is_remote_entity, log_network_result};
+use ambient_physics::intersection::{rpc_pick, RaycastFilter};
+use ambient_prefab::prefab_from_url;
+use ambient_std::{color::Color, math::interpolate, shapes::Ray, Cb};
 use ambient_ui::{
     layout::{height, width},
-    UIBase, UIExt,
+    Hotkey, UIBase, UIExt,
 };
 use glam::{vec2, vec3, Vec2, Vec3Swizzles};
-use winit::event::ElementState;
+use itertools::Itertools;
+use winit::event::{ElementState, ModifiersState, VirtualKeyCode};
 
+use super::{pivot::PivotEntity, teleport::teleport_to_entities, transform::use_alt_held, TagFilter, TransformMode};
 use crate::{
     intents::SelectMode,
     rpc::{rpc_select, SelectMethod},
+    ui::{use_player_selection, DoubleClickAction, EditorPrefs},
+    Selection,
 };
 
+/// How soon a second click must follow the first to be a double-click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+/// How far the cursor can move between the clicks of a double-click, in logical pixels
+const DOUBLE_CLICK_DISTANCE: f32 = 5.;
+
+/// While locked, clicking or dragging in the viewport doesn't change the selection. Explicit
+/// commands, such as inverting or saving the selection, still do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SelectionLock(pub bool);
+
 #[derive(Debug, Clone)]
 /// Handles the server communication for selecting objects
 pub struct SelectArea;
@@ -29,7 +49,29 @@ impl ElementComponent for SelectArea {
         let (mouse_pos, set_mouse_pos) = hooks.use_state(Vec2::ZERO);
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
         let (select_mode, _) = hooks.consume_context::<SelectMode>().unwrap();
+        let (TagFilter(tag_filter), _) = hooks.consume_context::<TagFilter>().unwrap();
+        let (SelectionLock(locked), _) = hooks.consume_context::<SelectionLock>().unwrap();
+        let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+        let (_, set_pivot_entity) = hooks.consume_context::<PivotEntity>().unwrap();
+        // Alt-clicking pins the pivot to the clicked entity rather than selecting it
+        let pick_pivot = use_alt_held(hooks);
         let is_clicking = hooks.use_ref_with(|_| false);
+        // Set by a box selection, to enter `box_select_mode` once the new selection arrives
+        let pending_mode = hooks.use_ref_with(|_| false);
+        let (selection, _) = use_player_selection(hooks);
+        let (_, set_srt_mode) = hooks.consume_context::<Option<TransformMode>>().unwrap();
+        hooks.use_effect(selection, {
+            let pending_mode = pending_mode.clone();
+            move |_, selection| {
+                if std::mem::take(&mut *pending_mode.lock()) && selection.len() > 1 {
+                    if let Some(mode) = TransformMode::from_default(prefs.box_select_mode) {
+                        set_srt_mode(Some(mode));
+                    }
+                }
+                Box::new(|_| {})
+            }
+        });
+        let last_click = hooks.use_ref_with(|_| None as Option<(Instant, Vec2)>);
 
         let client = game_client.clone();
         hooks.use_spawn(move |_| {
@@ -42,6 +84,7 @@ impl ElementComponent for SelectArea {
         hooks.use_world_event({
             let set_dragging = set_dragging.clone();
             let is_clicking = is_clicking.clone();
+            let pending_mode = pending_mode.clone();
             move |world, event| {
                 let scl = *world.resource(window_scale_factor()) as f32;
                 if let Some(position) = event.get(event_mouse_motion()) {
@@ -67,7 +110,8 @@ impl ElementComponent for SelectArea {
                             let min_y = dragging.y.min(mouse_pos.y);
                             let max_y = dragging.y.max(mouse_pos.y);
                             let size = vec2(max_x - min_x, max_y - min_y);
-                            if size.x > 5. || size.y > 5. {
+                            // Anything shorter is a click, which selects with the same mode
+                            if size.max_element() > prefs.box_select_threshold {
                                 let frustum = {
                                     let state = game_client.game_state.lock();
                                     let get_corner = |p, z| {
@@ -86,8 +130,11 @@ impl ElementComponent for SelectArea {
                                         get_corner(vec2(max_x, max_y), 0.001),
                                     ]
                                 };
+                                let method = SelectMethod::Frustum(frustum, tag_filter.clone());
+                                // Removing from the selection is not followed by a transform
+                                *pending_mode.lock() = matches!(select_mode, SelectMode::Set | SelectMode::Add);
                                 world.resource(runtime()).clone().spawn(async move {
-                                    log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Frustum(frustum), select_mode)).await);
+                                    log_network_result!(game_client.rpc(rpc_select, (method, select_mode)).await);
                                 });
                                 return;
                             }
@@ -99,6 +146,26 @@ impl ElementComponent for SelectArea {
                             state.screen_ray(p)
                         };
 
+                        let is_double_click = {
+                            let mut last_click = last_click.lock();
+                            let now = Instant::now();
+                            let is_double_click = last_click.map_or(false, |(time, pos)| {
+                                now - time < DOUBLE_CLICK_TIME && pos.distance(mouse_pos) < DOUBLE_CLICK_DISTANCE
+                            });
+                            // A third click starts over rather than being another double-click
+                            *last_click = if is_double_click { None } else { Some((now, mouse_pos)) };
+                            is_double_click
+                        };
+                        if is_double_click {
+                            world.resource(runtime()).spawn(double_click(game_client.clone(), ray, prefs.double_click));
+                            return;
+                        }
+
+                        if pick_pivot {
+                            world.resource(runtime()).spawn(pin_pivot_at(game_client.clone(), ray, set_pivot_entity.clone()));
+                            return;
+                        }
+
                         let game_client = game_client.clone();
                         world.resource(runtime()).clone().spawn(async move {
                             log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Ray(ray), select_mode)).await);
@@ -108,13 +175,17 @@ impl ElementComponent for SelectArea {
             }
         });
 
+        // The box is only drawn once the drag is long enough to select with it
+        let dragging = dragging.filter(|start| (*start - mouse_pos).abs().max_element() > prefs.box_select_threshold);
+
         UIBase
             .el()
             .with_clickarea()
             .on_mouse_down(closure!(clone set_dragging, clone is_clicking, |world, id, button| {
-                if button != MouseButton::Left {
+                if button != MouseButton::Left || locked {
                     return;
                 }
+                *pending_mode.lock() = false;
 
                 let area_offset = get_world_position(world, id).unwrap().xy();
                 let scl = *world.resource(window_scale_factor()) as f32;
@@ -140,3 +211,125 @@ impl ElementComponent for SelectArea {
             }])
     }
 }
+
+/// Pins the pivot to the selectable entity under `ray`, leaving the selection as it is
+async fn pin_pivot_at(game_client: GameClient, ray: Ray, set_pivot_entity: Cb<dyn Fn(PivotEntity) + Sync + Send>) {
+    let filter = RaycastFilter { entities: Some(ArchetypeFilter::new().incl(selectable())), collider_type: None };
+    match game_client.rpc(rpc_pick, (ray, filter)).await {
+        Ok(Some((id, _))) => set_pivot_entity(PivotEntity(Some(id))),
+        Ok(None) => tracing::info!("Nothing under the cursor to pin the pivot to"),
+        Err(err) => tracing::warn!("Failed to pick the pivot entity: {err:?}"),
+    }
+}
+
+/// Performs `action` on the entity under `ray`
+async fn double_click(game_client: GameClient, ray: Ray, action: DoubleClickAction) {
+    let filter = RaycastFilter { entities: None, collider_type: None };
+    let id = match game_client.rpc(rpc_pick, (ray, filter)).await {
+        Ok(Some((id, _))) => id,
+        Ok(None) => return,
+        Err(err) => {
+            tracing::warn!("Failed to pick the double-clicked entity: {err:?}");
+            return;
+        }
+    };
+
+    let selection = {
+        let mut state = game_client.game_state.lock();
+        match action {
+            DoubleClickAction::FocusCamera => {
+                teleport_to_entities(&mut state, &[id]);
+                return;
+            }
+            DoubleClickAction::EnterGroup => state.world.get_cloned(id, children()).unwrap_or_default(),
+            DoubleClickAction::OpenInspector => vec![id],
+            DoubleClickAction::SelectLinked => match state.world.get_cloned(id, prefab_from_url()) {
+                Ok(url) => query((selectable(), prefab_from_url()))
+                    .incl(is_remote_entity())
+                    .iter(&state.world, None)
+                    .filter(|(_, (_, v))| **v == url)
+                    .map(|(id, _)| id)
+                    .collect_vec(),
+                Err(_) => vec![id],
+            },
+        }
+    };
+
+    if selection.is_empty() {
+        tracing::info!("{id} has no children to enter");
+        return;
+    }
+    log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Manual(Selection::new(selection)), SelectMode::Set)).await);
+}
+
+/// The selectable parents of `targets`, or their selectable immediate children if `down`.
+///
+/// Going up, entities without a selectable parent stay selected, so that a mixed selection
+/// converges on the roots. Returns None if the selection wouldn't change
+pub(super) fn hierarchy_step(world: &World, targets: &[EntityId], down: bool) -> Option<Selection> {
+    let is_selectable = |id: EntityId| world.has_component(id, selectable());
+    let stepped = if down {
+        targets
+            .iter()
+            .flat_map(|&id| world.get_cloned(id, children()).unwrap_or_default())
+            .filter(|&id| is_selectable(id))
+            .unique()
+            .collect_vec()
+    } else {
+        targets.iter().map(|&id| world.get(id, parent()).ok().filter(|&parent| is_selectable(parent)).unwrap_or(id)).unique().collect_vec()
+    };
+
+    if stepped.is_empty() || stepped == targets {
+        None
+    } else {
+        Some(Selection::new(stepped))
+    }
+}
+
+/// The sticky select mode which follows `mode` when cycling with the hotkey
+pub(super) fn next_sticky_select_mode(mode: SelectMode) -> SelectMode {
+    match mode {
+        SelectMode::Set => SelectMode::Add,
+        SelectMode::Add => SelectMode::Remove,
+        SelectMode::Remove | SelectMode::Clear => SelectMode::Set,
+    }
+}
+
+pub(super) fn select_mode_label(mode: SelectMode) -> &'static str {
+    match mode {
+        SelectMode::Set => "Replace",
+        SelectMode::Add => "Add",
+        SelectMode::Remove => "Remove",
+        SelectMode::Clear => "Clear",
+    }
+}
+
+/// Selects the entities in view of the camera, limited to the tag filter
+pub(super) fn select_visible(world: &World, game_client: GameClient, mode: SelectMode, tag_filter: Option<String>) {
+    let proj_view = match game_client.game_state.lock().proj_view() {
+        Some(v) => v,
+        None => return,
+    };
+
+    world.resource(runtime()).spawn(async move {
+        log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Visible(proj_view, tag_filter), mode)).await);
+    });
+}
+
+/// Selecting everything on screen with Shift or Control held adds to or removes from the
+/// selection, which the toolbar button can't have a hotkey for
+#[element_component]
+pub fn SelectVisibleHotkeys(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (TagFilter(tag_filter), _) = hooks.consume_context::<TagFilter>().unwrap();
+
+    let hotkey = |modifier: ModifiersState, mode: SelectMode| {
+        let game_client = game_client.clone();
+        let tag_filter = tag_filter.clone();
+        Hotkey::new(VirtualKeyCode::V, move |world| select_visible(world, game_client.clone(), mode, tag_filter.clone()), Element::new())
+            .hotkey_modifier(modifier)
+            .el()
+    };
+
+    Group(vec![hotkey(ModifiersState::SHIFT, SelectMode::Add), hotkey(ModifiersState::CTRL, SelectMode::Remove)]).el()
+}
@@ -1,35 +1,80 @@
-use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
-use ambient_network::{client::GameClient, log_network_result};
-use ambient_std::Cb;
+use std::{
+    cmp::Reverse,
+    f32::consts::{PI, TAU},
+    iter::once,
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_core::{name, runtime, selectable, transform::get_world_transform};
+use ambient_ecs::{query, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_network::{client::GameClient, is_remote_entity, log_network_result};
+use ambient_prefab::prefab_from_url;
+use ambient_std::{cb, Cb};
 use ambient_ui::{
     layout::{fit_horizontal, fit_vertical, space_between_items, Fit},
-    Button, FlowColumn, Text, UIExt, STREET,
+    use_interval_deps, Button, ButtonStyle, DropdownSelect, F32Input, FlowColumn, FlowRow, Slider, StylesExt, Text, UIExt, STREET,
 };
+use glam::{EulerRot, Mat4, Vec3};
+use itertools::Itertools;
+use parking_lot::Mutex;
 
-use super::super::entity_editor::EntityEditor;
-use crate::{rpc::rpc_toggle_visualize_colliders, ui::EditorSettings, Selection};
+use super::{
+    super::entity_editor::EntityEditor,
+    pivot::use_pivot,
+    select_area::SelectionLock,
+    transform::{current_transforms, scale_around, IntialState},
+    EditorAction,
+};
+use crate::{
+    intents::{intent_set_transform, IntentTransform, TerrainOffset},
+    rpc::rpc_toggle_visualize_colliders,
+    ui::{EditorPrefs, EditorSettings},
+    Selection,
+};
 
 #[derive(Debug, Clone)]
 pub struct SelectionPanel {
     pub selection: Selection,
     pub set_selection: Cb<dyn Fn(Selection) + Sync + Send>,
+    /// Selected entities which could not be resolved in the local world
+    pub missing: Vec<EntityId>,
 }
 
 impl ElementComponent for SelectionPanel {
     #[profiling::function]
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
-        let Self { selection, set_selection: _ } = *self;
+        let Self { selection, set_selection, missing } = *self;
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
         let (settings, _) = hooks.consume_context::<EditorSettings>().unwrap();
+        let (SelectionLock(locked), set_lock) = hooks.consume_context::<SelectionLock>().unwrap();
 
         FlowColumn(vec![
+            if locked {
+                FlowRow::el([
+                    Text::el("\u{f023} Selection locked").section_style(),
+                    Button::new("Unlock", move |_| set_lock(SelectionLock(false))).style(ButtonStyle::Flat).el(),
+                ])
+                .set(space_between_items(), STREET)
+            } else {
+                Element::new()
+            },
+            if !missing.is_empty() {
+                MissingEntitiesNotice { selection: selection.clone(), set_selection: set_selection.clone(), missing }.el()
+            } else {
+                Element::new()
+            },
+            SelectionDimensions { selection: selection.clone() }.el(),
+            TransformAccumulator::el(selection.clone()).key(format!("{selection:?}")),
+            if let Some(&active) = selection.entities.last() { SelectSimilar::el(active, set_selection.clone()) } else { Element::new() },
             #[allow(clippy::comparison_chain)]
             if selection.len() == 1 {
                 let _state = game_client.game_state.lock();
 
                 EntityEditor { entity_id: selection.entities[0] }.el().set(fit_horizontal(), Fit::Parent)
             } else {
-                Text::el(format!("{} entities", selection.len()))
+                SelectionBreakdown::el(selection.clone(), set_selection)
             },
             if !selection.is_empty() && settings.debug_mode {
                 Button::new_async(
@@ -55,3 +100,290 @@ impl ElementComponent for SelectionPanel {
         .el()
     }
 }
+
+/// Shown when some of the selected entities have vanished, for example due to being deleted by
+/// another client, and offers to drop them from the selection.
+#[derive(Debug, Clone)]
+pub struct MissingEntitiesNotice {
+    pub selection: Selection,
+    pub set_selection: Cb<dyn Fn(Selection) + Sync + Send>,
+    pub missing: Vec<EntityId>,
+}
+
+impl ElementComponent for MissingEntitiesNotice {
+    fn render(self: Box<Self>, _: &mut Hooks) -> Element {
+        let Self { selection, set_selection, missing } = *self;
+
+        FlowRow(vec![
+            Text::el(format!("{} selected entities no longer exist", missing.len())).error_text_style(),
+            Button::new("Clear", move |_| {
+                let mut selection = selection.clone();
+                selection.difference(&Selection::new(missing.clone()));
+                set_selection(selection);
+            })
+            .style(ButtonStyle::Flat)
+            .el(),
+        ])
+        .el()
+        .set(space_between_items(), STREET)
+    }
+}
+
+/// Shows the world space width, depth and height of the selection, and allows typing in an exact
+/// dimension which the selection is then scaled to around the pivot of the transform controls
+#[element_component]
+fn SelectionDimensions(hooks: &mut Hooks, selection: Selection) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let (state, set_state) = hooks.use_state(IntialState::default());
+    let pivot = use_pivot(hooks);
+    let action = hooks.use_memo_with(selection.clone(), |world, _| {
+        Arc::new(Mutex::new(EditorAction::new(
+            world.resource(runtime()).clone(),
+            game_client.clone(),
+            intent_set_transform(),
+            &prefs.throttles,
+        )))
+    });
+
+    use_interval_deps(hooks, Duration::from_millis(100), true, selection.clone(), {
+        let game_client = game_client.clone();
+        move |selection| {
+            let game_state = game_client.game_state.lock();
+            let targets = selection.iter().filter(|id| game_state.world.exists(*id)).collect_vec();
+            set_state(current_transforms(&game_state.world, &targets));
+        }
+    });
+
+    let bounds = match state.bounds {
+        Some(v) => v,
+        None => return Element::new(),
+    };
+
+    let set_dimension = cb(move |index: usize, value: f32| {
+        let current = bounds.size()[index];
+        if value <= 0.0 || current <= f32::EPSILON {
+            return;
+        }
+
+        let mut scale = Vec3::ONE;
+        scale[index] = value / current;
+
+        let (entities, pivot) = {
+            let game_state = game_client.game_state.lock();
+            (selection.iter().filter(|id| game_state.world.exists(*id)).collect_vec(), pivot.point(&game_state.world, &state))
+        };
+        if entities.len() != state.transforms.len() {
+            return;
+        }
+
+        let mut action = action.lock();
+        action.push_intent(IntentTransform {
+            entities,
+            transforms: scale_around(&state.transforms, pivot, scale),
+            terrain_offset: TerrainOffset::Update,
+        });
+        action.confirm();
+    });
+
+    FlowRow(
+        ["W", "D", "H"]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, label)| {
+                let set_dimension = set_dimension.clone();
+                [Text::el(label), F32Input::new(bounds.size()[index], move |value| set_dimension(index, value)).el()]
+            })
+            .collect_vec(),
+    )
+    .el()
+    .set(space_between_items(), STREET)
+}
+
+/// Shows how far the selection has been moved, rotated and scaled in total since it was selected
+/// or the readout was reset, for checking relative moves made over several edits
+#[element_component]
+fn TransformAccumulator(hooks: &mut Hooks, selection: Selection) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let baseline = hooks.use_ref_with(|_| None as Option<IntialState>);
+    let (current, set_current) = hooks.use_state(IntialState::default());
+
+    use_interval_deps(hooks, Duration::from_millis(250), true, selection, {
+        let baseline = baseline.clone();
+        let set_current = set_current.clone();
+        move |selection| {
+            let game_state = game_client.game_state.lock();
+            let targets = selection.iter().filter(|id| game_state.world.exists(*id)).collect_vec();
+            let state = current_transforms(&game_state.world, &targets);
+            let mut baseline = baseline.lock();
+            if baseline.is_none() && !state.transforms.is_empty() {
+                *baseline = Some(state.clone());
+            }
+            set_current(state);
+        }
+    });
+
+    let reset = {
+        let baseline = baseline.clone();
+        let current = current.clone();
+        move |_: &mut World| {
+            *baseline.lock() = Some(current.clone());
+            set_current(current.clone());
+        }
+    };
+
+    let baseline = match baseline.lock().clone() {
+        // The midpoint only compares over the same entities, which may resolve late
+        Some(v) if v.transforms.len() == current.transforms.len() => v,
+        _ => return Element::new(),
+    };
+    let (from, to) = match (baseline.transforms.last(), current.transforms.last()) {
+        (Some(from), Some(to)) => (from.to_scale_rotation_translation(), to.to_scale_rotation_translation()),
+        _ => return Element::new(),
+    };
+
+    let moved = current.midpoint - baseline.midpoint;
+    let rotated = (to.1 * from.1.inverse()).normalize().to_axis_angle().1.to_degrees();
+    let rotated = if rotated > 180. { 360. - rotated } else { rotated };
+    let scaled = to.0 / from.0;
+
+    FlowColumn::el([
+        Text::el(format!("Moved {:.3} {:.3} {:.3} ({:.3} total)", moved.x, moved.y, moved.z, moved.length())).small_style(),
+        FlowRow::el([
+            Text::el(format!("Rotated {rotated:.2}\u{b0}, scaled \u{d7}{:.3} {:.3} {:.3}", scaled.x, scaled.y, scaled.z)).small_style(),
+            Button::new("\u{f2f9}", reset).tooltip("Reset the totals").style(ButtonStyle::Flat).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+}
+
+/// The kind of an entity when summarizing a selection, taken from the prefab it was spawned from
+fn entity_kind(world: &World, id: EntityId) -> String {
+    if let Ok(url) = world.get_ref(id, prefab_from_url()) {
+        let url = url.trim_end_matches("/prefabs/main.json").trim_end_matches('/');
+        let file = url.rsplit('/').next().unwrap_or(url);
+        return file.split('.').next().unwrap_or(file).to_string();
+    }
+
+    world.get_cloned(id, name()).unwrap_or_else(|_| "Other".to_string())
+}
+
+/// Summarizes the selection by the kind of entities in it, such as "12 selected: 8 crate, 4 lamp".
+/// Clicking a kind narrows the selection to it
+#[element_component]
+fn SelectionBreakdown(hooks: &mut Hooks, selection: Selection, set_selection: Cb<dyn Fn(Selection) + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (kinds, set_kinds) = hooks.use_state(Vec::<(String, Vec<EntityId>)>::new());
+
+    use_interval_deps(hooks, Duration::from_millis(500), true, selection, move |selection| {
+        let state = game_client.game_state.lock();
+        let kinds = selection
+            .iter()
+            .filter(|id| state.world.exists(*id))
+            .into_group_map_by(|&id| entity_kind(&state.world, id))
+            .into_iter()
+            .sorted_by_key(|(kind, ids)| (Reverse(ids.len()), kind.clone()))
+            .collect_vec();
+        set_kinds(kinds);
+    });
+
+    let count: usize = kinds.iter().map(|(_, ids)| ids.len()).sum();
+
+    FlowRow(
+        once(Text::el(format!("{count} selected:")))
+            .chain(kinds.into_iter().map(|(kind, ids)| {
+                let set_selection = set_selection.clone();
+                Button::new(format!("{} {kind}", ids.len()), move |_| set_selection(Selection::new(ids.clone())))
+                    .style(ButtonStyle::Flat)
+                    .tooltip(format!("Select only {kind}"))
+                    .el()
+            }))
+            .collect_vec(),
+    )
+    .el()
+    .set(space_between_items(), STREET)
+}
+
+/// A transform property shared by similar entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SimilarBy {
+    Scale,
+    Yaw,
+    Height,
+}
+
+impl SimilarBy {
+    const ALL: [SimilarBy; 3] = [SimilarBy::Scale, SimilarBy::Yaw, SimilarBy::Height];
+
+    fn label(self) -> &'static str {
+        match self {
+            SimilarBy::Scale => "Same scale",
+            SimilarBy::Yaw => "Same yaw",
+            SimilarBy::Height => "Same height",
+        }
+    }
+
+    /// Compares the property within `tolerance`, which is in the unit of the property: a scale
+    /// factor, radians or meters
+    fn is_similar(self, a: Mat4, b: Mat4, tolerance: f32) -> bool {
+        let (a_scale, a_rot, a_pos) = a.to_scale_rotation_translation();
+        let (b_scale, b_rot, b_pos) = b.to_scale_rotation_translation();
+        match self {
+            SimilarBy::Scale => (a_scale - b_scale).abs().max_element() <= tolerance,
+            SimilarBy::Yaw => {
+                let diff = a_rot.to_euler(EulerRot::ZYX).0 - b_rot.to_euler(EulerRot::ZYX).0;
+                ((diff + PI).rem_euclid(TAU) - PI).abs() <= tolerance
+            }
+            SimilarBy::Height => (a_pos.z - b_pos.z).abs() <= tolerance,
+        }
+    }
+}
+
+/// Selects all entities which share a transform property with the active entity, which is useful for
+/// finding outliers among mostly uniform objects
+#[element_component]
+fn SelectSimilar(hooks: &mut Hooks, active: EntityId, set_selection: Cb<dyn Fn(Selection) + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (tolerance, set_tolerance) = hooks.use_state(0.01);
+
+    let on_select = cb(move |index: usize| {
+        let by = SimilarBy::ALL[index];
+        let state = game_client.game_state.lock();
+        let reference = match get_world_transform(&state.world, active) {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        let similar = query(selectable())
+            .incl(is_remote_entity())
+            .iter(&state.world, None)
+            .map(|(id, _)| id)
+            .filter(|&id| get_world_transform(&state.world, id).map_or(false, |transform| by.is_similar(reference, transform, tolerance)))
+            .collect_vec();
+
+        tracing::info!("Selecting {} entities with {by:?} as {active}", similar.len());
+        set_selection(Selection::new(similar));
+    });
+
+    FlowRow::el([
+        DropdownSelect {
+            content: Text::el("Select similar"),
+            on_select,
+            items: SimilarBy::ALL.iter().map(|by| Text::el(by.label())).collect(),
+            inline: false,
+        }
+        .el(),
+        Slider {
+            value: tolerance,
+            on_change: Some(set_tolerance),
+            min: 0.001,
+            max: 10.,
+            width: 100.,
+            logarithmic: true,
+            round: Some(3),
+            suffix: None,
+        }
+        .el(),
+    ])
+    .set(space_between_items(), STREET)
+}
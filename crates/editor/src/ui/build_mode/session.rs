@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use ambient_core::{asset_cache, runtime};
+use ambient_element::{element_component, Element, Hooks};
+use ambient_network::{client::GameClient, hooks::use_player_id, log_network_result};
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt},
+    Cb,
+};
+use itertools::Itertools;
+use parking_lot::Mutex;
+
+use super::TransformMode;
+use crate::{
+    intents::SelectMode,
+    rpc::{rpc_select, SelectMethod},
+    selection, Selection,
+};
+
+/// What was being edited when the connection was lost, kept in the asset cache of the app so that
+/// it outlives the game client
+#[derive(Debug, Clone, Default)]
+pub(super) struct LastSession {
+    selection: Selection,
+    srt_mode: Option<TransformMode>,
+}
+
+#[derive(Debug)]
+pub(super) struct LastSessionKey;
+impl SyncAssetKey<Arc<Mutex<LastSession>>> for LastSessionKey {
+    fn load(&self, _assets: AssetCache) -> Arc<Mutex<LastSession>> {
+        Default::default()
+    }
+}
+
+/// Saves the selection and transform mode as they change, and re-applies them once the player has
+/// joined again after a reconnect. Entities which were removed while away are dropped, and
+/// `on_notice` is told how many
+#[element_component]
+pub fn SessionKeeper(hooks: &mut Hooks, selection: Selection, on_notice: Cb<dyn Fn(String) + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (srt_mode, set_srt_mode) = hooks.consume_context::<Option<TransformMode>>().unwrap();
+    let session = LastSessionKey.get(hooks.world.resource(asset_cache()));
+    let player_id = use_player_id(hooks);
+    // Nothing is saved before the last session has been restored, as the selection starts out empty
+    let (restored, set_restored) = hooks.use_state(false);
+
+    hooks.use_effect(player_id, {
+        let session = session.clone();
+        move |world, &player_id| {
+            let player_id = match player_id {
+                Some(id) if !restored => id,
+                _ => return Box::new(|_| {}),
+            };
+
+            let last = session.lock().clone();
+            let (found, missing): (Vec<_>, Vec<_>) = {
+                let state = game_client.game_state.lock();
+                // The server keeps the selection of a player which is still joined
+                let current = state.world.get_ref(player_id, selection()).map(|s| !s.is_empty()).unwrap_or(false);
+                if current {
+                    (Vec::new(), Vec::new())
+                } else {
+                    last.selection.iter().partition(|&id| state.world.exists(id))
+                }
+            };
+
+            if !found.is_empty() {
+                let game_client = game_client.clone();
+                let method = SelectMethod::Manual(Selection::new(found));
+                world.resource(runtime()).spawn(async move {
+                    log_network_result!(game_client.rpc(rpc_select, (method, SelectMode::Set)).await);
+                });
+                set_srt_mode(last.srt_mode);
+            }
+            if !missing.is_empty() {
+                on_notice(format!(
+                    "{} of the previously selected entities no longer exist and were not reselected: {}",
+                    missing.len(),
+                    missing.iter().join(", ")
+                ));
+            }
+            set_restored(true);
+            Box::new(|_| {})
+        }
+    });
+
+    hooks.use_effect((selection, srt_mode, restored), move |_, (selection, srt_mode, restored)| {
+        if *restored {
+            *session.lock() = LastSession { selection: selection.clone(), srt_mode: *srt_mode };
+        }
+        Box::new(|_| {})
+    });
+
+    Element::new()
+}
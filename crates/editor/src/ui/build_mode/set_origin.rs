@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use ambient_core::{bounding::calc_world_bounding_recursive, get_mouse_clip_space_position, runtime};
+use ambient_ecs::{EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_physics::intersection::{rpc_pick, RaycastFilter};
+use ambient_std::Cb;
+use ambient_ui::{space_between_items, Button, FlowColumn, FlowRow, Hotkey, StylesExt, Text, STREET};
+use glam::{vec3, Vec3};
+use itertools::Itertools;
+use winit::event::VirtualKeyCode;
+
+use crate::intents::intent_set_origin;
+
+/// Where in its bounds an entity's origin is moved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OriginPreset {
+    Center,
+    /// The center of the bottom face, so that the entity rests on what it is placed on
+    Bottom,
+}
+
+impl OriginPreset {
+    fn point(self, world: &World, id: EntityId) -> Option<Vec3> {
+        let bounds = calc_world_bounding_recursive(world, id)?;
+        Some(match self {
+            OriginPreset::Center => bounds.center(),
+            OriginPreset::Bottom => vec3(bounds.center().x, bounds.center().y, bounds.min.z),
+        })
+    }
+}
+
+/// Moves the origin of each target to the point of its bounds given by `preset`, as one undo step
+fn push_presets(world: &World, game_client: &GameClient, targets: &[EntityId], preset: OriginPreset) {
+    let origins = {
+        let state = game_client.game_state.lock();
+        targets.iter().filter_map(|&id| Some((id, preset.point(&state.world, id)?))).collect_vec()
+    };
+    world.resource(runtime()).spawn(client_push_intent(game_client.clone(), intent_set_origin(), origins, None, None));
+}
+
+/// Moves the origin of an entity relative to its mesh and children, without moving how it looks.
+///
+/// Fixes objects whose origin makes them awkward to place or rotate
+#[element_component]
+pub fn SetOriginPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    FlowColumn::el([
+        Text::el("Set origin").section_style(),
+        Text::el("Moves the origin without moving the mesh or the children").small_style(),
+        FlowRow::el([
+            Button::new("Bounds center", {
+                let game_client = game_client.clone();
+                let targets = targets.clone();
+                move |world| push_presets(world, &game_client, &targets, OriginPreset::Center)
+            })
+            .el(),
+            Button::new("Bottom center", {
+                let game_client = game_client.clone();
+                let targets = targets.clone();
+                move |world| push_presets(world, &game_client, &targets, OriginPreset::Bottom)
+            })
+            .el(),
+        ])
+        .set(space_between_items(), STREET),
+        Hotkey::new(
+            VirtualKeyCode::O,
+            move |world| {
+                let active = match targets.last() {
+                    Some(&active) => active,
+                    None => return,
+                };
+                let mouse_clip_pos = get_mouse_clip_space_position(world);
+                let game_client = game_client.clone();
+                world.resource(runtime()).spawn(async move {
+                    let ray = game_client.game_state.lock().screen_ray(mouse_clip_pos);
+                    let filter = RaycastFilter { entities: None, collider_type: None };
+                    match game_client.rpc(rpc_pick, (ray, filter)).await {
+                        Ok(Some((_, dist))) => {
+                            let origin = ray.origin + ray.dir * dist;
+                            client_push_intent(game_client, intent_set_origin(), vec![(active, origin)], None, None).await
+                        }
+                        Ok(None) => tracing::info!("Nothing under the cursor to move the origin to"),
+                        Err(err) => tracing::warn!("Failed to pick the new origin: {err:?}"),
+                    }
+                });
+            },
+            Text::el("Press O to move the origin of the active entity to the point under the cursor").small_style(),
+        )
+        .el(),
+        Button::new("Close", move |_| on_close()).el(),
+    ])
+    .set(space_between_items(), STREET)
+}
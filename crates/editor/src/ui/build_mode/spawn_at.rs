@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use ambient_core::runtime;
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::client::GameClient;
+use ambient_std::{cb, Cb};
+use ambient_ui::{space_between_items, Button, FlowColumn, FlowRow, StylesExt, Text, STREET};
+use itertools::Itertools;
+
+use super::spawn_guard::GuardedSpawnButton;
+use crate::intents::{intent_spawn_at, IntentSpawnAt};
+
+/// Spawns a copy of the active entity at each of the other selected entities, such as markers
+/// for spawn points or sockets
+#[element_component]
+pub fn SpawnAtPanel(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_close: Cb<dyn Fn() + Sync + Send>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (inherit_transform, set_inherit_transform) = hooks.use_state(true);
+    let (parent_to_anchor, set_parent_to_anchor) = hooks.use_state(false);
+
+    let (template, anchors) = match targets.split_last() {
+        Some((&template, anchors)) if !anchors.is_empty() => (template, anchors.to_vec()),
+        _ => {
+            return FlowColumn::el([
+                Text::el("Spawn at each selected").section_style(),
+                Text::el("Select the anchors, then the entity to copy last"),
+                Button::new("Close", move |_| on_close()).el(),
+            ])
+            .set(space_between_items(), STREET)
+        }
+    };
+
+    FlowColumn::el([
+        Text::el("Spawn at each selected").section_style(),
+        Text::el(format!("Copies the last selected entity to the {} other selected entities", anchors.len())).small_style(),
+        FlowRow::el([
+            Button::new("Inherit rotation and scale", move |_| set_inherit_transform(!inherit_transform)).toggled(inherit_transform).el(),
+            Button::new("Parent to anchors", move |_| set_parent_to_anchor(!parent_to_anchor)).toggled(parent_to_anchor).el(),
+        ])
+        .set(space_between_items(), STREET),
+        FlowRow::el([
+            GuardedSpawnButton::el(
+                "Spawn".to_string(),
+                anchors.len(),
+                cb({
+                    let on_close = on_close.clone();
+                    move |world| {
+                        let new_uids = anchors.iter().map(|_| EntityId::new()).collect_vec();
+                        world.resource(runtime()).spawn(client_push_intent(
+                            game_client.clone(),
+                            intent_spawn_at(),
+                            IntentSpawnAt {
+                                template,
+                                anchors: anchors.clone(),
+                                new_uids,
+                                inherit_transform,
+                                parent_to_anchor,
+                                select: true,
+                            },
+                            None,
+                            None,
+                        ));
+                        on_close();
+                    }
+                }),
+            ),
+            Button::new("Cancel", move |_| on_close()).el(),
+        ])
+        .set(space_between_items(), STREET),
+    ])
+    .set(space_between_items(), STREET)
+}
@@ -0,0 +1,39 @@
+use ambient_ecs::World;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_std::Cb;
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, StylesExt, Text, STREET};
+
+use crate::ui::EditorPrefs;
+
+/// A button for an operation which creates `count` entities.
+///
+/// Above [`EditorPrefs::spawn_warning`] entities the button asks for confirmation first, so that a
+/// mistyped count doesn't bloat the scene
+#[element_component]
+pub fn GuardedSpawnButton(hooks: &mut Hooks, label: String, count: usize, on_spawn: Cb<dyn Fn(&mut World) + Sync + Send>) -> Element {
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let (confirming, set_confirming) = hooks.use_state(false);
+    let needs_confirm = count > prefs.spawn_warning as usize;
+
+    if confirming && needs_confirm {
+        FlowColumn::el([
+            Text::el(format!("This creates {count} entities, which can slow down both the editor and the game")).error_text_style(),
+            FlowRow::el([
+                Button::new(format!("Create {count} anyway"), {
+                    let set_confirming = set_confirming.clone();
+                    move |world| {
+                        set_confirming(false);
+                        on_spawn(world);
+                    }
+                })
+                .style(ButtonStyle::Primary)
+                .el(),
+                Button::new("Back", move |_| set_confirming(false)).el(),
+            ])
+            .set(space_between_items(), STREET),
+        ])
+        .set(space_between_items(), STREET)
+    } else {
+        Button::new(label, move |world| if needs_confirm { set_confirming(true) } else { on_spawn(world) }).style(ButtonStyle::Primary).el()
+    }
+}
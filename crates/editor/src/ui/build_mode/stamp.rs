@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use ambient_core::{mouse_position, runtime, screen_to_clip_space};
+use ambient_ecs::EntityId;
+use ambient_element::{element_component, Element, ElementComponentExt, Group, Hooks};
+use ambient_input::event_mouse_input;
+use ambient_network::client::GameClient;
+use ambient_std::{
+    cb,
+    shapes::{Plane, RayIntersectable},
+};
+use ambient_ui::{HighjackMouse, Text};
+use glam::{Mat4, Vec3};
+use itertools::Itertools;
+use parking_lot::Mutex;
+use winit::event::{ElementState, MouseButton};
+
+use super::{
+    ghost::use_preview_tint,
+    transform::{current_transforms, IntialState},
+    EditorAction,
+};
+use crate::{
+    intents::{intent_stamp, IntentStamp},
+    ui::EditorPrefs,
+};
+
+/// The spacing used when the stamped objects have no bounds
+const FALLBACK_SPACING: f32 = 1.;
+
+/// Computes the transforms of the copies stamped along the drag from `start` to `end`.
+///
+/// Copies are spaced by the length of the selection bounds along the drag direction, so that
+/// neighbouring copies abut. The first copy is centered on `start`.
+fn stamp_transforms(initial: &IntialState, start: Vec3, end: Vec3) -> Vec<Mat4> {
+    let dir = (end - start).normalize_or_zero();
+    let spacing = match initial.bounds {
+        Some(bounds) => (bounds.size() * dir).abs().dot(Vec3::ONE),
+        None => FALLBACK_SPACING,
+    };
+    let spacing = if spacing > f32::EPSILON { spacing } else { FALLBACK_SPACING };
+
+    let count = (start.distance(end) / spacing).floor() as usize + 1;
+    let center = initial.bounds.map(|v| v.center()).unwrap_or(initial.midpoint);
+
+    (0..count)
+        .flat_map(|i| {
+            let offset = Mat4::from_translation(start + dir * spacing * i as f32 - center);
+            initial.transforms.iter().map(move |&transform| offset * transform)
+        })
+        .collect_vec()
+}
+
+/// Stamps copies of the selection along a drag in the viewport. Each run of copies is a single
+/// undo step
+#[element_component]
+pub(super) fn StampController(hooks: &mut Hooks, targets: Arc<[EntityId]>) -> Element {
+    assert_ne!(targets.len(), 0);
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let initial = hooks.use_memo_with(targets.clone(), |_, targets| current_transforms(&game_client.game_state.lock().world, targets));
+
+    let action = hooks.use_memo_with(targets.clone(), |world, _| {
+        Arc::new(Mutex::new(EditorAction::new(world.resource(runtime()).clone(), game_client.clone(), intent_stamp(), &prefs.throttles)))
+    });
+    // The start of the current run and the uids allocated for it so far. Uids are never released
+    // during a run, so the copies keep their identity as the drag grows and shrinks
+    let run = hooks.use_ref_with::<Option<(Vec3, Vec<EntityId>)>>(|_| None);
+    // Where the cursor last hit the drag plane, as clicks carry no position
+    let cursor = hooks.use_ref_with::<Option<Vec3>>(|_| None);
+    let (count, set_count) = hooks.use_state(0);
+    let (copies, set_copies) = hooks.use_state_with(|_| Arc::<[EntityId]>::from([]));
+    use_preview_tint(hooks, copies);
+
+    // Pushes the copies between the start of the run and `point`
+    let stamp = {
+        let action = action.clone();
+        let run = run.clone();
+        let set_count = set_count.clone();
+        let set_copies = set_copies.clone();
+        move |point: Vec3| {
+            let mut run = run.lock();
+            let (start, new_uids) = match &mut *run {
+                Some(v) => v,
+                None => return,
+            };
+
+            let transforms = stamp_transforms(&initial, *start, point);
+            if transforms.len() > new_uids.len() {
+                new_uids.resize_with(transforms.len(), EntityId::new);
+            }
+
+            set_count(transforms.len() / targets.len());
+            set_copies(Arc::from(&new_uids[..transforms.len()]));
+            action.lock().push_intent(IntentStamp { template: targets.to_vec(), new_uids: new_uids.clone(), transforms });
+        }
+    };
+
+    hooks.use_world_event({
+        let run = run.clone();
+        move |_, event| {
+            if let Some(event) = event.get_ref(event_mouse_input()) {
+                if event.button == MouseButton::Left && event.state == ElementState::Released && run.lock().take().is_some() {
+                    action.lock().confirm();
+                    set_count(0);
+                    set_copies(Arc::from([]));
+                }
+            }
+        }
+    });
+
+    // The drag happens on the horizontal plane through the selection
+    let plane = Plane::from_normal_and_point(Vec3::Z, initial.midpoint).expect("Invalid stamp plane");
+    let readout = if count > 0 { format!("Stamping {count} copies") } else { "Drag to stamp copies of the selection".to_string() };
+
+    Group(vec![
+        Text::el(readout),
+        HighjackMouse {
+            on_click: {
+                let stamp = stamp.clone();
+                let cursor = cursor.clone();
+                cb(move |button| {
+                    if button != MouseButton::Left {
+                        return;
+                    }
+                    if let Some(point) = *cursor.lock() {
+                        *run.lock() = Some((point, Vec::new()));
+                        stamp(point);
+                    }
+                })
+            },
+            on_mouse_move: cb(move |world, _, _| {
+                let ray = game_client.game_state.lock().screen_ray(screen_to_clip_space(world, *world.resource(mouse_position())));
+                let point = match plane.ray_intersect(ray) {
+                    Some(dist) => prefs.snap(ray.origin + ray.dir * dist),
+                    None => return,
+                };
+
+                *cursor.lock() = Some(point);
+                stamp(point);
+            }),
+            hide_mouse: false,
+        }
+        .el(),
+    ])
+    .el()
+}
@@ -0,0 +1,133 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use ambient_core::{bounding::calc_world_bounding_recursive, selectable, tags};
+use ambient_ecs::{query, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::client::GameClient;
+use ambient_renderer::outline_recursive;
+use ambient_std::cb;
+use ambient_ui::{use_interval, use_interval_deps, DropdownSelect, Text};
+use glam::{vec3, Vec3};
+use itertools::Itertools;
+use parking_lot::Mutex;
+
+use crate::{organization_color, ui::SelectionHighlight};
+
+/// Limits area selection to the entities with this tag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagFilter(pub Option<String>);
+
+/// Picks the tag which area selection is limited to
+#[element_component]
+pub fn TagFilterSelect(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (TagFilter(filter), set_filter) = hooks.consume_context::<TagFilter>().unwrap();
+    let (all_tags, set_all_tags) = hooks.use_state(Vec::<String>::new());
+
+    use_interval(hooks, 1., move || {
+        let state = game_client.game_state.lock();
+        let all_tags =
+            query((selectable(), tags())).iter(&state.world, None).flat_map(|(_, (_, tags))| tags).sorted().dedup().cloned().collect_vec();
+        set_all_tags(all_tags);
+    });
+
+    let options = [None].into_iter().chain(all_tags.iter().cloned().map(Some)).collect_vec();
+
+    DropdownSelect {
+        content: Text::el(match &filter {
+            Some(tag) => format!("\u{f02b} {tag}"),
+            None => "\u{f02b} Any tag".to_string(),
+        }),
+        items: options.iter().map(|tag| Text::el(tag.clone().unwrap_or_else(|| "Any tag".to_string()))).collect(),
+        on_select: cb(move |index| set_filter(TagFilter(options[index].clone()))),
+        inline: false,
+    }
+    .el()
+}
+
+/// The outline of selected entities without an organization color
+const SELECTION_COLOR: Vec3 = vec3(1., 1., 1.);
+/// The brightness of the dimmest outline, so that every selected entity stays visible
+const MIN_INTENSITY: f32 = 0.25;
+/// The number of overlapping selected entities at which the outline is brightest
+const MAX_OVERLAP_DEPTH: usize = 4;
+
+/// The brightness of the outline of each of `targets`, from [`MIN_INTENSITY`] to 1
+fn outline_intensities(world: &World, targets: &[EntityId], highlight: SelectionHighlight) -> Vec<f32> {
+    let scaled = |v: f32| MIN_INTENSITY + (1. - MIN_INTENSITY) * v.clamp(0., 1.);
+    match highlight {
+        SelectionHighlight::Uniform => vec![1.; targets.len()],
+        SelectionHighlight::Recency => (0..targets.len()).map(|i| scaled((i + 1) as f32 / targets.len() as f32)).collect_vec(),
+        SelectionHighlight::OverlapDepth => {
+            let bounds = targets.iter().map(|&id| calc_world_bounding_recursive(world, id)).collect_vec();
+            bounds
+                .iter()
+                .enumerate()
+                .map(|(i, a)| {
+                    let depth = match a {
+                        Some(a) => {
+                            bounds.iter().enumerate().filter(|(j, b)| i != *j && b.as_ref().map_or(false, |b| a.intersect_aabb(b))).count()
+                        }
+                        None => 0,
+                    };
+                    scaled(depth.min(MAX_OVERLAP_DEPTH) as f32 / MAX_OVERLAP_DEPTH as f32)
+                })
+                .collect_vec()
+        }
+    }
+}
+
+/// Outlines the selected entities which have an organization color in that color.
+///
+/// Unless `highlight` is [`SelectionHighlight::Uniform`], every selected entity is outlined, with a
+/// brightness telling the selected entities in a dense cluster apart
+#[element_component]
+pub fn OrganizationOutlines(hooks: &mut Hooks, targets: Arc<[EntityId]>, highlight: SelectionHighlight) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    // The outlined entities, so that the outlines can be removed when they are deselected
+    let (outlined, _) = hooks.use_state_with(|_| Arc::new(Mutex::new(HashMap::<EntityId, Vec3>::new())));
+
+    use_interval_deps(hooks, Duration::from_millis(200), true, (targets, highlight), {
+        let game_client = game_client.clone();
+        let outlined = outlined.clone();
+        move |(targets, highlight)| {
+            let mut state = game_client.game_state.lock();
+            let world = &mut state.world;
+            let mut outlined = outlined.lock();
+
+            let intensities = outline_intensities(world, targets, *highlight);
+            let colors = targets
+                .iter()
+                .zip(intensities)
+                .filter_map(|(&id, intensity)| {
+                    let color = match world.get(id, organization_color()) {
+                        Ok(color) => color,
+                        Err(_) if *highlight != SelectionHighlight::Uniform => SELECTION_COLOR,
+                        Err(_) => return None,
+                    };
+                    Some((id, color * intensity))
+                })
+                .collect::<HashMap<_, _>>();
+
+            for (id, _) in outlined.iter().filter(|(id, _)| !colors.contains_key(id)) {
+                world.remove_component(*id, outline_recursive()).ok();
+            }
+            for (&id, &color) in colors.iter().filter(|(id, color)| outlined.get(id) != Some(color)) {
+                world.add_component(id, outline_recursive(), color.extend(1.)).ok();
+            }
+
+            *outlined = colors;
+        }
+    });
+
+    hooks.use_spawn(move |_| {
+        Box::new(move |_| {
+            let mut state = game_client.game_state.lock();
+            for (id, _) in outlined.lock().drain() {
+                state.world.remove_component(id, outline_recursive()).ok();
+            }
+        })
+    });
+
+    Element::new()
+}
@@ -0,0 +1,51 @@
+use ambient_core::{bounding::calc_world_bounding_recursive, camera::get_active_camera, main_scene, transform::translation};
+use ambient_ecs::EntityId;
+use ambient_network::client::{ClientGameState, GameClient};
+use ambient_physics::intersection::{rpc_pick, RaycastFilter};
+use ambient_std::shapes::AABB;
+use glam::{Vec2, Vec3};
+use itertools::Itertools;
+
+/// How far in front of the camera the teleport target ends up
+pub(super) const TELEPORT_DISTANCE: f32 = 10.;
+
+/// Moves the active camera so that it looks at `target` from [`TELEPORT_DISTANCE`] away, keeping its
+/// orientation.
+///
+/// The editor has no player body, so only the viewpoint is moved.
+pub(super) fn teleport_camera(state: &mut ClientGameState, target: Vec3) {
+    let dir = state.center_screen_ray().dir;
+    let camera = match get_active_camera(&state.world, main_scene()) {
+        Some(v) => v,
+        None => {
+            tracing::warn!("No active camera to teleport");
+            return;
+        }
+    };
+
+    let position = target - dir * TELEPORT_DISTANCE;
+    tracing::info!("Teleporting camera to {position}");
+    if let Err(err) = state.world.set(camera, translation(), position) {
+        tracing::warn!("Failed to teleport camera: {err:?}");
+    }
+}
+
+/// Teleports the camera to the point under the cursor, if the cursor is over anything
+pub(super) async fn teleport_to_cursor(game_client: GameClient, mouse_clip_pos: Vec2) {
+    let ray = game_client.game_state.lock().screen_ray(mouse_clip_pos);
+    let filter = RaycastFilter { entities: None, collider_type: None };
+
+    match game_client.rpc(rpc_pick, (ray, filter)).await {
+        Ok(Some((_, dist))) => teleport_camera(&mut game_client.game_state.lock(), ray.origin + ray.dir * dist),
+        Ok(None) => tracing::info!("Nothing under the cursor to teleport to"),
+        Err(err) => tracing::warn!("Failed to pick teleport target: {err:?}"),
+    }
+}
+
+/// Teleports the camera to the center of the bounds of the given entities
+pub(super) fn teleport_to_entities(state: &mut ClientGameState, targets: &[EntityId]) {
+    let bounds = targets.iter().filter_map(|&id| calc_world_bounding_recursive(&state.world, id)).collect_vec();
+    if let Some(bounds) = AABB::unions(&bounds) {
+        teleport_camera(state, bounds.center());
+    }
+}
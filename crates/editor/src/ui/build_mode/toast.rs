@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use ambient_core::runtime;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_std::Cb;
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowRow, StylesExt, Text, STREET};
+use tokio::time::sleep;
+
+/// How long an error toast stays up unless dismissed
+const TOAST_DURATION: Duration = Duration::from_secs(6);
+
+/// A short lived error message.
+///
+/// Key the element on the message to restart the timeout when the message changes.
+#[element_component]
+pub fn ErrorToast(hooks: &mut Hooks, message: String, on_dismiss: Cb<dyn Fn() + Sync + Send>) -> Element {
+    hooks.use_spawn({
+        let on_dismiss = on_dismiss.clone();
+        move |world| {
+            let task = world.resource(runtime()).spawn(async move {
+                sleep(TOAST_DURATION).await;
+                on_dismiss();
+            });
+            Box::new(move |_| task.abort())
+        }
+    });
+
+    FlowRow::el([Text::el(message).error_text_style(), Button::new("\u{f00d}", move |_| on_dismiss()).style(ButtonStyle::Flat).el()])
+        .set(space_between_items(), STREET)
+}
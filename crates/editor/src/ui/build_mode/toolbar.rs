@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_std::cb;
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowRow, Hotkey, STREET};
+
+/// The categories the top toolbar is divided into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToolbarGroupKind {
+    Create,
+    Transform,
+    Arrange,
+    View,
+}
+
+impl ToolbarGroupKind {
+    fn title(self) -> &'static str {
+        match self {
+            ToolbarGroupKind::Create => "Create",
+            ToolbarGroupKind::Transform => "Transform",
+            ToolbarGroupKind::Arrange => "Arrange",
+            ToolbarGroupKind::View => "View",
+        }
+    }
+
+    fn expanded_by_default(self) -> bool {
+        matches!(self, ToolbarGroupKind::Transform)
+    }
+}
+
+/// Which toolbar groups the user has expanded or collapsed.
+///
+/// Provided as a context by the build mode, so that the state is kept when a group is re-created,
+/// such as when the selection changes.
+#[derive(Debug, Clone, Default)]
+pub struct ToolbarGroupsState(HashMap<ToolbarGroupKind, bool>);
+
+impl ToolbarGroupsState {
+    fn is_expanded(&self, kind: ToolbarGroupKind) -> bool {
+        self.0.get(&kind).copied().unwrap_or_else(|| kind.expanded_by_default())
+    }
+}
+
+/// A collapsible group of toolbar buttons.
+///
+/// The hotkeys of the buttons stay active while the group is collapsed.
+#[element_component]
+pub fn ToolbarGroup(hooks: &mut Hooks, kind: ToolbarGroupKind, buttons: Vec<Button>) -> Element {
+    let (state, set_state) = hooks.consume_context::<ToolbarGroupsState>().unwrap();
+
+    if buttons.is_empty() {
+        return Element::new();
+    }
+
+    let expanded = state.is_expanded(kind);
+    let toggle = Button::new(format!("{} {}", kind.title(), if expanded { "\u{f053}" } else { "\u{f054}" }), move |_| {
+        let mut state = state.clone();
+        state.0.insert(kind, !expanded);
+        set_state(state);
+    })
+    .style(ButtonStyle::Flat)
+    .tooltip(if expanded { "Collapse" } else { "Expand" })
+    .el();
+
+    let items = if expanded {
+        buttons.into_iter().map(|button| button.el()).collect()
+    } else {
+        buttons
+            .into_iter()
+            .filter(|button| !button.disabled)
+            .filter_map(|button| {
+                let on_invoked = button.on_invoked;
+                let hotkey = Hotkey::new(button.hotkey?, move |world| on_invoked.invoke(world, cb(|_| {})), Element::new());
+                Some(hotkey.hotkey_modifier(button.hotkey_modifier).el())
+            })
+            .collect()
+    };
+
+    FlowRow(std::iter::once(toggle).chain(items).collect()).el().set(space_between_items(), STREET)
+}
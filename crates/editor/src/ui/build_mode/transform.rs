@@ -1,30 +1,48 @@
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 
-use ambient_core::{mouse_position, runtime, screen_to_clip_space, transform::get_world_transform};
-use ambient_ecs::{EntityId, World};
+use std::time::Duration;
+
+use ambient_core::{
+    bounding::{calc_world_bounding_recursive, local_bounding_aabb},
+    mouse_position, runtime, screen_to_clip_space, selectable,
+    transform::get_world_transform,
+};
+use ambient_ecs::{query, EntityId, World};
 use ambient_element::{element_component, Element, ElementComponent, ElementComponentExt, Group, Hooks};
-use ambient_network::client::GameClient;
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_input::{event_keyboard_input, KeyboardEvent};
+use ambient_network::{
+    client::{ClientGameState, GameClient},
+    is_remote_entity,
+};
+use ambient_prefab::{prefab_from_url, spawned};
 use ambient_std::{
-    cb,
-    shapes::{Plane, Ray, RayIntersectable},
+    cb, line_hash,
+    shapes::{Plane, Ray, RayIntersectable, AABB},
     Cb,
 };
-use ambient_ui::{space_between_items, Button, FlowRow, HighjackMouse, Hotkey, Separator, STREET};
+use ambient_ui::{space_between_items, use_interval_deps, Button, FlowRow, HighjackMouse, Hotkey, Separator, Text, STREET};
 use anyhow::Context;
 use glam::{vec3, Mat4, Quat, Vec2, Vec3, Vec3Swizzles};
 use itertools::Itertools;
 use parking_lot::Mutex;
-use winit::event::{ModifiersState, MouseButton, VirtualKeyCode};
+use winit::event::{ElementState, ModifiersState, MouseButton, VirtualKeyCode};
 
 use crate::{
-    intents::{intent_place_ray, intent_set_transform, intent_translate, IntentPlaceRay, IntentTransform, IntentTranslate, TerrainOffset},
+    intents::{
+        dominant_axis, intent_place_ray, intent_set_transform, intent_translate, parent_frame, round_transform, snap_to_points,
+        IntentPlaceRay, IntentTransform, IntentTranslate, TerrainOffset,
+    },
     rpc::AxisFlags,
+    transform_pinned,
     ui::{
-        build_mode::{AxisGuide, EditorAction, GridGuide},
-        EditorPrefs,
+        build_mode::{
+            construction::guide_intersections, ghost::use_preview_tint, use_gizmo_scope, AxisGuide, DeferredPreview, EditorAction,
+            GridGuide, Pivot,
+        },
+        ConstructionGuides, CoordinateSpace, EditorPrefs,
     },
 };
-const TRANSFORM_THROTTLE: Duration = Duration::from_millis(60);
 
 fn get_world_transforms(world: &World, targets: &[EntityId]) -> anyhow::Result<Vec<Mat4>> {
     targets
@@ -36,12 +54,42 @@ fn get_world_transforms(world: &World, targets: &[EntityId]) -> anyhow::Result<V
         .collect()
 }
 
+/// The targets spawned from a prefab which the server hasn't finished loading, which have none of
+/// the components of the prefab yet
+pub(super) fn loading_entities(world: &World, targets: &[EntityId]) -> Vec<EntityId> {
+    targets.iter().copied().filter(|&id| world.has_component(id, prefab_from_url()) && !world.has_component(id, spawned())).collect_vec()
+}
+
 fn to_isometry(transform: Mat4) -> Mat4 {
     let (_, rot, pos) = transform.to_scale_rotation_translation();
 
     Mat4::from_scale_rotation_translation(Vec3::ONE, rot, pos)
 }
 
+/// The local axes and origin of `transform` without its scale.
+///
+/// Unlike [`to_isometry`], an axis with a negative scale keeps pointing the way it is drawn, so
+/// handles of a mirrored entity match what is seen. The result is a reflection in that case, and
+/// should not be turned into a rotation
+pub(super) fn local_frame(transform: Mat4) -> Mat4 {
+    Mat4::from_cols(
+        transform.x_axis.truncate().normalize_or_zero().extend(0.),
+        transform.y_axis.truncate().normalize_or_zero().extend(0.),
+        transform.z_axis.truncate().normalize_or_zero().extend(0.),
+        transform.w_axis,
+    )
+}
+
+/// The frame whose axes the controls follow in `space`, for the active entity `id` with the world
+/// transform `transform`. Parent space falls back to world space for entities without a parent
+pub(super) fn axes_frame(world: &World, space: CoordinateSpace, id: EntityId, transform: Mat4) -> Mat4 {
+    match space {
+        CoordinateSpace::World => Mat4::IDENTITY,
+        CoordinateSpace::Local => local_frame(transform),
+        CoordinateSpace::Parent => parent_frame(world, id).map_or(Mat4::IDENTITY, local_frame),
+    }
+}
+
 #[derive(PartialEq, Copy, Debug, Clone)]
 enum ConstraintSpace {
     Plane { normal: Vec3, point: Vec3 },
@@ -79,77 +127,371 @@ impl ConstraintSpace {
     }
 }
 
+/// A face of the bounds of the reference entity for coplanar snapping
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReferenceFace {
+    Top,
+    Bottom,
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+}
+
+impl ReferenceFace {
+    const ALL: [ReferenceFace; 6] =
+        [ReferenceFace::Top, ReferenceFace::Bottom, ReferenceFace::PosX, ReferenceFace::NegX, ReferenceFace::PosY, ReferenceFace::NegY];
+
+    fn label(self) -> &'static str {
+        match self {
+            ReferenceFace::Top => "top",
+            ReferenceFace::Bottom => "bottom",
+            ReferenceFace::PosX => "+X",
+            ReferenceFace::NegX => "-X",
+            ReferenceFace::PosY => "+Y",
+            ReferenceFace::NegY => "-Y",
+        }
+    }
+
+    /// The outward normal of the face in the local space of the entity
+    fn normal(self) -> Vec3 {
+        match self {
+            ReferenceFace::Top => Vec3::Z,
+            ReferenceFace::Bottom => -Vec3::Z,
+            ReferenceFace::PosX => Vec3::X,
+            ReferenceFace::NegX => -Vec3::X,
+            ReferenceFace::PosY => Vec3::Y,
+            ReferenceFace::NegY => -Vec3::Y,
+        }
+    }
+
+    /// The next face when cycling through them, ending with coplanar snapping turned off
+    fn next(face: Option<Self>) -> Option<Self> {
+        match face {
+            None => Some(Self::ALL[0]),
+            Some(face) => Self::ALL.iter().skip_while(|&&v| v != face).nth(1).copied(),
+        }
+    }
+}
+
+/// The world space plane through `face` of the bounds of `id`.
+///
+/// Entities without bounds of their own use their world bounds, or their origin if they have no
+/// bounds at all
+fn reference_plane(world: &World, id: EntityId, face: ReferenceFace) -> Option<(Vec3, Vec3)> {
+    let (transform, aabb) = match (get_world_transform(world, id), world.get(id, local_bounding_aabb())) {
+        (Ok(transform), Ok(aabb)) => (transform, aabb),
+        (transform, _) => match calc_world_bounding_recursive(world, id) {
+            Some(aabb) => (Mat4::IDENTITY, aabb),
+            None => {
+                let origin = transform.ok()?.transform_point3(Vec3::ZERO);
+                (Mat4::IDENTITY, AABB { min: origin, max: origin })
+            }
+        },
+    };
+
+    let normal = face.normal();
+    let center = (aabb.min + aabb.max) / 2.;
+    let point = transform.transform_point3(center + normal * (aabb.max - aabb.min) / 2.);
+    // Normals follow the inverse transpose, which keeps them perpendicular under non-uniform scale
+    let normal = transform.inverse().transpose().transform_vector3(normal).try_normalize()?;
+    Some((point, normal))
+}
+
+/// How far `bounds` extends from `midpoint` in the direction of `normal`
+fn extent_along(bounds: AABB, midpoint: Vec3, normal: Vec3) -> f32 {
+    let furthest = Vec3::select(normal.cmpge(Vec3::ZERO), bounds.max, bounds.min);
+    (furthest - midpoint).dot(normal)
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct IntialState {
-    transforms: Vec<Mat4>,
-    midpoint: Vec3,
+    pub transforms: Vec<Mat4>,
+    pub midpoint: Vec3,
+    /// The combined world space bounds of the targets
+    pub bounds: Option<AABB>,
+}
+
+pub(super) fn current_transforms(world: &World, targets: &[EntityId]) -> IntialState {
+    let transforms = match get_world_transforms(world, targets) {
+        Ok(v) => v,
+        Err(err) => {
+            log::error!("{err:?}");
+            return Default::default();
+        }
+    };
+
+    let midpoint: Vec3 =
+        transforms.iter().map(|v| v.transform_point3(Vec3::ZERO)).fold(Vec3::ZERO, |acc, x| acc + x) / (targets.len().max(1)) as f32;
+
+    let bounds = AABB::unions(&targets.iter().filter_map(|&id| calc_world_bounding_recursive(world, id)).collect_vec());
+
+    IntialState { transforms, midpoint, bounds }
 }
 
 fn initial_transforms(hooks: &mut Hooks, game_client: &GameClient, targets: Arc<[EntityId]>) -> IntialState {
     hooks.use_memo_with(targets, |_, targets| {
         let state = game_client.game_state.lock();
-        let transforms = match get_world_transforms(&state.world, targets) {
-            Ok(v) => v,
-            Err(err) => {
-                log::error!("{err:?}");
-                return Default::default();
-            }
-        };
+        current_transforms(&state.world, targets)
+    })
+}
 
-        let midpoint: Vec3 =
-            transforms.iter().map(|v| v.transform_point3(Vec3::ZERO)).fold(Vec3::ZERO, |acc, x| acc + x) / (targets.len().max(1)) as f32;
+/// Previews a deferred translation by moving the unpinned targets from `initial` like the server
+/// does, keeping their positions relative to their midpoint
+fn translate_preview(initial: Vec<Mat4>) -> DeferredPreview<IntentTranslate> {
+    Arc::new(move |world, intent| {
+        let moved = intent
+            .targets
+            .iter()
+            .copied()
+            .zip(initial.iter().copied())
+            .filter(|&(id, _)| !world.has_component(id, transform_pinned()))
+            .collect_vec();
+        let midpoint = moved.iter().map(|(_, v)| v.transform_point3(Vec3::ZERO)).sum::<Vec3>() / moved.len().max(1) as f32;
+        let offset = Mat4::from_translation(intent.position - midpoint);
+        moved.into_iter().map(|(id, transform)| (id, offset * transform)).collect_vec()
+    })
+}
 
-        IntialState { transforms, midpoint }
+/// Previews a deferred rotation or scale with the transforms of the intent
+fn set_transform_preview() -> DeferredPreview<IntentTransform> {
+    Arc::new(|world, intent| {
+        intent
+            .entities
+            .iter()
+            .copied()
+            .zip(intent.transforms.iter().copied())
+            .filter(|&(id, _)| !world.has_component(id, transform_pinned()))
+            .collect_vec()
     })
 }
 
+/// The world transforms of copies of `targets` one grid cell of `size` over, along the world axis
+/// closest to the right of the camera, so that a copy lands next to its original on screen
+pub(super) fn next_cell_transforms(state: &ClientGameState, targets: &[EntityId], size: f32) -> Option<Vec<Mat4>> {
+    let right = state.view()?.inverse().x_axis.truncate();
+    let right = if right.truncate().length_squared() > 0. { right.truncate().extend(0.) } else { Vec3::X };
+    let offset = Mat4::from_translation(dominant_axis(right) * size);
+    let transforms = get_world_transforms(&state.world, targets).ok()?;
+    Some(transforms.into_iter().map(|transform| offset * transform).collect_vec())
+}
+
+/// Scales the transforms in world space around `pivot`
+pub(super) fn scale_around(transforms: &[Mat4], pivot: Vec3, scale: Vec3) -> Vec<Mat4> {
+    let to_local = Mat4::from_translation(-pivot);
+    let to_scaled_world = Mat4::from_translation(pivot) * Mat4::from_scale(scale);
+
+    transforms.iter().map(|&transform| to_scaled_world * (to_local * transform)).collect_vec()
+}
+
+/// The axes the translate, scale and rotate controllers are limited to. It's shared through context
+/// by [`TransformControls`](super::TransformControls), which clears it whenever the transform mode
+/// changes so that a constraint doesn't carry over into the next action
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisConstraint(pub AxisFlags);
+
+impl Default for AxisConstraint {
+    fn default() -> Self {
+        Self(AxisFlags::all())
+    }
+}
+
+/// The shared [`AxisConstraint`], with a setter for the [`AxisButtons`]
+fn use_axis_constraint(hooks: &mut Hooks) -> (AxisFlags, Cb<dyn Fn(AxisFlags) + Sync + Send>) {
+    let (AxisConstraint(axis), set_constraint) = hooks.consume_context::<AxisConstraint>().unwrap();
+    (axis, cb(move |axis| set_constraint(AxisConstraint(axis))))
+}
+
+const CONSTRAINT_AXIS_COLORS: [(AxisFlags, Vec3, Vec3); 3] =
+    [(AxisFlags::X, Vec3::X, vec3(1., 0.2, 0.2)), (AxisFlags::Y, Vec3::Y, vec3(0.2, 1., 0.2)), (AxisFlags::Z, Vec3::Z, vec3(0.2, 0.2, 1.))];
+/// How much the axes left out of the constraint are darkened
+const CONSTRAINT_DIM: f32 = 0.25;
+const CONSTRAINT_AXIS_THICKNESS: f32 = 0.03;
+
+fn constraint_axes_scope() -> u64 {
+    line_hash!()
+}
+
+/// Draws the axes of `frame` out from `origin`, with the axes left out of `axis` dimmed, so that it
+/// is clear which way a constrained drag goes
+#[element_component]
+fn ConstraintAxes(hooks: &mut Hooks, frame: Mat4, origin: Vec3, length: f32, axis: AxisFlags) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+
+    use_gizmo_scope(hooks, constraint_axes_scope());
+    hooks.use_effect((frame, origin, length, axis), {
+        let game_state = game_client.game_state.clone();
+        move |_, &(frame, origin, length, axis)| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(constraint_axes_scope());
+            for (flag, dir, color) in CONSTRAINT_AXIS_COLORS {
+                let color = if axis.contains(flag) { color } else { color * CONSTRAINT_DIM };
+                let end = origin + frame.transform_vector3(dir).normalize_or_zero() * length;
+                scope.draw(GizmoPrimitive::line(origin, end, CONSTRAINT_AXIS_THICKNESS).with_color(color));
+            }
+
+            Box::new(|_| {})
+        }
+    });
+
+    Element::new()
+}
+
+/// How far the constraint axes reach, so that they stick out of the selection with the `bounds`
+fn constraint_axes_length(bounds: Option<AABB>) -> f32 {
+    bounds.map_or(0., |bounds| bounds.size().max_element() / 2.) + 1.
+}
+
+/// How close the placed entities must be to an object origin to count as snapped to it
+const SNAP_ENGAGED_DISTANCE: f32 = 1e-3;
+const SNAP_INDICATOR_COLOR: Vec3 = vec3(1., 0.8, 0.1);
+
+fn snap_indicator_scope() -> u64 {
+    line_hash!()
+}
+
+/// The origin of another object which the midpoint of `targets` has been snapped onto, if any
+fn snapped_origin(world: &World, targets: &[EntityId]) -> Option<Vec3> {
+    let midpoint = current_transforms(world, targets).midpoint;
+    query(selectable())
+        .incl(is_remote_entity())
+        .iter(world, None)
+        .map(|(id, _)| id)
+        .filter(|id| !targets.contains(id))
+        .filter_map(|id| Some(get_world_transform(world, id).ok()?.transform_point3(Vec3::ZERO)))
+        .find(|origin| origin.distance(midpoint) < SNAP_ENGAGED_DISTANCE)
+}
+
+/// Whether either Alt key is held, which changes how the controllers snap
+pub(super) fn use_alt_held(hooks: &mut Hooks) -> bool {
+    let (held, set_held) = hooks.use_state(false);
+    hooks.use_world_event(move |_, event| {
+        if let Some(KeyboardEvent { keycode: Some(VirtualKeyCode::LAlt | VirtualKeyCode::RAlt), state, .. }) =
+            event.get_ref(event_keyboard_input())
+        {
+            set_held(*state == ElementState::Pressed);
+        }
+    });
+    held
+}
+
+/// Tracks whether the cursor has moved at least `threshold` pixels since the controller was
+/// mounted, given the movement `pos` reported by [`HighjackMouse`]. Until then nothing is pushed, so
+/// that a click with a slight tremble exits the mode without adding a near-zero change to the undo
+/// history. Once past the threshold it stays a drag
+fn use_drag_threshold(hooks: &mut Hooks, threshold: f32) -> impl Fn(Vec2) -> bool + Sync + Send + 'static {
+    let dragging = hooks.use_ref_with(|_| false);
+    move |pos| {
+        let mut dragging = dragging.lock();
+        *dragging |= pos.length() >= threshold;
+        *dragging
+    }
+}
+
 #[element_component]
 pub(super) fn PlaceController(hooks: &mut Hooks, targets: Arc<[EntityId]>, on_click: Cb<dyn Fn(MouseButton) + Sync + Send>) -> Element {
     assert_ne!(targets.len(), 0);
     let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
-    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let (prefs, set_prefs) = hooks.consume_context::<EditorPrefs>().unwrap();
+    let (guides, _) = hooks.consume_context::<ConstructionGuides>().unwrap();
+    let snap_points = guide_intersections(&guides.0);
+    let selection_size = hooks.use_memo_with(targets.clone(), |_, targets| {
+        current_transforms(&game_client.game_state.lock().world, targets).bounds.map(|bounds| bounds.size().max_element())
+    });
+    let snap = prefs.sized_to(selection_size).snap;
+    // Holding alt snaps to the origins of other objects rather than the grid
+    let snap_to_origins = use_alt_held(hooks);
+    use_preview_tint(hooks, targets.clone());
+
+    // While snapping to origins, mark the origin the entities locked onto and where the cursor is
+    let cursor_ray = hooks.use_ref_with(|_| None as Option<Ray>);
+    use_interval_deps(hooks, Duration::from_millis(50), true, snap_to_origins, {
+        let game_state = game_client.game_state.clone();
+        let targets = targets.clone();
+        let cursor_ray = cursor_ray.clone();
+        move |&snap_to_origins| {
+            let state = game_state.lock();
+            // Getting the scope clears it, which hides the indicator once snapping disengages
+            let mut scope = state.world.resource(gizmos()).scope(snap_indicator_scope());
+            let origin = match snap_to_origins.then(|| snapped_origin(&state.world, &targets)).flatten() {
+                Some(v) => v,
+                None => return,
+            };
+
+            scope.draw(GizmoPrimitive::torus(origin, 0.3, 0.05).with_color(SNAP_INDICATOR_COLOR));
+            if let Some(ray) = *cursor_ray.lock() {
+                let cursor = ray.origin + ray.dir * (origin - ray.origin).dot(ray.dir);
+                scope.draw(GizmoPrimitive::line(cursor, origin, 0.02).with_color(SNAP_INDICATOR_COLOR));
+                scope.draw(GizmoPrimitive::sphere(cursor, 0.08).with_color(SNAP_INDICATOR_COLOR));
+            }
+        }
+    });
+    use_gizmo_scope(hooks, snap_indicator_scope());
 
     // Use a memo, that way the intent is reverted when the axis changes
-    let action = hooks.use_memo_with(prefs, |world, _| {
+    let action = hooks.use_memo_with((prefs, snap), |world, _| {
         Arc::new(Mutex::new(EditorAction::new(
             world.resource(runtime()).clone(),
             game_client.clone(),
             intent_place_ray(),
-            TRANSFORM_THROTTLE,
+            &prefs.throttles,
         )))
     });
 
+    let readout = match (snap_to_origins, snap) {
+        (true, _) => "Snapping to object origins".to_string(),
+        (false, Some(size)) => format!("Snapping to a {size} m grid, hold Alt for object origins"),
+        (false, None) => "Not snapping, hold Alt for object origins".to_string(),
+    };
+
     let action = Arc::downgrade(&action);
-    Group(vec![HighjackMouse {
-        on_click: {
-            let action = action.clone();
-            cb(move |button| {
-                if button != MouseButton::Left {
-                    return;
-                }
+    Group(vec![
+        Text::el(readout),
+        Button::new("\u{f2f1}", move |_| set_prefs(EditorPrefs { auto_orient: !prefs.auto_orient, ..prefs }))
+            .tooltip("Auto-orient to surface")
+            .toggled(prefs.auto_orient)
+            .el(),
+        HighjackMouse {
+            on_click: {
+                let action = action.clone();
+                cb(move |button| {
+                    if button != MouseButton::Left {
+                        return;
+                    }
+                    if let Some(action) = action.upgrade() {
+                        action.lock().confirm_with(|v| Some(IntentPlaceRay { round_to: Some(prefs.rounding.step(snap)?), ..v }));
+                    }
+                    on_click(button)
+                })
+            },
+            on_mouse_move: cb(move |world, _, _| {
+                let state = game_client.game_state.lock();
+                let mouse_clip_pos = screen_to_clip_space(world, *world.resource(mouse_position()));
+
+                let targets = targets.clone();
+
+                let ray = state.screen_ray(mouse_clip_pos);
+                *cursor_ray.lock() = Some(ray);
+
+                let intent = IntentPlaceRay {
+                    targets: targets.to_vec(),
+                    ray,
+                    snap,
+                    snap_to_origins,
+                    round_to: None,
+                    snap_in_parent: prefs.snap_in_parent,
+                    auto_orient: prefs.auto_orient,
+                    snap_points: snap_points.clone(),
+                };
+
                 if let Some(action) = action.upgrade() {
-                    action.lock().confirm();
+                    action.lock().push_intent(intent);
                 }
-                on_click(button)
-            })
-        },
-        on_mouse_move: cb(move |world, _, _| {
-            let state = game_client.game_state.lock();
-            let mouse_clip_pos = screen_to_clip_space(world, *world.resource(mouse_position()));
-
-            let targets = targets.clone();
-
-            let ray = state.screen_ray(mouse_clip_pos);
-
-            let intent = IntentPlaceRay { targets: targets.to_vec(), ray, snap: prefs.snap };
-
-            if let Some(action) = action.upgrade() {
-                action.lock().push_intent(intent);
-            }
-        }),
-        hide_mouse: false,
-    }
-    .el()])
+            }),
+            hide_mouse: false,
+        }
+        .el(),
+    ])
     .el()
 }
 
@@ -166,29 +508,56 @@ impl ElementComponent for TranslationController {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
         let Self { targets, on_click } = *self;
 
-        let (axis, set_axis) = hooks.use_state(AxisFlags::all());
+        let (axis, set_axis) = use_axis_constraint(hooks);
+        let (coplanar, set_coplanar) = hooks.use_state(None as Option<ReferenceFace>);
+        // Holding alt snaps to half the grid, such as to center between the cells
+        let half_snap = use_alt_held(hooks);
+
+        // The active entity is the reference for coplanar snapping, so it stays in place while the
+        // rest of the selection moves
+        let (targets, reference) = match (coplanar, targets.split_last()) {
+            (Some(face), Some((&active, rest))) if !rest.is_empty() => (Arc::from(rest), Some((active, face))),
+            _ => (targets, None),
+        };
 
         assert_ne!(targets.len(), 0);
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
         let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+        let (guides, _) = hooks.consume_context::<ConstructionGuides>().unwrap();
+        let past_threshold = use_drag_threshold(hooks, prefs.click_threshold);
 
         // Freeze to_relative to the position when moving was started
         let initial_state = initial_transforms(hooks, &game_client, targets.clone());
+        let prefs = prefs.sized_to(initial_state.bounds.map(|bounds| bounds.size().max_element()));
 
         let game_state = game_client.game_state.lock();
 
-        let to_target_local = to_isometry(initial_state.transforms.last().unwrap().inverse());
+        let active = *targets.last().unwrap();
+        let to_axes = axes_frame(&game_state.world, prefs.coordinates, active, *initial_state.transforms.last().unwrap()).inverse();
+        // Whether the axes are the world axes, as parent space is world space without a parent
+        let world_axes = match prefs.coordinates {
+            CoordinateSpace::World => true,
+            CoordinateSpace::Local => false,
+            CoordinateSpace::Parent => parent_frame(&game_state.world, active).is_none(),
+        };
         let to_view_local = to_isometry(game_state.view().unwrap());
+        let parent_frame = if prefs.snap_in_parent { parent_frame(&game_state.world, active) } else { None };
+        let reference_plane = reference.and_then(|(id, face)| reference_plane(&game_state.world, id, face));
+        // How far the matching face of the moved selection is from its midpoint
+        let coplanar_offset = reference_plane.map(|(_, normal)| match initial_state.bounds {
+            Some(bounds) => extent_along(bounds, initial_state.midpoint, normal),
+            None => 0.,
+        });
 
-        // Use a memo, that way the intent is reverted when the axis changes
-        let action = hooks.use_memo_with((axis, prefs), |world, _| {
-            Arc::new(Mutex::new(EditorAction::new(
-                world.resource(runtime()).clone(),
-                game_client.clone(),
-                intent_translate(),
-                TRANSFORM_THROTTLE,
-            )))
+        // Use a memo, that way the intent is reverted when the axis or reference changes
+        let action = hooks.use_memo_with((axis, prefs, coplanar), |world, _| {
+            Arc::new(Mutex::new(
+                EditorAction::new(world.resource(runtime()).clone(), game_client.clone(), intent_translate(), &prefs.throttles)
+                    .deferred(prefs.deferred_transforms.then(|| translate_preview(initial_state.transforms.clone()))),
+            ))
         });
+        // After the memo, so that holding alt doesn't restart the action
+        let prefs = if half_snap { prefs.halved_snap() } else { prefs };
 
         let action = Arc::downgrade(&action);
 
@@ -208,14 +577,14 @@ impl ElementComponent for TranslationController {
         let (to_relative, constraints) = match bits.count_ones() {
             1 => {
                 // Line
-                let to_relative = if prefs.use_global_coordinates { Default::default() } else { to_target_local };
+                let to_relative = to_axes;
                 let point = to_relative.transform_point3(initial_state.midpoint);
                 let point = prefs.snap(point);
 
                 (to_relative, ConstraintSpace::Axis { axis: axis_vec, point })
             }
             2 => {
-                let to_relative = if prefs.use_global_coordinates { Default::default() } else { to_target_local };
+                let to_relative = to_axes;
                 let point = to_relative.transform_point3(initial_state.midpoint);
                 let point = prefs.snap(point);
 
@@ -229,16 +598,24 @@ impl ElementComponent for TranslationController {
         };
 
         let from_relative = to_relative.inverse();
+        // Snapping to where construction lines cross moves horizontally, so only when that is free
+        let snap_points = if axis.contains(AxisFlags::X | AxisFlags::Y) && (axis.is_all() || world_axes) {
+            guide_intersections(&guides.0)
+        } else {
+            Vec::new()
+        };
 
         let guide = {
             // Update the guide according to the constraint space
             match constraints {
+                ConstraintSpace::Plane { .. } if !prefs.show_grid => Element::new(),
                 ConstraintSpace::Plane { normal, point } => {
                     // let point = prefs.snap(point);
 
                     // Convert it into world space
                     GridGuide {
-                        rotation: Quat::from_mat4(&from_relative) * Quat::from_rotation_arc(Vec3::Z, normal),
+                        // The grid is symmetric, so a mirrored space can use the closest rotation
+                        rotation: Quat::from_mat4(&to_isometry(from_relative)) * Quat::from_rotation_arc(Vec3::Z, normal),
                         // Transform into world space
                         point: from_relative.transform_point3(point),
                     }
@@ -255,10 +632,36 @@ impl ElementComponent for TranslationController {
             }
         };
 
+        let constraint_axes =
+            ConstraintAxes::el(to_axes.inverse(), initial_state.midpoint, constraint_axes_length(initial_state.bounds), axis);
+
         drop(game_state);
 
+        let coplanar_button = Button::new("\u{f5fd}", move |_| set_coplanar(ReferenceFace::next(coplanar)))
+            .tooltip(match coplanar {
+                Some(face) => format!("Coplanar with the {} face of the active entity", face.label()),
+                None => "Snap coplanar with a face of the active entity".to_string(),
+            })
+            .hotkey(VirtualKeyCode::K)
+            .toggled(coplanar.is_some())
+            .el();
+
+        let snap_readout = match (half_snap, prefs.snap) {
+            (true, Some(size)) => Text::el(format!("Snapping to a {size} m grid while Alt is held")),
+            _ => Element::new(),
+        };
+
+        let reference_guide = match reference_plane {
+            Some((point, normal)) => GridGuide { rotation: Quat::from_rotation_arc(Vec3::Z, normal), point }.el(),
+            None => Element::new(),
+        };
+
         AxisButtons { axis, set_axis }.el().children(vec![Group(vec![
+            coplanar_button,
+            snap_readout,
             guide,
+            constraint_axes,
+            reference_guide,
             HighjackMouse {
                 on_click: {
                     let action = action.clone();
@@ -268,13 +671,16 @@ impl ElementComponent for TranslationController {
                         }
 
                         if let Some(action) = action.upgrade() {
-                            action.lock().confirm();
+                            action.lock().confirm_with(|v| Some(IntentTranslate { round_to: Some(prefs.rounding.step(prefs.snap)?), ..v }));
                         }
 
                         on_click(button)
                     })
                 },
-                on_mouse_move: cb(move |world, _, _| {
+                on_mouse_move: cb(move |world, pos, _| {
+                    if !past_threshold(pos) {
+                        return;
+                    }
                     let game_state = game_client.game_state.lock();
                     let mouse_clip_pos = screen_to_clip_space(world, *world.resource(mouse_position())) - initial_cursor_offset;
 
@@ -296,13 +702,23 @@ impl ElementComponent for TranslationController {
                         }
                     };
 
-                    let position = prefs.snap(position);
+                    let position = match parent_frame {
+                        Some(frame) => to_relative.transform_point3(prefs.snap_in(from_relative.transform_point3(position), frame)),
+                        None => prefs.snap(position),
+                    };
                     let position = constraints.constrain(position);
 
                     // Convert back into world space
                     let position = from_relative.transform_point3(position);
+                    let position = snap_to_points(&snap_points, position).unwrap_or(position);
 
-                    let intent = IntentTranslate { targets: targets.to_vec(), position };
+                    // Slide along the reference normal until the matching face lies on its plane
+                    let position = match (reference_plane, coplanar_offset) {
+                        (Some((point, normal)), Some(offset)) => position - normal * ((position - point).dot(normal) + offset),
+                        _ => position,
+                    };
+
+                    let intent = IntentTranslate { targets: targets.to_vec(), position, surface: prefs.surface, round_to: None };
                     tracing::debug!("Translating: {intent:#?}");
 
                     if let Some(action) = action.upgrade() {
@@ -317,47 +733,62 @@ impl ElementComponent for TranslationController {
     }
 }
 
+/// Rounds the transforms of a committed operation according to the preferences, if enabled
+fn round_committed(intent: IntentTransform, prefs: EditorPrefs) -> Option<IntentTransform> {
+    let step = prefs.rounding.step(prefs.snap)?;
+    Some(IntentTransform { transforms: intent.transforms.into_iter().map(|v| round_transform(v, step)).collect_vec(), ..intent })
+}
+
 #[derive(Debug, Clone)]
 pub(super) struct ScaleController {
     pub targets: Arc<[EntityId]>,
+    pub pivot: Pivot,
     pub on_click: Cb<dyn Fn(MouseButton) + Sync + Send>,
 }
 impl ElementComponent for ScaleController {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
-        let Self { on_click, targets } = *self;
+        let Self { on_click, targets, pivot } = *self;
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+        let (prefs, set_prefs) = hooks.consume_context::<EditorPrefs>().unwrap();
         let runtime = hooks.world.resource(runtime()).clone();
-        let (axis, set_axis) = hooks.use_state(AxisFlags::all());
+        let (axis, set_axis) = use_axis_constraint(hooks);
+        // Holding alt snaps in steps half as large
+        let half_snap = use_alt_held(hooks);
+        let past_threshold = use_drag_threshold(hooks, prefs.click_threshold);
 
         let action = hooks.use_memo_with(axis, |_, _| {
-            Arc::new(Mutex::new(EditorAction::new(runtime, game_client.clone(), intent_set_transform(), TRANSFORM_THROTTLE)))
+            Arc::new(Mutex::new(
+                EditorAction::new(runtime, game_client.clone(), intent_set_transform(), &prefs.throttles)
+                    .deferred(prefs.deferred_transforms.then(set_transform_preview)),
+            ))
         });
 
         let action = Arc::downgrade(&action);
 
         // Freeze to_relative to the position when moving was started
         let state = initial_transforms(hooks, &game_client, targets.clone());
+        let pivot = pivot.point(&game_client.game_state.lock().world, &state);
+        // Scaling is along the world axes
+        let constraint_axes = ConstraintAxes::el(Mat4::IDENTITY, pivot, constraint_axes_length(state.bounds), axis);
 
         let update = {
             let action = action.clone();
             Arc::new(move |pos: Vec2| {
                 let delta = pos.x * 0.01;
+                let factor = if prefs.snap_scale { prefs.scale_snap.snap(1. + delta, half_snap) } else { 1. + delta };
 
                 let mut new_scale = Vec3::ONE;
                 if axis.contains(AxisFlags::X) {
-                    new_scale.x = 1. + delta;
+                    new_scale.x = factor;
                 }
                 if axis.contains(AxisFlags::Y) {
-                    new_scale.y = 1. + delta;
+                    new_scale.y = factor;
                 }
                 if axis.contains(AxisFlags::Z) {
-                    new_scale.z = 1. + delta;
+                    new_scale.z = factor;
                 }
 
-                let to_local = Mat4::from_translation(-state.midpoint);
-                let to_scaled_world = Mat4::from_translation(state.midpoint) * Mat4::from_scale(new_scale);
-
-                let new_transforms = state.transforms.iter().map(|&transform| to_scaled_world * (to_local * transform)).collect_vec();
+                let new_transforms = scale_around(&state.transforms, pivot, new_scale);
 
                 if let Some(action) = action.upgrade() {
                     action.lock().push_intent(IntentTransform {
@@ -369,20 +800,41 @@ impl ElementComponent for ScaleController {
             })
         };
 
-        AxisButtons { axis, set_axis }.el().children(vec![Group(vec![HighjackMouse {
-            on_mouse_move: cb(move |_, pos, _| update(pos)),
-            on_click: cb(move |button| {
-                if button != MouseButton::Left {
-                    return;
-                }
-                if let Some(action) = action.upgrade() {
-                    action.lock().confirm();
-                }
-                on_click(button);
-            }),
-            hide_mouse: false,
-        }
-        .el()])
+        let snap_button = Button::new("\u{f424}", move |_| set_prefs(EditorPrefs { snap_scale: !prefs.snap_scale, ..prefs }))
+            .tooltip(format!("Snap scale to {}", prefs.scale_snap.label().to_lowercase()))
+            .hotkey(VirtualKeyCode::N)
+            .toggled(prefs.snap_scale)
+            .el();
+
+        let snap_readout = if half_snap && prefs.snap_scale {
+            Text::el(format!("Snapping scale to half {} while Alt is held", prefs.scale_snap.label().to_lowercase()))
+        } else {
+            Element::new()
+        };
+
+        AxisButtons { axis, set_axis }.el().children(vec![Group(vec![
+            snap_button,
+            snap_readout,
+            constraint_axes,
+            HighjackMouse {
+                on_mouse_move: cb(move |_, pos, _| {
+                    if past_threshold(pos) {
+                        update(pos)
+                    }
+                }),
+                on_click: cb(move |button| {
+                    if button != MouseButton::Left {
+                        return;
+                    }
+                    if let Some(action) = action.upgrade() {
+                        action.lock().confirm_with(|v| round_committed(v, prefs));
+                    }
+                    on_click(button);
+                }),
+                hide_mouse: false,
+            }
+            .el(),
+        ])
         .el()])
     }
 }
@@ -390,20 +842,26 @@ impl ElementComponent for ScaleController {
 #[derive(Debug, Clone)]
 pub(super) struct RotateController {
     pub targets: Arc<[EntityId]>,
+    pub pivot: Pivot,
     pub on_click: Cb<dyn Fn(MouseButton) + Sync + Send>,
 }
 
 impl ElementComponent for RotateController {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
-        let Self { on_click, targets } = *self;
+        let Self { on_click, targets, pivot } = *self;
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
         let runtime = hooks.world.resource(runtime()).clone();
-        let (axis, set_axis) = hooks.use_state(AxisFlags::all());
+        let (axis, set_axis) = use_axis_constraint(hooks);
 
         let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
-
-        let action = hooks.use_memo_with(axis, |_, _| {
-            Arc::new(Mutex::new(EditorAction::new(runtime, game_client.clone(), intent_set_transform(), TRANSFORM_THROTTLE)))
+        let past_threshold = use_drag_threshold(hooks, prefs.click_threshold);
+
+        // Restarted when the space changes, as the drag so far was along the old axes
+        let action = hooks.use_memo_with((axis, prefs.coordinates), |_, _| {
+            Arc::new(Mutex::new(
+                EditorAction::new(runtime, game_client.clone(), intent_set_transform(), &prefs.throttles)
+                    .deferred(prefs.deferred_transforms.then(set_transform_preview)),
+            ))
         });
 
         let action = Arc::downgrade(&action);
@@ -411,18 +869,12 @@ impl ElementComponent for RotateController {
         // Freeze to_relative to the position when moving was started
         let state = initial_transforms(hooks, &game_client, targets.clone());
 
-        let to_relative = {
-            if prefs.use_global_coordinates {
-                Mat4::IDENTITY
-            } else if let Some(transform) = state.transforms.last() {
-                transform.inverse()
-            } else {
-                Mat4::IDENTITY
-            }
+        let to_relative = match (state.transforms.last(), targets.last()) {
+            (Some(&transform), Some(&id)) => axes_frame(&game_client.game_state.lock().world, prefs.coordinates, id, transform).inverse(),
+            _ => Mat4::IDENTITY,
         };
 
-        let midpoint: Vec3 =
-            state.transforms.iter().map(|v| v.transform_point3(Vec3::ZERO)).fold(Vec3::ZERO, |acc, x| acc + x) / targets.len() as f32;
+        let pivot = pivot.point(&game_client.game_state.lock().world, &state);
 
         let axis = if axis.is_all() {
             AxisFlags::Z
@@ -436,6 +888,8 @@ impl ElementComponent for RotateController {
         let up = from_relative.transform_vector3(Vec3::Z).normalize();
         let right = from_relative.transform_vector3(Vec3::X).normalize();
         let forward = from_relative.transform_vector3(Vec3::Y).normalize();
+        // The axis rotated around is drawn bright
+        let constraint_axes = ConstraintAxes::el(from_relative, pivot, constraint_axes_length(state.bounds), axis);
 
         let update = {
             let action = action.clone();
@@ -450,8 +904,8 @@ impl ElementComponent for RotateController {
 
                 let rot = Quat::from_axis_angle(up, yaw) * Quat::from_axis_angle(right, pitch) * Quat::from_axis_angle(forward, roll);
 
-                let to_local = Mat4::from_translation(-midpoint);
-                let to_rotated_world = Mat4::from_translation(midpoint) * Mat4::from_quat(rot);
+                let to_local = Mat4::from_translation(-pivot);
+                let to_rotated_world = Mat4::from_translation(pivot) * Mat4::from_quat(rot);
 
                 let new_transforms = state.transforms.iter().map(|&transform| to_rotated_world * to_local * transform).collect_vec();
 
@@ -465,28 +919,30 @@ impl ElementComponent for RotateController {
             })
         };
 
-        let mut items = Vec::new();
+        let mut items = vec![constraint_axes];
         if axis.contains(AxisFlags::X) {
-            items.push(AxisGuide { axis: right, point: midpoint }.el())
+            items.push(AxisGuide { axis: right, point: pivot }.el())
         }
         if axis.contains(AxisFlags::Y) {
-            items.push(AxisGuide { axis: forward, point: midpoint }.el())
+            items.push(AxisGuide { axis: forward, point: pivot }.el())
         }
         if axis.contains(AxisFlags::Z) {
-            items.push(AxisGuide { axis: up, point: midpoint }.el())
+            items.push(AxisGuide { axis: up, point: pivot }.el())
         }
 
         items.push(
             HighjackMouse {
                 on_mouse_move: cb(move |_, pos, _| {
-                    update(pos);
+                    if past_threshold(pos) {
+                        update(pos);
+                    }
                 }),
                 on_click: cb(move |button| {
                     if button != MouseButton::Left {
                         return;
                     }
                     if let Some(action) = action.upgrade() {
-                        action.lock().confirm();
+                        action.lock().confirm_with(|v| round_committed(v, prefs));
                     }
                     on_click(button)
                 }),
@@ -569,3 +1025,38 @@ pub fn AxisButtons(_: &mut Hooks, axis: AxisFlags, set_axis: Cb<dyn Fn(AxisFlags
     .el()
     .set(space_between_items(), STREET)
 }
+
+#[cfg(test)]
+mod test {
+    use glam::{Mat4, Quat, Vec3};
+
+    use super::local_frame;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 1e-5), "{a} != {b}");
+    }
+
+    #[test]
+    fn local_frame_follows_mixed_sign_scales() {
+        let rot = Quat::from_rotation_z(0.7) * Quat::from_rotation_x(-0.3);
+        let pos = Vec3::new(3., -2., 5.);
+        for scale in [Vec3::new(-1., 2., 1.), Vec3::new(-1., -1., 1.), Vec3::new(-2., 1., -3.), Vec3::new(0.5, -4., 2.)] {
+            let transform = Mat4::from_scale_rotation_translation(scale, rot, pos);
+            let frame = local_frame(transform);
+
+            // Moving along a handle moves along the axis as drawn, with the sign of the scale
+            for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                assert_close(frame.transform_vector3(axis), transform.transform_vector3(axis).normalize());
+            }
+            assert_close(frame.transform_point3(Vec3::ZERO), pos);
+            assert_close(frame.inverse().transform_point3(transform.transform_point3(Vec3::X)), Vec3::X * scale.x.abs());
+        }
+    }
+
+    #[test]
+    fn local_frame_is_rotation_for_positive_scales() {
+        let transform = Mat4::from_scale_rotation_translation(Vec3::new(2., 3., 4.), Quat::from_rotation_y(1.2), Vec3::ONE);
+        let frame = local_frame(transform);
+        assert!(frame.abs_diff_eq(super::to_isometry(transform), 1e-5));
+    }
+}
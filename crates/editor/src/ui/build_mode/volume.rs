@@ -0,0 +1,253 @@
+use std::{sync::Arc, time::Duration};
+
+use ambient_core::{
+    mouse_position, runtime, screen_to_clip_space,
+    transform::{get_world_transform, translation},
+};
+use ambient_ecs::{EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Group, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_input::event_mouse_input;
+use ambient_network::client::GameClient;
+use ambient_physics::collider::box_collider;
+use ambient_std::{cb, line_hash, shapes::Ray};
+use ambient_ui::{use_interval_deps, HighjackMouse, Text};
+use glam::{vec3, Mat4, Vec3};
+use itertools::Itertools;
+use parking_lot::Mutex;
+use winit::event::{ElementState, MouseButton};
+
+use super::{expression::set_or_add, use_gizmo_scope, EditorAction};
+use crate::{
+    intents::{intent_component_changes, parent_frame},
+    ui::EditorPrefs,
+};
+
+const AXES: [Vec3; 3] = [Vec3::X, Vec3::Y, Vec3::Z];
+const AXIS_COLORS: [Vec3; 3] = [vec3(1., 0.2, 0.2), vec3(0.2, 1., 0.2), vec3(0.2, 0.2, 1.)];
+const HANDLE_RADIUS: f32 = 0.12;
+/// How close the cursor must be to a handle to grab it, relative to the distance of the handle
+const GRAB_TOLERANCE: f32 = 0.03;
+/// The smallest extent a volume can be dragged down to
+const MIN_EXTENT: f32 = 0.01;
+
+fn handles_scope() -> u64 {
+    line_hash!()
+}
+
+/// A face of the box collider of an entity, which is dragged to resize the box along `axis`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FaceHandle {
+    id: EntityId,
+    axis: usize,
+    /// 1 for the face on the positive side of the axis, -1 for the opposite one
+    sign: f32,
+}
+
+impl FaceHandle {
+    /// The world space center of the face
+    fn position(self, transform: Mat4, size: Vec3) -> Vec3 {
+        transform.transform_point3(AXES[self.axis] * self.sign * size[self.axis] / 2.)
+    }
+}
+
+/// The world transform and box collider size of `id`, if it is a box volume
+fn volume(world: &World, id: EntityId) -> Option<(Mat4, Vec3)> {
+    Some((get_world_transform(world, id).ok()?, world.get(id, box_collider()).ok()?))
+}
+
+fn face_handles(world: &World, targets: &[EntityId]) -> Vec<(FaceHandle, Vec3)> {
+    targets
+        .iter()
+        .filter_map(|&id| Some((id, volume(world, id)?)))
+        .flat_map(|(id, (transform, size))| {
+            (0..3).flat_map(move |axis| {
+                [1., -1.].map(|sign| {
+                    let handle = FaceHandle { id, axis, sign };
+                    (handle, handle.position(transform, size))
+                })
+            })
+        })
+        .collect_vec()
+}
+
+/// The handle closest to `ray`, if any is within grabbing distance
+fn grabbed_handle(handles: &[(FaceHandle, Vec3)], ray: Ray) -> Option<FaceHandle> {
+    handles
+        .iter()
+        .filter_map(|&(handle, position)| {
+            let along = (position - ray.origin).dot(ray.dir);
+            let off = (position - (ray.origin + ray.dir * along)).length() / along;
+            (along > 0. && off < GRAB_TOLERANCE).then_some((handle, off))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(handle, _)| handle)
+}
+
+/// How far along the line through `point` in `dir` the point closest to `ray` lies
+fn closest_along(point: Vec3, dir: Vec3, ray: Ray) -> Option<f32> {
+    let b = dir.dot(ray.dir);
+    let denom = 1. - b * b;
+    if denom.abs() < 1e-4 {
+        return None;
+    }
+    let w = point - ray.origin;
+    Some((b * w.dot(ray.dir) - w.dot(dir)) / denom)
+}
+
+/// A drag of a face, with the state of the volume when it started
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    handle: FaceHandle,
+    transform: Mat4,
+    size: Vec3,
+    translation: Vec3,
+    parent: Option<Mat4>,
+}
+
+impl Drag {
+    /// Resizes the volume so that the dragged face follows `ray`, keeping the opposite face in place
+    fn resize(&self, ray: Ray, prefs: EditorPrefs) -> Option<(Vec3, Vec3)> {
+        let FaceHandle { axis, sign, .. } = self.handle;
+        let outward = self.transform.transform_vector3(AXES[axis] * sign);
+        let scale = outward.length();
+        let t = closest_along(self.handle.position(self.transform, self.size), outward / scale, ray)?;
+
+        let mut size = self.size;
+        size[axis] = self.size[axis] + t / scale;
+        if let Some(snap) = prefs.snap {
+            size[axis] = (size[axis] / snap).round() * snap;
+        }
+        size[axis] = size[axis].max(MIN_EXTENT);
+
+        let shift = self.transform.transform_vector3(AXES[axis] * sign * (size[axis] - self.size[axis]) / 2.);
+        let shift = match self.parent {
+            Some(parent) => parent.inverse().transform_vector3(shift),
+            None => shift,
+        };
+        Some((size, self.translation + shift))
+    }
+}
+
+fn extents_label(size: Vec3) -> String {
+    format!("{:.2} x {:.2} x {:.2} m", size.x, size.y, size.z)
+}
+
+/// Resizes the box colliders of the selection, such as trigger volumes, by dragging handles on
+/// their faces. The collider size is edited rather than the scale, so that the scale stays at 1
+#[element_component]
+pub(super) fn VolumeController(hooks: &mut Hooks, targets: Arc<[EntityId]>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+
+    let action = hooks.use_memo_with(targets.clone(), |world, _| {
+        Arc::new(Mutex::new(EditorAction::new(
+            world.resource(runtime()).clone(),
+            game_client.clone(),
+            intent_component_changes(),
+            &prefs.throttles,
+        )))
+    });
+    let drag = hooks.use_ref_with::<Option<Drag>>(|_| None);
+    // Where the cursor last pointed, as clicks carry no position
+    let last_ray = hooks.use_ref_with::<Option<Ray>>(|_| None);
+    let (dragged, set_dragged) = hooks.use_state(None as Option<(FaceHandle, Vec3)>);
+
+    use_interval_deps(hooks, Duration::from_millis(50), true, (targets.clone(), dragged.map(|(handle, _)| handle)), {
+        let game_state = game_client.game_state.clone();
+        move |(targets, dragged)| {
+            let state = game_state.lock();
+            let mut scope = state.world.resource(gizmos()).scope(handles_scope());
+            for (handle, position) in face_handles(&state.world, targets) {
+                let color = if Some(handle) == *dragged { Vec3::ONE } else { AXIS_COLORS[handle.axis] };
+                scope.draw(GizmoPrimitive::sphere(position, HANDLE_RADIUS).with_color(color));
+            }
+        }
+    });
+    use_gizmo_scope(hooks, handles_scope());
+
+    hooks.use_world_event({
+        let action = action.clone();
+        let drag = drag.clone();
+        let set_dragged = set_dragged.clone();
+        move |_, event| {
+            if let Some(event) = event.get_ref(event_mouse_input()) {
+                if event.button == MouseButton::Left && event.state == ElementState::Released && drag.lock().take().is_some() {
+                    action.lock().confirm();
+                    set_dragged(None);
+                }
+            }
+        }
+    });
+
+    let readout = match dragged {
+        Some((handle, size)) => format!("Extents {}, dragging along {}", extents_label(size), ["X", "Y", "Z"][handle.axis]),
+        None => {
+            let state = game_client.game_state.lock();
+            match targets.iter().rev().find_map(|&id| state.world.get(id, box_collider()).ok()) {
+                Some(size) => format!("Extents {}, drag a face to resize", extents_label(size)),
+                None => "The selection has no box volumes to resize".to_string(),
+            }
+        }
+    };
+
+    Group(vec![
+        Text::el(readout),
+        HighjackMouse {
+            on_click: {
+                let game_client = game_client.clone();
+                let drag = drag.clone();
+                let set_dragged = set_dragged.clone();
+                let last_ray = last_ray.clone();
+                cb(move |button| {
+                    if button != MouseButton::Left {
+                        return;
+                    }
+                    let ray = match *last_ray.lock() {
+                        Some(v) => v,
+                        None => return,
+                    };
+                    let state = game_client.game_state.lock();
+                    let handle = match grabbed_handle(&face_handles(&state.world, &targets), ray) {
+                        Some(v) => v,
+                        None => return,
+                    };
+                    let (transform, size) = match volume(&state.world, handle.id) {
+                        Some(v) => v,
+                        None => return,
+                    };
+                    let translation = state.world.get(handle.id, translation()).unwrap_or_default();
+                    let parent = parent_frame(&state.world, handle.id);
+                    *drag.lock() = Some(Drag { handle, transform, size, translation, parent });
+                    set_dragged(Some((handle, size)));
+                })
+            },
+            on_mouse_move: cb(move |world, _, _| {
+                let ray = game_client.game_state.lock().screen_ray(screen_to_clip_space(world, *world.resource(mouse_position())));
+                *last_ray.lock() = Some(ray);
+                let drag = match *drag.lock() {
+                    Some(v) => v,
+                    None => return,
+                };
+                let (size, position) = match drag.resize(ray, prefs) {
+                    Some(v) => v,
+                    None => return,
+                };
+
+                set_dragged(Some((drag.handle, size)));
+                let id = drag.handle.id;
+                let changes = {
+                    let state = game_client.game_state.lock();
+                    vec![
+                        (id, set_or_add(&state.world, id, box_collider(), size)),
+                        (id, set_or_add(&state.world, id, translation(), position)),
+                    ]
+                };
+                action.lock().push_intent(changes);
+            }),
+            hide_mouse: false,
+        }
+        .el(),
+    ])
+    .el()
+}
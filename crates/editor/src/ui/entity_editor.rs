@@ -20,12 +20,12 @@ use ambient_ui::{
     space_between_items, use_interval_deps, Align, Button, ButtonStyle, DropdownSelect, Editor, EditorPrompt, FlowColumn, FlowRow,
     ScreenContainer, StylesExt, Text, STREET,
 };
-use glam::{Vec2, Vec3, Vec4};
+use glam::{vec3, Vec2, Vec3, Vec4};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use super::EditingEntityContext;
-use crate::intents::intent_component_change;
+use crate::{intents::intent_component_change, organization_color, review_marker, transform_pinned};
 
 #[tracing::instrument(level = "info", skip_all)]
 #[element_component]
@@ -56,9 +56,41 @@ pub fn EntityEditor(hooks: &mut Hooks, entity_id: EntityId) -> Element {
 
     if let Some(entity) = entity {
         let _translation = entity.get_cloned(translation());
+        let pinned = entity.contains(transform_pinned());
+        let marked = entity.contains(review_marker());
         FlowColumn(vec![
             Text::el(name).section_style(),
             if let Some(mass) = entity.get(mass()) { Text::el(format!("{mass} kg")).small_style() } else { Element::new() },
+            Button::new("\u{f08d} Pin transform", {
+                let runtime = runtime.clone();
+                let game_client = game_client.clone();
+                move |_| {
+                    let change = if pinned {
+                        EntityComponentChange::Remove(transform_pinned().into())
+                    } else {
+                        EntityComponentChange::Add(ComponentEntry::new(transform_pinned(), ()))
+                    };
+                    runtime.spawn(client_push_intent(game_client.clone(), intent_component_change(), (entity_id, change), None, None));
+                }
+            })
+            .tooltip("Keep the editor from moving, rotating or scaling this entity")
+            .toggled(pinned)
+            .el(),
+            Button::new("\u{f024} Mark for review", {
+                let runtime = runtime.clone();
+                let game_client = game_client.clone();
+                move |_| {
+                    let change = if marked {
+                        EntityComponentChange::Remove(review_marker().into())
+                    } else {
+                        EntityComponentChange::Add(ComponentEntry::new(review_marker(), String::new()))
+                    };
+                    runtime.spawn(client_push_intent(game_client.clone(), intent_component_change(), (entity_id, change), None, None));
+                }
+            })
+            .tooltip("Keep this entity highlighted for everyone until the marker is removed")
+            .toggled(marked)
+            .el(),
             EntityComponentsEditor {
                 value: entity,
                 on_change: cb(move |change| {
@@ -168,6 +200,10 @@ fn EntityComponentsEditor(_hooks: &mut Hooks, value: Entity, on_change: Cb<dyn F
     let mut component_editors = [
         reg_default_component!("Name", true, name()),
         reg_default_component!("Tags", false, tags()),
+        reg_component(&value, on_change.clone(), &mut missing_components, "Organization color", true, organization_color(), || {
+            vec3(1., 0.5, 0.)
+        }),
+        reg_default_component!("Review marker", false, review_marker()),
         reg_default_component!("Translation", true, translation()),
         reg_default_component!("Scale", true, scale()),
         // reg_default_component!("Model", false, model_def()),
@@ -1,5 +1,6 @@
-use std::{collections::HashMap, fmt::Debug, io::Cursor};
+use std::{collections::HashMap, fmt::Debug, io::Cursor, time::Duration};
 
+mod autosave;
 mod build_mode;
 pub mod entity_editor;
 mod terrain_mode;
@@ -7,7 +8,7 @@ mod terrain_mode;
 use ambient_core::{game_mode, runtime, transform::translation, GameMode};
 use ambient_ecs::{Entity, EntityId};
 use ambient_element::{element_component, Element, ElementComponent, ElementComponentExt, Group, Hooks, Setter};
-use ambient_intent::{rpc_redo, rpc_undo_head, IntentHistoryVisualizer};
+use ambient_intent::{rpc_redo, rpc_undo_head, use_intent_history, IntentHistoryVisualizer};
 use ambient_naturals::{get_default_natural_layers, natural_layers, NaturalLayer, NaturalsPreset};
 use ambient_network::{
     client::GameClient,
@@ -26,17 +27,21 @@ use ambient_terrain::{
 use ambient_ui::{
     command_modifier, height,
     layout::{docking, space_between_items, width, Borders, Docking},
-    margin, use_interval, use_window_logical_resolution, Button, Editor, FlowColumn, FlowRow, FontAwesomeIcon, Hotkey, Rectangle,
-    ScreenContainer, ScrollArea, Separator, StylesExt, Text, UIExt, WindowSized, STREET,
+    margin, use_interval, use_window_logical_resolution, Button, Editor, FlowColumn, FlowRow, FontAwesomeIcon, Hotkey,
+    HotkeysYieldToInputs, Rectangle, ScreenContainer, ScrollArea, Separator, StylesExt, Text, UIExt, WindowSized, STREET,
 };
+use autosave::*;
 use build_mode::*;
-use glam::{vec3, Vec3};
+use glam::{vec3, Mat4, Vec2, Vec3, Vec4};
 use image::{DynamicImage, ImageOutputFormat, RgbImage};
 use itertools::Itertools;
 use terrain_mode::*;
 use winit::event::{ModifiersState, VirtualKeyCode};
 
-use crate::{selection, Selection};
+use crate::{
+    intents::{intent_place_ray, intent_set_transform, intent_stamp, intent_translate, SurfaceConstraint},
+    selection, Selection,
+};
 use serde::{de::DeserializeOwned, Serialize};
 
 pub fn use_player_selection(hooks: &mut Hooks) -> (Selection, Setter<Selection>) {
@@ -50,13 +55,553 @@ impl EditorPrefs {
             Some(snap) => (pos / snap).round() * snap,
         }
     }
+
+    /// The color applied to entities previewing a placement
+    pub fn preview_tint(self) -> Vec4 {
+        self.preview_color.extend(self.preview_opacity)
+    }
+
+    /// The prefs with the snap increment sized to a selection `size` across, when the increment is
+    /// automatic. It's rounded to a power of two, so that objects of about the same size share a grid
+    pub fn sized_to(self, size: Option<f32>) -> Self {
+        match (self.snap, self.snap_increment.fraction(), size) {
+            (Some(_), Some(fraction), Some(size)) if size > 0. => Self { snap: Some(2f32.powf((size * fraction).log2().round())), ..self },
+            _ => self,
+        }
+    }
+
+    /// The prefs with the snap increment halved, for snapping between the cells of the grid
+    pub fn halved_snap(self) -> Self {
+        Self { snap: self.snap.map(|snap| snap / 2.), ..self }
+    }
+
+    /// Snaps the world space `pos` on the grid of `frame`, such as the world transform of a parent
+    pub fn snap_in(self, pos: Vec3, frame: Mat4) -> Vec3 {
+        frame.transform_point3(self.snap(frame.inverse().transform_point3(pos)))
+    }
 }
 
-#[derive(Default, Copy, Debug, Clone, PartialEq)]
+#[derive(Copy, Debug, Clone, PartialEq)]
 /// Saves the options for the build mode and other editors
 struct EditorPrefs {
-    pub use_global_coordinates: bool,
+    /// The axes the transform controls move and turn along
+    pub coordinates: CoordinateSpace,
     pub snap: Option<f32>,
+    /// Show the grid while translating, independently of `snap`
+    pub show_grid: bool,
+    /// Snap on the local grid of the parent of the selection rather than the world grid
+    pub snap_in_parent: bool,
+    /// How often the selection is re-resolved against the world, to catch external deletions
+    pub resolve_interval: Duration,
+    /// Change the cursor to reflect the active transform mode
+    pub mode_cursors: bool,
+    /// Re-enter the last used transform mode when the selection changes
+    pub keep_transform_mode: bool,
+    /// The mode entered on a new selection, unless `keep_transform_mode` restores another one
+    pub default_transform_mode: DefaultTransformMode,
+    /// The mode entered once a box selection selects more than one entity
+    pub box_select_mode: DefaultTransformMode,
+    pub delete_hotkey: DeleteHotkey,
+    pub input_layout: InputLayout,
+    /// Where new objects are created while something is selected
+    pub spawn_placement: SpawnPlacement,
+    /// The gap between the selection and an object created beside it
+    pub spawn_gap: f32,
+    /// Render entities outside the selection at `ghost_opacity`
+    pub ghost_unselected: bool,
+    pub ghost_opacity: f32,
+    /// How far in pixels the cursor has to move before translating, rotating or scaling pushes a
+    /// change, so that an imprecise click only exits the mode
+    pub click_threshold: f32,
+    /// How far in pixels the cursor has to be dragged from a click in the viewport before it draws a
+    /// selection box rather than selecting what is under the cursor
+    pub box_select_threshold: f32,
+    /// The tint of the entities being placed or stamped, and the color of gizmo previews such as arrays
+    pub preview_color: Vec3,
+    /// How opaque the entities being placed or stamped are. Gizmo previews are always drawn opaque
+    pub preview_opacity: f32,
+    /// Show lines along the world axes and a marker at the origin
+    pub show_world_axes: bool,
+    /// Show the camera position and orientation
+    pub show_camera_hud: bool,
+    /// Show which way the camera faces relative to the world axes
+    pub show_compass: bool,
+    /// Show a box around the whole selection along with its size
+    pub show_selection_bounds: bool,
+    /// Show a top-down map of the scene
+    pub show_minimap: bool,
+    /// How far the camera moves per press of the keyboard dolly and pan hotkeys
+    pub camera_step: f32,
+    /// How often the scene is snapshotted for recovering from a crash
+    pub autosave_interval: Duration,
+    /// Quantize the scale factor when scaling, independently of `snap`
+    pub snap_scale: bool,
+    /// Turn placed entities to stand off the surface they are placed on, such as walls
+    pub auto_orient: bool,
+    pub scale_snap: ScaleSnap,
+    /// Scale the snap increment with the size of the selection, rather than the fixed grid size
+    pub snap_increment: SnapIncrement,
+    /// How translated entities follow the scene geometry below them
+    pub surface: SurfaceConstraint,
+    /// Which entities to outline when their bounds overlap
+    pub overlaps: OverlapCheck,
+    pub throttles: ActionThrottles,
+    /// Only move a local preview while translating, rotating or scaling, and push the result on
+    /// release rather than streaming it, for slow connections
+    pub deferred_transforms: bool,
+    /// How the transforms are rounded when an operation is committed
+    pub rounding: CommitRounding,
+    pub double_click: DoubleClickAction,
+    /// Operations creating more entities than this ask for confirmation first
+    pub spawn_warning: u32,
+    /// Batched operations on more entities than this, such as bulk deletes, ask for confirmation first
+    pub batch_warning: u32,
+    pub selection_highlight: SelectionHighlight,
+}
+
+impl Default for EditorPrefs {
+    fn default() -> Self {
+        Self {
+            coordinates: CoordinateSpace::Local,
+            snap: None,
+            show_grid: true,
+            snap_in_parent: false,
+            resolve_interval: Duration::from_millis(2000),
+            mode_cursors: true,
+            keep_transform_mode: false,
+            default_transform_mode: DefaultTransformMode::None,
+            box_select_mode: DefaultTransformMode::None,
+            delete_hotkey: DeleteHotkey::Backspace,
+            input_layout: InputLayout::NumberRow,
+            spawn_placement: SpawnPlacement::Cursor,
+            spawn_gap: 0.,
+            ghost_unselected: false,
+            ghost_opacity: 0.3,
+            click_threshold: 3.,
+            box_select_threshold: 5.,
+            preview_color: vec3(0.25, 0.75, 1.),
+            preview_opacity: 0.6,
+            show_world_axes: false,
+            show_camera_hud: false,
+            show_compass: false,
+            show_selection_bounds: false,
+            show_minimap: false,
+            camera_step: 0.25,
+            autosave_interval: Duration::from_secs(60),
+            snap_scale: false,
+            auto_orient: false,
+            scale_snap: ScaleSnap::PowersOfTwo,
+            snap_increment: SnapIncrement::Fixed,
+            surface: SurfaceConstraint::Free,
+            overlaps: OverlapCheck::Off,
+            throttles: ActionThrottles::default(),
+            deferred_transforms: false,
+            rounding: CommitRounding::Off,
+            double_click: DoubleClickAction::FocusCamera,
+            spawn_warning: 500,
+            batch_warning: 50,
+            selection_highlight: SelectionHighlight::Uniform,
+        }
+    }
+}
+
+/// The values a scale factor is snapped to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleSnap {
+    /// 0.25, 0.5, 1, 2, 4 and so on
+    PowersOfTwo,
+    /// Multiples of 0.25
+    Quarters,
+    /// Multiples of 0.1
+    Tenths,
+}
+
+impl ScaleSnap {
+    pub const ALL: [ScaleSnap; 3] = [ScaleSnap::PowersOfTwo, ScaleSnap::Quarters, ScaleSnap::Tenths];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScaleSnap::PowersOfTwo => "Powers of two",
+            ScaleSnap::Quarters => "Quarters",
+            ScaleSnap::Tenths => "Tenths",
+        }
+    }
+
+    /// Snaps a scale factor, never snapping it to zero or below. When `halved`, the steps are half
+    /// as large, with powers of two stepping by powers of the square root of two
+    pub fn snap(self, factor: f32, halved: bool) -> f32 {
+        let steps = if halved { 2. } else { 1. };
+        match self {
+            ScaleSnap::PowersOfTwo => 2f32.powf((factor.max(0.01).log2() * steps).round() / steps),
+            ScaleSnap::Quarters => ((factor * 4. * steps).round() / (4. * steps)).max(0.25 / steps),
+            ScaleSnap::Tenths => ((factor * 10. * steps).round() / (10. * steps)).max(0.1 / steps),
+        }
+    }
+}
+
+/// The axes the transform controls move and turn along
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoordinateSpace {
+    /// The axes of the world
+    World,
+    /// The axes of the active entity
+    Local,
+    /// The axes of the parent of the active entity, or of the world when it has no parent
+    Parent,
+}
+
+impl CoordinateSpace {
+    pub const ALL: [CoordinateSpace; 3] = [CoordinateSpace::World, CoordinateSpace::Local, CoordinateSpace::Parent];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CoordinateSpace::World => "World",
+            CoordinateSpace::Local => "Local",
+            CoordinateSpace::Parent => "Parent",
+        }
+    }
+
+    /// The space after this one, for cycling through them with a hotkey
+    pub fn next(self) -> Self {
+        match self {
+            CoordinateSpace::World => CoordinateSpace::Local,
+            CoordinateSpace::Local => CoordinateSpace::Parent,
+            CoordinateSpace::Parent => CoordinateSpace::World,
+        }
+    }
+}
+
+/// How large the snap increment is while snapping is on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapIncrement {
+    /// The size set on the grid
+    Fixed,
+    /// Half of the size of the selection
+    Half,
+    /// A quarter of the size of the selection
+    Quarter,
+    /// An eighth of the size of the selection
+    Eighth,
+}
+
+impl SnapIncrement {
+    pub const ALL: [SnapIncrement; 4] = [SnapIncrement::Fixed, SnapIncrement::Half, SnapIncrement::Quarter, SnapIncrement::Eighth];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SnapIncrement::Fixed => "Fixed grid",
+            SnapIncrement::Half => "Auto, half the selection",
+            SnapIncrement::Quarter => "Auto, a quarter of the selection",
+            SnapIncrement::Eighth => "Auto, an eighth of the selection",
+        }
+    }
+
+    /// The part of the size of the selection the increment is, or `None` for the fixed grid
+    pub fn fraction(self) -> Option<f32> {
+        match self {
+            SnapIncrement::Fixed => None,
+            SnapIncrement::Half => Some(0.5),
+            SnapIncrement::Quarter => Some(0.25),
+            SnapIncrement::Eighth => Some(0.125),
+        }
+    }
+}
+
+/// What double-clicking an entity in the viewport does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoubleClickAction {
+    /// Move the camera to look at the entity
+    FocusCamera,
+    /// Select the children of the entity
+    EnterGroup,
+    /// Select only the entity, which shows its components in the inspector
+    OpenInspector,
+    /// Select every instance of the object the entity was spawned from
+    SelectLinked,
+}
+
+impl DoubleClickAction {
+    pub const ALL: [DoubleClickAction; 4] =
+        [DoubleClickAction::FocusCamera, DoubleClickAction::EnterGroup, DoubleClickAction::OpenInspector, DoubleClickAction::SelectLinked];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DoubleClickAction::FocusCamera => "Focus camera",
+            DoubleClickAction::EnterGroup => "Enter group",
+            DoubleClickAction::OpenInspector => "Open inspector",
+            DoubleClickAction::SelectLinked => "Select linked",
+        }
+    }
+}
+
+/// The transform mode entered as soon as something is selected, so that its gizmo shows without
+/// pressing a mode key first
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefaultTransformMode {
+    None,
+    Translate,
+    Rotate,
+    Scale,
+}
+
+impl DefaultTransformMode {
+    pub const ALL: [DefaultTransformMode; 4] =
+        [DefaultTransformMode::None, DefaultTransformMode::Translate, DefaultTransformMode::Rotate, DefaultTransformMode::Scale];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DefaultTransformMode::None => "None",
+            DefaultTransformMode::Translate => "Translate",
+            DefaultTransformMode::Rotate => "Rotate",
+            DefaultTransformMode::Scale => "Scale",
+        }
+    }
+}
+
+/// The key which deletes the selection, as a bare Backspace is easy to press by accident
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeleteHotkey {
+    Backspace,
+    ShiftBackspace,
+    /// Only the toolbar button deletes
+    None,
+}
+
+impl DeleteHotkey {
+    pub const ALL: [DeleteHotkey; 3] = [DeleteHotkey::Backspace, DeleteHotkey::ShiftBackspace, DeleteHotkey::None];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DeleteHotkey::Backspace => "Backspace",
+            DeleteHotkey::ShiftBackspace => "Shift+Backspace",
+            DeleteHotkey::None => "None",
+        }
+    }
+}
+
+/// A ready-made set of hotkeys for the transform modes and the select mode, so that they can be
+/// reached with the hand which isn't on the mouse
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputLayout {
+    NumberRow,
+    /// For the right hand on the numpad, with the mouse in the left hand
+    Numpad,
+}
+
+impl InputLayout {
+    pub const ALL: [InputLayout; 2] = [InputLayout::NumberRow, InputLayout::Numpad];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputLayout::NumberRow => "Number row",
+            InputLayout::Numpad => "Numpad",
+        }
+    }
+
+    /// The keys of the transform modes and terrain brushes, in the order of their toolbar buttons
+    pub fn mode_keys(self) -> [VirtualKeyCode; 6] {
+        match self {
+            InputLayout::NumberRow => [
+                VirtualKeyCode::Key1,
+                VirtualKeyCode::Key2,
+                VirtualKeyCode::Key3,
+                VirtualKeyCode::Key4,
+                VirtualKeyCode::Key5,
+                VirtualKeyCode::Key6,
+            ],
+            InputLayout::Numpad => [
+                VirtualKeyCode::Numpad1,
+                VirtualKeyCode::Numpad2,
+                VirtualKeyCode::Numpad3,
+                VirtualKeyCode::Numpad4,
+                VirtualKeyCode::Numpad5,
+                VirtualKeyCode::Numpad6,
+            ],
+        }
+    }
+
+    /// The key which cycles the sticky select mode
+    pub fn select_mode_key(self) -> VirtualKeyCode {
+        match self {
+            InputLayout::NumberRow => VirtualKeyCode::B,
+            InputLayout::Numpad => VirtualKeyCode::NumpadAdd,
+        }
+    }
+}
+
+/// Where a new object is created while something is selected, for building out from what is
+/// already there
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnPlacement {
+    /// Under the cursor, and then placed with the mouse
+    Cursor,
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    Above,
+}
+
+impl SpawnPlacement {
+    pub const ALL: [SpawnPlacement; 6] = [
+        SpawnPlacement::Cursor,
+        SpawnPlacement::PositiveX,
+        SpawnPlacement::NegativeX,
+        SpawnPlacement::PositiveY,
+        SpawnPlacement::NegativeY,
+        SpawnPlacement::Above,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SpawnPlacement::Cursor => "At the cursor",
+            SpawnPlacement::PositiveX => "Beside selection, +X",
+            SpawnPlacement::NegativeX => "Beside selection, -X",
+            SpawnPlacement::PositiveY => "Beside selection, +Y",
+            SpawnPlacement::NegativeY => "Beside selection, -Y",
+            SpawnPlacement::Above => "Above selection",
+        }
+    }
+
+    /// The world direction from the selection to the new object
+    pub fn direction(self) -> Option<Vec3> {
+        match self {
+            SpawnPlacement::Cursor => None,
+            SpawnPlacement::PositiveX => Some(Vec3::X),
+            SpawnPlacement::NegativeX => Some(Vec3::NEG_X),
+            SpawnPlacement::PositiveY => Some(Vec3::Y),
+            SpawnPlacement::NegativeY => Some(Vec3::NEG_Y),
+            SpawnPlacement::Above => Some(Vec3::Z),
+        }
+    }
+}
+
+/// How committed positions and scales are rounded, to keep drift such as 2.9999998 out of the
+/// scene data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitRounding {
+    Off,
+    /// Round to this many decimal places
+    Decimals(u32),
+    /// Round to the snap size, or not at all when snapping is off
+    Snap,
+}
+
+impl CommitRounding {
+    pub const ALL: [CommitRounding; 5] =
+        [CommitRounding::Off, CommitRounding::Decimals(2), CommitRounding::Decimals(3), CommitRounding::Decimals(4), CommitRounding::Snap];
+
+    pub fn label(self) -> String {
+        match self {
+            CommitRounding::Off => "Off".to_string(),
+            CommitRounding::Decimals(decimals) => format!("{decimals} decimal places"),
+            CommitRounding::Snap => "Snap size".to_string(),
+        }
+    }
+
+    /// The increment values are rounded to, if any
+    pub fn step(self, snap: Option<f32>) -> Option<f32> {
+        match self {
+            CommitRounding::Off => None,
+            CommitRounding::Decimals(decimals) => Some(10f32.powi(-(decimals as i32))),
+            CommitRounding::Snap => snap,
+        }
+    }
+}
+
+/// How long an `EditorAction` waits between pushes, for each kind of operation.
+///
+/// Drags tolerate coarse throttling, which keeps the network load down, while discrete edits
+/// should be pushed at once
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActionThrottles {
+    /// Translating, rotating and scaling with the mouse
+    pub drag: Duration,
+    /// Placing along the cursor ray and stamping copies
+    pub place: Duration,
+    /// Any other operation
+    pub discrete: Duration,
+}
+
+impl Default for ActionThrottles {
+    fn default() -> Self {
+        Self { drag: Duration::from_millis(60), place: Duration::from_millis(60), discrete: Duration::ZERO }
+    }
+}
+
+impl ActionThrottles {
+    /// The throttle of actions which push the intent with the component index `intent`
+    pub fn get(&self, intent: u32) -> Duration {
+        if [intent_translate().index(), intent_set_transform().index()].contains(&intent) {
+            self.drag
+        } else if [intent_place_ray().index(), intent_stamp().index()].contains(&intent) {
+            self.place
+        } else {
+            self.discrete
+        }
+    }
+}
+
+/// Which overlapping entities are outlined, to catch intersecting placements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapCheck {
+    Off,
+    /// Any two entities in the scene
+    Scene,
+    /// Entities overlapping the selection
+    Selection,
+}
+
+impl OverlapCheck {
+    pub const ALL: [OverlapCheck; 3] = [OverlapCheck::Off, OverlapCheck::Scene, OverlapCheck::Selection];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OverlapCheck::Off => "Off",
+            OverlapCheck::Scene => "Whole scene",
+            OverlapCheck::Selection => "Selection only",
+        }
+    }
+}
+
+/// A vertical construction plane, drawn as a line on the plan of the scene. Placing and moving snap
+/// to where two of them cross
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstructionLine {
+    /// A point on the line, of which only the horizontal position matters
+    pub point: Vec3,
+    /// The horizontal direction of the line
+    pub dir: Vec2,
+}
+
+/// The construction lines of this session. Like the reference image, they are not saved with the
+/// scene
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConstructionGuides(pub Vec<ConstructionLine>);
+
+/// How bright the outline of each selected entity is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionHighlight {
+    /// Only entities with an organization color are outlined, all equally bright
+    Uniform,
+    /// Every selected entity is outlined, brighter the later it was selected, so the active
+    /// entity is the brightest
+    Recency,
+    /// Every selected entity is outlined, brighter the more other selected entities its bounds
+    /// overlap
+    OverlapDepth,
+}
+
+impl SelectionHighlight {
+    pub const ALL: [SelectionHighlight; 3] = [SelectionHighlight::Uniform, SelectionHighlight::Recency, SelectionHighlight::OverlapDepth];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SelectionHighlight::Uniform => "Organization colors only",
+            SelectionHighlight::Recency => "By selection order",
+            SelectionHighlight::OverlapDepth => "By overlap depth",
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -88,9 +633,13 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
     let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
     let (hide_ui, set_hide_ui) = hooks.use_state(false);
     let (user_settings, _) = hooks.consume_context::<EditorSettings>().unwrap();
-    let (screen, _set_screen) = hooks.use_state(None);
+    let (screen, set_screen) = hooks.use_state(None);
+    let history = use_intent_history(hooks);
 
     hooks.provide_context(EditorPrefs::default);
+    // Typing a dimension or a name shouldn't switch tools or delete the selection
+    hooks.provide_context(|| HotkeysYieldToInputs);
+    hooks.provide_context(ConstructionGuides::default);
 
     hooks.provide_context(|| Brush::Raise);
     hooks.provide_context(|| 0u32);
@@ -133,6 +682,7 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
 
     Group(vec![
         Crosshair.el(),
+        Autosave::el(set_screen),
         WindowSized(vec![
             ScreenContainer(screen).el(),
             FlowColumn::el([FlowRow::el([
@@ -202,7 +752,8 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
                 })
                 .hotkey(VirtualKeyCode::Z)
                 .hotkey_modifier(command_modifier())
-                .tooltip("Undo")
+                .tooltip(history.undo.as_ref().map_or_else(|| "Nothing to undo".to_string(), |label| format!("Undo {label}")))
+                .disabled(history.undo.is_none())
                 .el(),
                 Button::new_async(FontAwesomeIcon::el(0xf2f9, true), move || {
                     let game_client = game_client.clone();
@@ -212,7 +763,8 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
                 })
                 .hotkey(VirtualKeyCode::Z)
                 .hotkey_modifier(command_modifier() | ModifiersState::SHIFT)
-                .tooltip("Redo")
+                .tooltip(history.redo.as_ref().map_or_else(|| "Nothing to redo".to_string(), |label| format!("Redo {label}")))
+                .disabled(history.redo.is_none())
                 .el(),
                 ServerInstancesInfo.el(),
             ])
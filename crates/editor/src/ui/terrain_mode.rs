@@ -36,7 +36,7 @@ use glam::{vec3, Vec3, Vec3Swizzles, Vec4};
 use wgpu::{util::DeviceExt, BindGroup};
 use winit::event::VirtualKeyCode;
 
-use super::EditorPlayerInputHandler;
+use super::{EditorPlayerInputHandler, EditorPrefs};
 
 #[derive(Clone, Debug)]
 pub struct TerrainRaycastPicker {
@@ -290,27 +290,29 @@ impl ElementComponent for EditorTerrainMode {
         let (brush_shape, set_brush_shape) = hooks.consume_context::<BrushShape>().unwrap();
         let (brush_smoothness, set_brush_smoothness) = hooks.consume_context::<BrushSmoothness>().unwrap();
         let (erosion_config, _set_erosion_config) = hooks.consume_context::<HydraulicErosionConfig>().unwrap();
+        let (prefs, _) = hooks.consume_context::<EditorPrefs>().unwrap();
+        let mode_keys = prefs.input_layout.mode_keys();
 
         let mut items = vec![
             EditorPlayerInputHandler.el(),
             Button::new_value(FontAwesomeIcon::el(0xf35b, true), brush, set_brush.clone(), Brush::Raise)
-                .hotkey(VirtualKeyCode::Key1)
+                .hotkey(mode_keys[0])
                 .tooltip("Raise")
                 .el(),
             Button::new_value(FontAwesomeIcon::el(0xf358, true), brush, set_brush.clone(), Brush::Lower)
-                .hotkey(VirtualKeyCode::Key2)
+                .hotkey(mode_keys[1])
                 .tooltip("Lower")
                 .el(),
             Button::new_value(FontAwesomeIcon::el(0xf056, true), brush, set_brush.clone(), Brush::Flatten)
-                .hotkey(VirtualKeyCode::Key3)
+                .hotkey(mode_keys[2])
                 .tooltip("Flatten")
                 .el(),
             Button::new_value(FontAwesomeIcon::el(0xf043, true), brush, set_brush.clone(), Brush::Erode)
-                .hotkey(VirtualKeyCode::Key4)
+                .hotkey(mode_keys[3])
                 .tooltip("Hydraulic Erosion")
                 .el(),
             Button::new_value(FontAwesomeIcon::el(0xf185, true), brush, set_brush.clone(), Brush::Thermal)
-                .hotkey(VirtualKeyCode::Key5)
+                .hotkey(mode_keys[4])
                 .tooltip("Thermal Erosion")
                 .el(),
             Separator { vertical: true }.el(),
@@ -4,8 +4,8 @@ mod registry;
 use std::{fmt::Debug, sync::Arc};
 
 use ambient_ecs::{
-    components, index_system, query, ArchetypeFilter, Component, ComponentValue, Debuggable, Entity, EntityId, Index, IndexColumns,
-    Networked, QueryState, Resource, Store, SystemGroup,
+    components, index_system, query, with_component_registry, ArchetypeFilter, Component, ComponentValue, Debuggable, Entity, EntityId,
+    Index, IndexColumns, Networked, QueryState, Resource, Store, SystemGroup,
 };
 use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
 use ambient_network::{
@@ -193,6 +193,55 @@ impl ElementComponent for IntentVisualizer {
     }
 }
 
+/// A human-friendly label for an intent kind, such as "Set transform" for `intent_set_transform`
+pub fn intent_label(intent: u32) -> String {
+    let desc = match with_component_registry(|r| r.get_by_index(intent)) {
+        Some(v) => v,
+        None => return "Unknown".to_string(),
+    };
+
+    desc.name().unwrap_or_else(|| {
+        let path = desc.path_last();
+        let words = path.strip_prefix("intent_").unwrap_or(&path).replace('_', " ");
+        let mut chars = words.chars();
+        chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+    })
+}
+
+/// The labels of the intents which undo and redo would act on for the local user
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntentHistoryState {
+    pub undo: Option<String>,
+    pub redo: Option<String>,
+}
+
+/// Mirrors the intent history of the local user, matching what [`rpc_undo_head`] and [`rpc_redo`]
+/// would pick
+pub fn use_intent_history(hooks: &mut Hooks) -> IntentHistoryState {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (history, set_history) = hooks.use_state(IntentHistoryState::default());
+
+    use_remote_world_system(hooks, query((intent(), intent_user_id(), intent_timestamp())), move |q, world, qs, _| {
+        let intents = q
+            .iter(world, qs)
+            .filter(|(_, (_, user_id, _))| **user_id == game_client.user_id)
+            .map(|(id, (&kind, _, &timestamp))| (id, kind, timestamp))
+            .collect_vec();
+
+        let (reverted, active): (Vec<_>, Vec<_>) = intents.into_iter().partition(|(id, _, _)| world.has_component(*id, intent_reverted()));
+
+        let new_history = IntentHistoryState {
+            undo: active.iter().max_by_key(|(_, _, timestamp)| *timestamp).map(|&(_, kind, _)| intent_label(kind)),
+            redo: reverted.iter().min_by_key(|(_, _, timestamp)| *timestamp).map(|&(_, kind, _)| intent_label(kind)),
+        };
+        if new_history != history {
+            set_history(new_history);
+        }
+    });
+
+    history
+}
+
 /// Helper functions for collapsing absolute state intents
 pub fn use_old_state<T: Clone + Debug, U: Clone + Debug>(_old_arg: &T, old_state: &U, new_arg: &T, _new_state: &U) -> (T, U) {
     (new_arg.clone(), old_state.clone())
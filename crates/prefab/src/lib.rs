@@ -24,7 +24,7 @@ components!("prefab", {
     ]
     prefab_from_url: String,
     @[
-        Debuggable,
+        Debuggable, Networked,
         Name["Spawned"],
         Description["If attached, this entity was built from a prefab that has finished spawning."]
     ]
@@ -37,7 +37,7 @@ pub fn systems() -> SystemGroup {
         vec![query(prefab_from_url()).spawned().to_system(|q, world, qs, _| {
             let mut to_load = HashMap::<String, Vec<EntityId>>::new();
             for (id, url) in q.collect_cloned(world, qs) {
-                let url = if url.ends_with("/prefabs/main.json") { url } else { format!("{url}/prefabs/main.json") };
+                let url = prefab_main_url(url);
                 to_load.entry(url).or_default().push(id);
             }
             for (url, ids) in to_load {
@@ -63,6 +63,15 @@ pub fn systems() -> SystemGroup {
     )
 }
 
+/// Resolves a `prefab_from_url` value, which may point to the prefab directory, to its main json file
+pub fn prefab_main_url(url: String) -> String {
+    if url.ends_with("/prefabs/main.json") {
+        url
+    } else {
+        format!("{url}/prefabs/main.json")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PrefabFromUrl(pub AssetUrl);
 #[async_trait]
@@ -24,8 +24,8 @@ use winit::{
 
 use super::{FlowColumn, FlowRow, Text, UIBase, UIElement, UIExt};
 use crate::{
-    border_color, border_radius, border_thickness, cutout_color, font_style, layout::*, primary_color, secondary_color, Corners, FontStyle,
-    Tooltip,
+    border_color, border_radius, border_thickness, cutout_color, font_style, layout::*, primary_color, secondary_color, Corners, Focus,
+    FontStyle, Tooltip,
 };
 
 #[derive(Clone, Debug)]
@@ -372,6 +372,11 @@ impl Button {
     }
 }
 
+/// Provided by apps which want the [`Hotkey`]s below them to ignore key presses while a text
+/// input has focus, so that typing into the input doesn't trigger them
+#[derive(Clone, Copy, Debug)]
+pub struct HotkeysYieldToInputs;
+
 #[derive(Clone, Debug)]
 pub struct Hotkey {
     pub hotkey: VirtualKeyCode,
@@ -393,6 +398,8 @@ impl ElementComponent for Hotkey {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
         let Self { on_is_pressed_changed, content, hotkey, hotkey_modifier, on_invoke } = *self;
         let (is_pressed, _) = hooks.use_state_with(|_| Arc::new(AtomicBool::new(false)));
+        let input_focused = hooks.consume_context::<HotkeysYieldToInputs>().is_some()
+            && hooks.consume_context::<Focus>().map_or(false, |(focus, _)| focus.0.is_some());
         hooks.use_world_event({
             let is_pressed = is_pressed;
             move |world, event| {
@@ -400,7 +407,7 @@ impl ElementComponent for Hotkey {
                     if let KeyboardEvent { keycode: Some(virtual_keycode), state, modifiers, .. } = event {
                         if virtual_keycode == &hotkey {
                             if state == &ElementState::Pressed {
-                                if modifiers == &hotkey_modifier {
+                                if modifiers == &hotkey_modifier && !input_focused {
                                     if let Some(on_is_pressed_changed) = on_is_pressed_changed.clone() {
                                         on_is_pressed_changed.0(true);
                                     }
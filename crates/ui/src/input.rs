@@ -8,7 +8,7 @@ use ambient_core::{
 };
 use ambient_ecs::{ComponentValue, EntityId};
 use ambient_element::{define_el_function_for_vec_element_newtype, Element, ElementComponent, ElementComponentExt, Hooks};
-use ambient_input::{event_mouse_input, event_mouse_motion};
+use ambient_input::{event_keyboard_input, event_mouse_input, event_mouse_motion, KeyboardEvent};
 use ambient_std::{
     cb,
     math::{interpolate, interpolate_clamped},
@@ -16,7 +16,10 @@ use ambient_std::{
 };
 use glam::*;
 use itertools::Itertools;
-use winit::{event::ElementState, window::CursorIcon};
+use winit::{
+    event::{ElementState, VirtualKeyCode},
+    window::CursorIcon,
+};
 
 use super::{Editor, EditorOpts, FlowColumn, FlowRow, Focus, Text, UIBase, UIExt};
 use crate::{
@@ -101,6 +104,75 @@ pub type U32Input = ParseableInput<u32>;
 pub type U64Input = ParseableInput<u64>;
 pub type UsizeInput = ParseableInput<usize>;
 
+/// An input for a number of items, which only accepts whole numbers of at least `min`.
+///
+/// While focused, the up and down arrow keys step the count by one.
+#[derive(Debug, Clone)]
+pub struct CountInput {
+    pub value: u32,
+    pub min: u32,
+    pub on_change: Cb<dyn Fn(u32) + Sync + Send>,
+}
+impl CountInput {
+    pub fn new(value: u32, on_change: impl Fn(u32) + Sync + Send + 'static) -> Self {
+        Self { value, min: 1, on_change: cb(on_change) }
+    }
+    pub fn min(mut self, min: u32) -> Self {
+        self.min = min;
+        self
+    }
+}
+impl ElementComponent for CountInput {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let Self { value, min, on_change } = *self;
+        let (self_id, set_self_id) = hooks.use_state(EntityId::null());
+        let (focus, _) = hooks.consume_context::<Focus>().expect("No FocusRoot found");
+        let focused = focus == Focus(Some(self_id));
+        let (text, set_text) = hooks.use_state(None);
+        if focused && text.is_none() {
+            set_text(Some(value.to_string()));
+        } else if !focused && text.is_some() {
+            set_text(None);
+        }
+        hooks.use_world_event({
+            let on_change = on_change.clone();
+            let set_text = set_text.clone();
+            move |_, event| {
+                if !focused {
+                    return;
+                }
+                if let Some(KeyboardEvent { keycode: Some(keycode), state: ElementState::Pressed, .. }) =
+                    event.get_ref(event_keyboard_input())
+                {
+                    let value = match keycode {
+                        VirtualKeyCode::Up => value.saturating_add(1),
+                        VirtualKeyCode::Down => value.saturating_sub(1).max(min),
+                        _ => return,
+                    };
+                    set_text(Some(value.to_string()));
+                    on_change.0(value);
+                }
+            }
+        });
+        TextInput::new(
+            text.unwrap_or_else(|| value.to_string()),
+            cb(move |text: String| {
+                if !text.chars().all(|c| c.is_ascii_digit()) {
+                    return;
+                }
+                if let Ok(value) = text.parse::<u32>() {
+                    if value >= min {
+                        on_change.0(value);
+                    }
+                }
+                set_text(Some(text));
+            }),
+        )
+        .el()
+        .on_spawned(move |_, id| set_self_id(id))
+    }
+}
+
 impl Editor for Duration {
     fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
         CustomParseInput {
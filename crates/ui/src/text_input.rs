@@ -24,6 +24,8 @@ pub fn TextInput(
     on_submit: Option<Cb<dyn Fn(String) + Sync + Send>>,
     password: bool,
     placeholder: Option<String>,
+    /// Take the focus when spawned, so that typing can start right away
+    autofocus: bool,
 ) -> Element {
     let (self_id, set_self_id) = hooks.use_state(EntityId::null());
     let (focus, set_focus) = hooks.consume_context::<Focus>().expect("No FocusRoot available");
@@ -93,7 +95,12 @@ pub fn TextInput(
     .set(fit_vertical(), Fit::None)
     .set(min_width(), 3.)
     .set(min_height(), 13.)
-    .on_spawned(move |_, id| set_self_id(id))
+    .on_spawned(closure!(clone set_focus, |_, id| {
+        set_self_id(id);
+        if autofocus {
+            set_focus(Focus(Some(id)));
+        }
+    }))
     .with_clickarea()
     .on_mouse_up(move |_, id, _| {
         set_focus(Focus(Some(id)));
@@ -115,7 +122,7 @@ pub fn TextInput(
 
 impl TextInput {
     pub fn new(value: String, on_change: Cb<dyn Fn(String) + Sync + Send>) -> Self {
-        Self { value, on_change, on_submit: None, password: false, placeholder: None }
+        Self { value, on_change, on_submit: None, password: false, placeholder: None, autofocus: false }
     }
     pub fn on_submit(mut self, on_submit: impl Fn(String) + Sync + Send + 'static) -> Self {
         self.on_submit = Some(cb(on_submit));
@@ -129,6 +136,10 @@ impl TextInput {
         self.password = true;
         self
     }
+    pub fn autofocus(mut self) -> Self {
+        self.autofocus = true;
+        self
+    }
 }
 
 impl Editor for String {